@@ -1 +1,60 @@
 //! CLIインターフェースを提供するモジュール
+//!
+//! # 責務
+//!
+//! - コマンドライン引数のパース（clap）
+//! - サブコマンドのディスパッチと終了コードの決定
+//!
+//! # モジュール構成
+//!
+//! - [`args`][]: 引数定義（[`args::Cli`], [`args::Command`]）
+//! - [`commands`][]: 各サブコマンドの実装
+//! - [`diff`][]: `adw runs diff` の実行結果の比較
+//! - [`progress`][]: `adw run` の進捗表示
+//!
+//! # 使用例
+//!
+//! ```text
+//! adw run workflows/example.toml --input "新しい認証機能を実装してください"
+//! adw run workflows/example.toml --input @requirements.md
+//! echo "要件" | adw run workflows/example.toml --input -
+//! adw resume 20251016-093012-3f2a
+//! adw runs list
+//! adw runs show 20251016-093012-3f2a --prompts
+//! adw runs diff 20251016-093012-3f2a 20251016-101544-91c0
+//! adw validate workflows/
+//! ```
+
+pub mod args;
+pub mod commands;
+pub mod diff;
+pub mod progress;
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use args::{Cli, Command};
+
+/// CLIのエントリーポイント
+///
+/// 引数をパースし、対応するサブコマンドを実行します。
+/// エラーは標準エラー出力に表示し、非0の終了コードを返します。
+pub async fn run() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Run(args) => commands::run(args).await,
+        Command::Resume(args) => commands::resume(args).await,
+        Command::Validate(args) => commands::validate(args),
+        Command::Runs(args) => commands::runs(args),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("エラー: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
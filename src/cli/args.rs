@@ -0,0 +1,386 @@
+//! コマンドライン引数の定義
+//!
+//! # 責務
+//!
+//! clap の derive API を用いて、`adw` コマンドの引数構造を定義する。
+//! 引数の解釈（ファイル読み込み等）は [`commands`](super::commands) が担当する。
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::engine::executor::DEFAULT_MAX_CONCURRENCY;
+
+/// 実行ごとのディレクトリの保存先の既定値
+pub const DEFAULT_RUNS_DIR: &str = ".adw/runs";
+
+/// 応答キャッシュの保存先の既定値
+pub const DEFAULT_CACHE_DIR: &str = ".adw/cache";
+
+/// 応答キャッシュの有効期限（秒）の既定値
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Melted ADW - Agent Development Workflow Builder
+#[derive(Debug, Parser)]
+#[command(name = "adw", version, about)]
+pub struct Cli {
+    /// 実行するサブコマンド
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// サブコマンド
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// ワークフローを実行する
+    Run(RunArgs),
+
+    /// 失敗した実行を、完了したステップの出力を再利用して再開する
+    Resume(ResumeArgs),
+
+    /// ワークフロー定義を検証し、すべての問題を報告する
+    Validate(ValidateArgs),
+
+    /// 保存した実行の一覧・ステップごとの出力・2つの実行の差分を表示する
+    Runs(RunsArgs),
+}
+
+/// `adw run` の引数
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// ワークフロー定義ファイル（TOML）
+    pub workflow: PathBuf,
+
+    /// 最初のステップへの入力
+    ///
+    /// `@path` でファイルから、`-` で標準入力から読み込みます。
+    #[arg(short, long, value_name = "TEXT|@FILE|-")]
+    pub input: Option<String>,
+
+    /// 実行の設定
+    #[command(flatten)]
+    pub execution: ExecutionArgs,
+}
+
+/// `adw resume` の引数
+#[derive(Debug, Args)]
+pub struct ResumeArgs {
+    /// 再開する実行の実行ID（`adw run` の出力や `--runs-dir` 配下のディレクトリ名）
+    pub run_id: String,
+
+    /// ワークフロー定義ファイル（既定: チェックポイントに記録されたパス）
+    #[arg(long, value_name = "PATH")]
+    pub workflow: Option<PathBuf>,
+
+    /// 実行の設定
+    #[command(flatten)]
+    pub execution: ExecutionArgs,
+}
+
+/// `adw run` と `adw resume` に共通の実行の設定
+#[derive(Debug, Args)]
+pub struct ExecutionArgs {
+    /// 実行結果をJSON形式で標準出力に出力する
+    #[arg(long)]
+    pub json: bool,
+
+    /// ユーザー設定ファイル（既定: `~/.config/adw/config.toml`、存在する場合のみ）
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// 同時に実行するステップ数の上限（`depends_on` で分岐したステップを並列実行）
+    #[arg(
+        short = 'j',
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_CONCURRENCY,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_concurrency: usize,
+
+    /// 実行ごとのディレクトリ（実行履歴とチェックポイント）の保存先
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_RUNS_DIR)]
+    pub runs_dir: PathBuf,
+
+    /// 実行履歴とチェックポイントを保存しない（`adw resume` と `adw runs` で参照できなくなります）
+    #[arg(long)]
+    pub no_run_store: bool,
+
+    /// プロバイダー・モデル・プロンプトが同じ呼び出しに、保存した応答を使う（LLM を呼び出さず、コストは0）
+    #[arg(long)]
+    pub cache: bool,
+
+    /// 応答キャッシュの保存先
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_CACHE_DIR, requires = "cache")]
+    pub cache_dir: PathBuf,
+
+    /// 応答キャッシュの有効期限（秒）
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = DEFAULT_CACHE_TTL_SECS,
+        requires = "cache",
+        value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub cache_ttl: u64,
+
+    /// テレメトリー（実行記録）の出力先ディレクトリ
+    #[arg(long, value_name = "DIR", default_value = "telemetry")]
+    pub telemetry_dir: PathBuf,
+
+    /// 実行記録を追記する DuckDB ファイル（`duckdb` フィーチャー）
+    #[cfg(feature = "duckdb")]
+    #[arg(long, value_name = "PATH", conflicts_with = "no_telemetry")]
+    pub telemetry_db: Option<PathBuf>,
+
+    /// テレメトリーを出力しない
+    #[arg(long, conflicts_with = "telemetry_dir")]
+    pub no_telemetry: bool,
+
+    /// 実行中の進捗（生成中のテキストやツール呼び出し）を標準エラー出力に表示しない
+    #[arg(long)]
+    pub no_progress: bool,
+}
+
+/// `adw validate` の引数
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// 検証するワークフロー定義ファイルまたはディレクトリ
+    ///
+    /// ディレクトリを指定した場合、配下の `.toml` ファイルを再帰的に検証します。
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+}
+
+/// `adw runs` の引数
+#[derive(Debug, Args)]
+pub struct RunsArgs {
+    /// 実行ごとのディレクトリの保存先
+    #[arg(long, value_name = "DIR", default_value = DEFAULT_RUNS_DIR, global = true)]
+    pub runs_dir: PathBuf,
+
+    /// 実行するサブコマンド
+    #[command(subcommand)]
+    pub command: RunsCommand,
+}
+
+/// `adw runs` のサブコマンド
+#[derive(Debug, Subcommand)]
+pub enum RunsCommand {
+    /// 保存した実行を新しい順に一覧表示する
+    List(RunsListArgs),
+
+    /// 実行の結果とステップごとの出力を表示する
+    Show(RunsShowArgs),
+
+    /// 同じワークフローの2つの実行をステップごとに比較する
+    Diff(RunsDiffArgs),
+}
+
+/// `adw runs list` の引数
+#[derive(Debug, Args)]
+pub struct RunsListArgs {
+    /// 指定したワークフロー名の実行のみ表示する
+    #[arg(long, value_name = "NAME")]
+    pub workflow: Option<String>,
+}
+
+/// `adw runs show` の引数
+#[derive(Debug, Args)]
+pub struct RunsShowArgs {
+    /// 表示する実行の実行ID
+    pub run_id: String,
+
+    /// ステップごとのシステムプロンプトと入力も表示する
+    #[arg(long, conflicts_with = "json")]
+    pub prompts: bool,
+
+    /// 保存した実行結果をJSON形式で標準出力に出力する
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// `adw runs diff` の引数
+#[derive(Debug, Args)]
+pub struct RunsDiffArgs {
+    /// 比較元の実行ID
+    pub base: String,
+
+    /// 比較先の実行ID
+    pub target: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_run_minimal() {
+        let cli = Cli::try_parse_from(["adw", "run", "workflow.toml"]).unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+
+        assert_eq!(args.workflow, PathBuf::from("workflow.toml"));
+        assert!(args.input.is_none());
+        assert!(!args.execution.json);
+        assert!(args.execution.config.is_none());
+        assert_eq!(args.execution.runs_dir, PathBuf::from(DEFAULT_RUNS_DIR));
+        assert!(!args.execution.no_run_store);
+        assert!(!args.execution.cache);
+        assert_eq!(args.execution.cache_ttl, DEFAULT_CACHE_TTL_SECS);
+        assert_eq!(args.execution.telemetry_dir, PathBuf::from("telemetry"));
+        assert!(!args.execution.no_telemetry);
+        assert!(!args.execution.no_progress);
+        assert_eq!(args.execution.max_concurrency, DEFAULT_MAX_CONCURRENCY);
+    }
+
+    #[test]
+    fn test_parse_run_no_progress() {
+        let cli = Cli::try_parse_from(["adw", "run", "workflow.toml", "--no-progress"]).unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+        assert!(args.execution.no_progress);
+    }
+
+    #[test]
+    fn test_parse_run_max_concurrency() {
+        let cli = Cli::try_parse_from(["adw", "run", "workflow.toml", "-j", "2"]).unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+        assert_eq!(args.execution.max_concurrency, 2);
+
+        let result = Cli::try_parse_from(["adw", "run", "workflow.toml", "--max-concurrency", "0"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_run_with_input() {
+        let cli = Cli::try_parse_from([
+            "adw", "run", "workflow.toml", "--input", "@requirements.md", "--json", "--config", "adw.toml",
+        ])
+        .unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+
+        assert_eq!(args.input, Some("@requirements.md".to_string()));
+        assert!(args.execution.json);
+        assert_eq!(args.execution.config, Some(PathBuf::from("adw.toml")));
+    }
+
+    #[test]
+    fn test_parse_run_telemetry_options() {
+        let cli = Cli::try_parse_from([
+            "adw", "run", "workflow.toml", "--telemetry-dir", "out/telemetry",
+        ])
+        .unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+        assert_eq!(args.execution.telemetry_dir, PathBuf::from("out/telemetry"));
+
+        let cli = Cli::try_parse_from(["adw", "run", "workflow.toml", "--no-telemetry"]).unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+        assert!(args.execution.no_telemetry);
+
+        let result = Cli::try_parse_from([
+            "adw", "run", "workflow.toml", "--no-telemetry", "--telemetry-dir", "x",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_run_cache_options() {
+        let cli = Cli::try_parse_from([
+            "adw", "run", "workflow.toml", "--cache", "--cache-dir", "out/cache", "--cache-ttl", "60",
+        ])
+        .unwrap();
+        let Command::Run(args) = cli.command else {
+            panic!("Expected run command");
+        };
+        assert!(args.execution.cache);
+        assert_eq!(args.execution.cache_dir, PathBuf::from("out/cache"));
+        assert_eq!(args.execution.cache_ttl, 60);
+
+        // キャッシュの設定は --cache と一緒に指定する
+        assert!(Cli::try_parse_from(["adw", "run", "workflow.toml", "--cache-ttl", "60"]).is_err());
+        assert!(Cli::try_parse_from(["adw", "run", "workflow.toml", "--cache", "--cache-ttl", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_resume() {
+        let cli = Cli::try_parse_from(["adw", "resume", "20251016-093012-3f2a", "--runs-dir", "out/runs"]).unwrap();
+        let Command::Resume(args) = cli.command else {
+            panic!("Expected resume command");
+        };
+        assert_eq!(args.run_id, "20251016-093012-3f2a");
+        assert!(args.workflow.is_none());
+        assert_eq!(args.execution.runs_dir, PathBuf::from("out/runs"));
+
+        let cli = Cli::try_parse_from(["adw", "resume", "run", "--workflow", "workflow.toml", "--json"]).unwrap();
+        let Command::Resume(args) = cli.command else {
+            panic!("Expected resume command");
+        };
+        assert_eq!(args.workflow, Some(PathBuf::from("workflow.toml")));
+        assert!(args.execution.json);
+
+        assert!(Cli::try_parse_from(["adw", "resume"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_runs() {
+        let cli = Cli::try_parse_from(["adw", "runs", "list", "--workflow", "implement"]).unwrap();
+        let Command::Runs(args) = cli.command else {
+            panic!("Expected runs command");
+        };
+        assert_eq!(args.runs_dir, PathBuf::from(DEFAULT_RUNS_DIR));
+        assert!(matches!(args.command, RunsCommand::List(list) if list.workflow.as_deref() == Some("implement")));
+
+        // --runs-dir はサブコマンドの後にも指定できる
+        let cli = Cli::try_parse_from(["adw", "runs", "show", "run", "--prompts", "--runs-dir", "out/runs"]).unwrap();
+        let Command::Runs(args) = cli.command else {
+            panic!("Expected runs command");
+        };
+        assert_eq!(args.runs_dir, PathBuf::from("out/runs"));
+        assert!(matches!(args.command, RunsCommand::Show(show) if show.run_id == "run" && show.prompts && !show.json));
+
+        let cli = Cli::try_parse_from(["adw", "runs", "diff", "a", "b"]).unwrap();
+        let Command::Runs(args) = cli.command else {
+            panic!("Expected runs command");
+        };
+        assert!(matches!(args.command, RunsCommand::Diff(diff) if diff.base == "a" && diff.target == "b"));
+
+        assert!(Cli::try_parse_from(["adw", "runs", "diff", "a"]).is_err());
+        assert!(Cli::try_parse_from(["adw", "runs", "show", "run", "--prompts", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_run_missing_workflow() {
+        let result = Cli::try_parse_from(["adw", "run"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_validate_multiple_paths() {
+        let cli = Cli::try_parse_from(["adw", "validate", "workflows", "extra.toml"]).unwrap();
+        let Command::Validate(args) = cli.command else {
+            panic!("Expected validate command");
+        };
+
+        assert_eq!(
+            args.paths,
+            vec![PathBuf::from("workflows"), PathBuf::from("extra.toml")]
+        );
+    }
+
+    #[test]
+    fn test_parse_validate_requires_path() {
+        let result = Cli::try_parse_from(["adw", "validate"]);
+        assert!(result.is_err());
+    }
+}
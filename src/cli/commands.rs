@@ -0,0 +1,540 @@
+//! サブコマンドの実装
+//!
+//! # 責務
+//!
+//! - `adw run`: ワークフローを読み込んで実行し、結果を表示する
+//! - `adw resume`: 失敗した実行をチェックポイントから再開し、結果を表示する
+//! - `adw validate`: ワークフロー定義を検証し、すべての問題を位置情報付きで表示する
+//! - `adw runs list/show/diff`: 保存した実行の一覧・ステップごとの出力・2つの実行の差分を表示する
+//!
+//! 各コマンドは [`ExitCode`] を返します。ワークフローが成功しなかった場合は
+//! [`ExitCode::FAILURE`] となり、シェルスクリプトやCIから失敗を検知できます。
+
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::user::UserConfig;
+use crate::config::validate;
+use crate::config::workflow::Workflow;
+use crate::engine::{Checkpoint, ExecutionStatus, RunStore, StepResult, StepStatus, WorkflowExecutor, WorkflowResult};
+use crate::engine::run_store::RunSummary;
+use crate::error::{CliError, RunStoreError};
+use crate::provider::cache::ResponseCache;
+use crate::telemetry::JsonExporter;
+
+use super::args::{
+    ExecutionArgs, ResumeArgs, RunArgs, RunsArgs, RunsCommand, RunsDiffArgs, RunsListArgs, RunsShowArgs, ValidateArgs,
+};
+use super::diff;
+use super::progress::StderrProgress;
+
+/// `adw run` を実行
+///
+/// # 処理フロー
+///
+/// 1. [`Workflow::from_file`] でワークフロー定義を、[`UserConfig`] でユーザー設定（`--config`）を読み込む
+/// 2. `--input` を解決して初期入力を設定
+/// 3. [`WorkflowExecutor::execute`] で実行（`--no-run-store` でない限り実行履歴とチェックポイントを保存、
+///    `--cache` で応答キャッシュを使用、
+///    `--no-telemetry` でない限り実行記録を出力、`--no-progress` でない限り進捗を標準エラー出力に表示）
+/// 4. ステップごとの結果（または `--json` 指定時はJSON）を出力
+///
+/// # 戻り値
+///
+/// - `Ok(ExitCode::SUCCESS)`: ワークフローが成功した場合
+/// - `Ok(ExitCode::FAILURE)`: ワークフローが成功しなかった場合
+/// - `Err(CliError)`: 読み込みまたは実行に失敗した場合
+pub async fn run(args: RunArgs) -> Result<ExitCode, CliError> {
+    let workflow = Workflow::from_file(&args.workflow)?;
+    let mut executor = build_executor(workflow, &args.workflow, &args.execution)?;
+    if let Some(spec) = &args.input {
+        executor = executor.with_initial_input(read_input(spec)?);
+    }
+
+    let result = executor.execute().await?;
+    report(&result, &args.execution)
+}
+
+/// `adw resume` を実行
+///
+/// # 処理フロー
+///
+/// 1. `--runs-dir` 配下から実行IDのチェックポイント（[`Checkpoint`]）を読み込む
+/// 2. `--workflow`（省略時はチェックポイントに記録されたパス）からワークフロー定義を読み込む
+/// 3. [`WorkflowExecutor::resume_from`] で、完了したステップの出力を再利用して再開
+///    （ワークフロー定義の完了したステップが変更されている場合はエラー）
+/// 4. `adw run` と同様に結果を出力
+///
+/// 再開した実行には新しい実行IDが割り当てられます。
+///
+/// # 戻り値
+///
+/// - `Ok(ExitCode::SUCCESS)`: ワークフローが成功した場合
+/// - `Ok(ExitCode::FAILURE)`: ワークフローが成功しなかった場合
+/// - `Err(CliError)`: チェックポイント・定義の読み込みに失敗した、または定義に互換性がない場合
+pub async fn resume(args: ResumeArgs) -> Result<ExitCode, CliError> {
+    let checkpoint = Checkpoint::load(&args.execution.runs_dir, &args.run_id)?;
+    let path = args
+        .workflow
+        .clone()
+        .or_else(|| checkpoint.workflow_path.clone())
+        .ok_or_else(|| CliError::WorkflowPathUnknown(args.run_id.clone()))?;
+
+    let workflow = Workflow::from_file(&path)?;
+    let executor = build_executor(workflow, &path, &args.execution)?;
+
+    let result = executor.resume_from(&checkpoint).await?;
+    report(&result, &args.execution)
+}
+
+/// `adw run` と `adw resume` に共通の設定でエグゼキューターを生成
+///
+/// ユーザー設定（`--config`）を読み込み、同時実行数・実行履歴・応答キャッシュ・進捗表示・テレメトリーを設定します。
+/// チェックポイントには定義ファイルの絶対パスを記録し、別のディレクトリからも再開できるようにします。
+fn build_executor(workflow: Workflow, path: &Path, args: &ExecutionArgs) -> Result<WorkflowExecutor, CliError> {
+    let user_config = match &args.config {
+        Some(path) => UserConfig::from_file(path)?,
+        None => UserConfig::load_default()?,
+    };
+
+    let mut executor = WorkflowExecutor::new(workflow)
+        .with_max_concurrency(args.max_concurrency)
+        .with_user_models(user_config.models().clone())
+        .with_user_prices(user_config.prices().clone());
+    if !args.no_run_store {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        executor = executor
+            .with_run_store(RunStore::new(args.runs_dir.clone()))
+            .with_workflow_path(path);
+    }
+    if args.cache {
+        let cache = ResponseCache::new(args.cache_dir.clone()).with_ttl(Duration::from_secs(args.cache_ttl));
+        executor = executor.with_response_cache(cache);
+    }
+    if !args.no_progress {
+        executor = executor.with_progress_sink(Arc::new(StderrProgress::new()));
+    }
+    if !args.no_telemetry {
+        executor = executor.with_exporter(Arc::new(JsonExporter::new(args.telemetry_dir.clone())));
+    }
+    #[cfg(feature = "duckdb")]
+    if let Some(path) = &args.telemetry_db {
+        let exporter = crate::telemetry::DuckDbExporter::open(path)?;
+        executor = executor.with_exporter(Arc::new(exporter));
+    }
+    Ok(executor)
+}
+
+/// 実行結果（または `--json` 指定時はJSON）を出力し、終了コードを決定
+///
+/// 失敗した実行は、チェックポイントを保存していれば再開のコマンドを表示します。
+fn report(result: &WorkflowResult, args: &ExecutionArgs) -> Result<ExitCode, CliError> {
+    if args.json {
+        println!("{}", result.to_json()?);
+    } else {
+        print_summary(result);
+        if !result.is_success() && !args.no_run_store {
+            println!("Resume: adw resume {}", result.run_id);
+        }
+    }
+
+    Ok(if result.is_success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// `adw validate` を実行
+///
+/// 指定されたファイル（ディレクトリの場合は配下の `.toml` ファイル）を検証し、
+/// 問題を `path:line:column: message` 形式で標準エラー出力に表示します。
+/// pre-commit フックからの利用を想定し、問題が1つでもあれば失敗を返します。
+///
+/// # 戻り値
+///
+/// - `Ok(ExitCode::SUCCESS)`: すべてのファイルに問題がない場合
+/// - `Ok(ExitCode::FAILURE)`: 問題が見つかった、または読み込めないファイルがあった場合
+/// - `Err(CliError)`: ディレクトリの走査に失敗した場合
+pub fn validate(args: ValidateArgs) -> Result<ExitCode, CliError> {
+    let mut files = Vec::new();
+    for path in &args.paths {
+        files.extend(validate::find_workflow_files(path)?);
+    }
+
+    let mut error_count = 0;
+    for file in &files {
+        match validate::validate_file(file) {
+            Ok(diagnostics) => {
+                for diagnostic in &diagnostics {
+                    eprintln!("{}:{}", file.display(), diagnostic);
+                }
+                error_count += diagnostics.len();
+            }
+            Err(e) => {
+                eprintln!("{}: {}", file.display(), e);
+                error_count += 1;
+            }
+        }
+    }
+
+    println!("{} file(s) checked, {} error(s)", files.len(), error_count);
+
+    Ok(if error_count == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// `adw runs` を実行
+///
+/// `--runs-dir` 配下に保存した実行履歴（[`RunStore`]）を読み込み、サブコマンドに応じて表示します。
+///
+/// - `list`: 実行を新しい順に一覧表示（`--workflow` でワークフロー名を絞り込み）
+/// - `show`: 実行結果の要約と、ステップごとの出力（`--prompts` でシステムプロンプトと入力も）を表示
+/// - `diff`: 同じワークフローの2つの実行を、ステップと繰り返し回数ごとに比較して表示
+///
+/// # 戻り値
+///
+/// - `Ok(ExitCode::SUCCESS)`: 表示した場合（差分の有無によらない）
+/// - `Err(CliError)`: 実行が見つからない、または異なるワークフローの実行を比較しようとした場合
+pub fn runs(args: RunsArgs) -> Result<ExitCode, CliError> {
+    let store = RunStore::new(args.runs_dir);
+    match args.command {
+        RunsCommand::List(list) => runs_list(&store, &list)?,
+        RunsCommand::Show(show) => runs_show(&store, &show)?,
+        RunsCommand::Diff(diff) => runs_diff(&store, &diff)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `adw runs list` を実行
+fn runs_list(store: &RunStore, args: &RunsListArgs) -> Result<(), CliError> {
+    let runs: Vec<RunSummary> = store
+        .list()?
+        .into_iter()
+        .filter(|run| args.workflow.as_ref().is_none_or(|name| &run.workflow_name == name))
+        .collect();
+    if runs.is_empty() {
+        println!("No runs in {}", store.dir().display());
+        return Ok(());
+    }
+
+    println!(
+        "{:<20}  {:<20}  {:<10}  {:>5}  {:>8}  {:>9}  DURATION",
+        "RUN ID", "WORKFLOW", "STATUS", "STEPS", "TOKENS", "COST"
+    );
+    for run in &runs {
+        let steps = match run.total_steps {
+            Some(total) => format!("{}/{}", run.completed_steps, total),
+            None => format!("{}/-", run.completed_steps),
+        };
+        let duration = run
+            .total_duration
+            .map_or_else(|| "-".to_string(), |duration| format!("{:.2?}", duration));
+        let resumed = run
+            .resumed_from
+            .as_ref()
+            .map_or_else(String::new, |run_id| format!("  (resumed from {})", run_id));
+        println!(
+            "{:<20}  {:<20}  {:<10}  {:>5}  {:>8}  {:>9}  {}{}",
+            run.run_id,
+            run.workflow_name,
+            format_run_status(run.status.as_ref()),
+            steps,
+            run.total_tokens_used,
+            format!("${:.4}", run.total_cost_usd),
+            duration,
+            resumed,
+        );
+    }
+    Ok(())
+}
+
+/// `adw runs show` を実行
+///
+/// 実行結果が保存されていない実行（実行中・中断）は、保存済みのステップの出力のみ表示します。
+fn runs_show(store: &RunStore, args: &RunsShowArgs) -> Result<(), CliError> {
+    if args.json {
+        println!("{}", store.load_result(&args.run_id)?.to_json()?);
+        return Ok(());
+    }
+
+    match store.load_result(&args.run_id) {
+        Ok(result) => print_summary(&result),
+        Err(RunStoreError::ResultMissing(_)) => println!("Run: {}  (incomplete: no result saved)", args.run_id),
+        Err(e) => return Err(e.into()),
+    }
+
+    for transcript in store.load_steps(&args.run_id)? {
+        println!();
+        println!("=== {} ===", display_name(&transcript.step_name, transcript.iteration));
+        if args.prompts {
+            print_section("system prompt", &transcript.system_prompt);
+            print_section("input", &transcript.user_input);
+        }
+        if let Some(output) = &transcript.output {
+            print_section("output", output);
+        }
+        if let Some(error) = &transcript.error {
+            print_section("error", error);
+        }
+    }
+    Ok(())
+}
+
+/// `adw runs diff` を実行
+///
+/// ステップごとに、結果（ステータス・出力・エラー）が同じなら `=`、異なれば `~` と出力の差分、
+/// 一方の実行にしかないステップの繰り返しは `-`（比較元のみ）・`+`（比較先のみ）で表示します。
+fn runs_diff(store: &RunStore, args: &RunsDiffArgs) -> Result<(), CliError> {
+    let base = store.load_result(&args.base)?;
+    let target = store.load_result(&args.target)?;
+    if base.workflow_name != target.workflow_name {
+        return Err(CliError::RunsNotComparable(
+            base.run_id,
+            base.workflow_name,
+            target.run_id,
+            target.workflow_name,
+        ));
+    }
+
+    println!("Diff: {} -> {}  workflow: {}", base.run_id, target.run_id, base.workflow_name);
+    for pair in diff::pair_steps(&base, &target) {
+        match pair {
+            (Some(a), Some(b)) => print_step_diff(a, b),
+            (Some(a), None) => println!("  - {:<20} only in {}", step_name(a), base.run_id),
+            (None, Some(b)) => println!("  + {:<20} only in {}", step_name(b), target.run_id),
+            (None, None) => {}
+        }
+    }
+    println!(
+        "Status: {} -> {}  tokens: {} -> {}  cost: ${:.4} -> ${:.4}  duration: {:.2?} -> {:.2?}",
+        format_run_status(Some(&base.status)),
+        format_run_status(Some(&target.status)),
+        base.total_tokens_used,
+        target.total_tokens_used,
+        base.total_cost_usd,
+        target.total_cost_usd,
+        base.total_duration,
+        target.total_duration,
+    );
+    Ok(())
+}
+
+/// 両方の実行にあるステップの比較を表示
+fn print_step_diff(base: &StepResult, target: &StepResult) {
+    let same = diff::same_outcome(base, target);
+    println!(
+        "  {} {:<20} [{} -> {}]  tokens: {} -> {}  cost: {} -> {}",
+        if same { "=" } else { "~" },
+        step_name(base),
+        format_status(&base.status),
+        format_status(&target.status),
+        base.token_usage.total(),
+        target.token_usage.total(),
+        format_cost(base.cost_usd),
+        format_cost(target.cost_usd),
+    );
+    if same {
+        return;
+    }
+
+    if base.error != target.error {
+        println!(
+            "      error: {} -> {}",
+            base.error.as_deref().unwrap_or("-"),
+            target.error.as_deref().unwrap_or("-"),
+        );
+    }
+    if base.output != target.output {
+        let lines = diff::diff_lines(
+            base.output.as_deref().unwrap_or_default(),
+            target.output.as_deref().unwrap_or_default(),
+        );
+        for line in diff::format_diff(&lines, diff::CONTEXT_LINES) {
+            println!("      {}", line);
+        }
+    }
+}
+
+/// 見出し付きでテキストを表示（`adw runs show`）
+fn print_section(title: &str, text: &str) {
+    println!("--- {} ---", title);
+    println!("{}", text);
+}
+
+/// `--input` の値を解決する
+///
+/// - `-`: 標準入力から読み込む
+/// - `@path`: ファイルから読み込む
+/// - それ以外: 値をそのまま入力として使用する
+fn read_input(spec: &str) -> Result<String, std::io::Error> {
+    if spec == "-" {
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        Ok(buf)
+    } else if let Some(path) = spec.strip_prefix('@') {
+        std::fs::read_to_string(path)
+    } else {
+        Ok(spec.to_string())
+    }
+}
+
+/// 実行結果の要約を表示
+fn print_summary(result: &WorkflowResult) {
+    println!("Workflow: {}  run: {}", result.workflow_name, result.run_id);
+
+    for step in &result.steps {
+        println!(
+            "  [{}] {:<20} tokens: {:>6} (in {}, out {})  duration: {:.2?}",
+            format_status(&step.status),
+            step_name(step),
+            step.token_usage.total(),
+            step.token_usage.input_tokens,
+            step.token_usage.output_tokens,
+            step.duration,
+        );
+        if let Some(cost) = step.cost_usd {
+            println!("      cost: ${:.4}", cost);
+        }
+        if step.resumed {
+            println!("      resumed from checkpoint");
+        }
+        if step.cache_hit {
+            println!("      cache hit");
+        }
+        if step.repair_count > 0 {
+            println!("      schema repairs: {}", step.repair_count);
+        }
+        if let Some(error) = &step.error {
+            println!("      error: {}", error);
+        }
+        if let Some(reason) = &step.skip_reason {
+            println!("      reason: {}", reason);
+        }
+    }
+
+    for step_loop in &result.loops {
+        println!(
+            "  loop {}: {} iteration(s){}",
+            step_loop.name,
+            step_loop.iterations,
+            if step_loop.converged { "" } else { " (not converged)" },
+        );
+    }
+
+    println!(
+        "Status: {:?}  steps: {}/{}  revisions: {}  tokens: {}  cost: ${:.4}  duration: {:.2?}",
+        result.status,
+        result.completed_steps(),
+        result.steps.len(),
+        result.revision_count(),
+        result.total_tokens_used,
+        result.total_cost_usd,
+        result.total_duration,
+    );
+
+    if let Some(error) = &result.error {
+        println!("Error: {}", error);
+    }
+}
+
+/// ステップの表示名（ステップ名。ループの2回目以降は繰り返し回数を付ける）
+fn display_name(step_name: &str, iteration: u32) -> String {
+    if iteration > 0 {
+        format!("{} (#{})", step_name, iteration + 1)
+    } else {
+        step_name.to_string()
+    }
+}
+
+/// ステップの結果の表示名
+fn step_name(step: &StepResult) -> String {
+    display_name(&step.step_name, step.iteration)
+}
+
+/// コストを表示用の文字列に変換（単価が不明な場合は `-`）
+fn format_cost(cost_usd: Option<f64>) -> String {
+    cost_usd.map_or_else(|| "-".to_string(), |cost| format!("${:.4}", cost))
+}
+
+/// 実行ステータスを表示用の短い文字列に変換（結果が保存されていない実行は `incomplete`）
+fn format_run_status(status: Option<&ExecutionStatus>) -> &'static str {
+    match status {
+        Some(ExecutionStatus::Success) => "success",
+        Some(ExecutionStatus::PartialSuccess { .. }) => "partial",
+        Some(ExecutionStatus::Failed) => "failed",
+        None => "incomplete",
+    }
+}
+
+/// ステップステータスを表示用の短い文字列に変換
+fn format_status(status: &StepStatus) -> String {
+    match status {
+        StepStatus::Success => "ok".to_string(),
+        StepStatus::Failed => "failed".to_string(),
+        StepStatus::Retried { attempts } => format!("ok after {} retries", attempts),
+        StepStatus::Skipped => "skipped".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_input_literal() {
+        let input = read_input("新しい認証機能を実装してください").unwrap();
+        assert_eq!(input, "新しい認証機能を実装してください");
+    }
+
+    #[test]
+    fn test_read_input_from_file() {
+        let path = std::env::temp_dir().join("adw_cli_test_input.txt");
+        std::fs::write(&path, "file content").unwrap();
+
+        let input = read_input(&format!("@{}", path.display())).unwrap();
+        assert_eq!(input, "file content");
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_read_input_missing_file() {
+        let result = read_input("@/nonexistent/path/to/input.txt");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_display_name() {
+        assert_eq!(display_name("review", 0), "review");
+        assert_eq!(display_name("review", 1), "review (#2)");
+    }
+
+    #[test]
+    fn test_format_run_status() {
+        assert_eq!(format_run_status(Some(&ExecutionStatus::Success)), "success");
+        assert_eq!(
+            format_run_status(Some(&ExecutionStatus::PartialSuccess { completed: 1, total: 2 })),
+            "partial"
+        );
+        assert_eq!(format_run_status(Some(&ExecutionStatus::Failed)), "failed");
+        assert_eq!(format_run_status(None), "incomplete");
+    }
+
+    #[test]
+    fn test_format_status() {
+        assert_eq!(format_status(&StepStatus::Success), "ok");
+        assert_eq!(format_status(&StepStatus::Failed), "failed");
+        assert_eq!(format_status(&StepStatus::Skipped), "skipped");
+        assert_eq!(
+            format_status(&StepStatus::Retried { attempts: 2 }),
+            "ok after 2 retries"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! 実行結果の比較（`adw runs diff`）
+//!
+//! # 責務
+//!
+//! - 2つの実行のステップの結果を、ステップと繰り返し回数ごとに対応付ける
+//! - ステップの出力の行単位の差分を計算し、表示用に整形する
+
+use std::collections::BTreeMap;
+
+use crate::engine::{StepResult, WorkflowResult};
+
+/// 差分の前後に表示する変更のない行数
+pub const CONTEXT_LINES: usize = 2;
+
+/// 行単位の差分の1行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    /// 両方にある行
+    Same(&'a str),
+
+    /// 比較元にのみある行
+    Removed(&'a str),
+
+    /// 比較先にのみある行
+    Added(&'a str),
+}
+
+/// 対応付けたステップの結果（比較元、比較先）
+pub type StepPair<'a> = (Option<&'a StepResult>, Option<&'a StepResult>);
+
+/// 2つの実行のステップの結果を、ステップの定義順（ループ内は繰り返し順）に対応付ける
+///
+/// 一方の実行にしかないステップの繰り返し（ループの回数の違い等）は、もう一方を `None` とします。
+pub fn pair_steps<'a>(base: &'a WorkflowResult, target: &'a WorkflowResult) -> Vec<StepPair<'a>> {
+    let mut pairs: BTreeMap<(usize, u32), StepPair<'a>> = BTreeMap::new();
+    for step in &base.steps {
+        pairs.entry((step.index, step.iteration)).or_default().0 = Some(step);
+    }
+    for step in &target.steps {
+        pairs.entry((step.index, step.iteration)).or_default().1 = Some(step);
+    }
+    pairs.into_values().collect()
+}
+
+/// 2つのステップの結果（ステータス・出力・エラー）が同じか
+///
+/// トークン使用量・実行時間・コストは比較しません。
+pub fn same_outcome(base: &StepResult, target: &StepResult) -> bool {
+    base.status == target.status && base.output == target.output && base.error == target.error
+}
+
+/// 行単位の差分を計算
+///
+/// 最長共通部分列に基づき、削除した行を追加した行より先に並べます。
+///
+/// # 例
+///
+/// ```rust
+/// use melted_adw::cli::diff::{diff_lines, DiffLine};
+///
+/// let lines = diff_lines("a\nb\nc", "a\nx\nc");
+/// assert_eq!(
+///     lines,
+///     [DiffLine::Same("a"), DiffLine::Removed("b"), DiffLine::Added("x"), DiffLine::Same("c")]
+/// );
+/// ```
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    // 共通の先頭と末尾を除いた範囲だけ最長共通部分列を計算する
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+    let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+
+    // lcs[i * width + j]: old_middle[i..] と new_middle[j..] の最長共通部分列の長さ
+    let width = new_middle.len() + 1;
+    let mut lcs = vec![0u32; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lcs[i * width + j] = if old_middle[i] == new_middle[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut lines: Vec<DiffLine<'a>> = old_lines[..prefix].iter().map(|line| DiffLine::Same(line)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            lines.push(DiffLine::Same(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_middle.len()
+            && (j == new_middle.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            lines.push(DiffLine::Removed(old_middle[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new_middle[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old_lines[old_lines.len() - suffix..].iter().map(|line| DiffLine::Same(line)));
+    lines
+}
+
+/// 差分を表示用の行に整形
+///
+/// 削除した行は `- `、追加した行は `+ `、変更のない行は `  ` で始めます。
+/// 変更から `context` 行より離れた変更のない行は、まとめて `  ...` とします。
+pub fn format_diff(lines: &[DiffLine], context: usize) -> Vec<String> {
+    let mut shown = vec![false; lines.len()];
+    for (position, line) in lines.iter().enumerate() {
+        if !matches!(line, DiffLine::Same(_)) {
+            let end = (position + context + 1).min(lines.len());
+            shown[position.saturating_sub(context)..end].fill(true);
+        }
+    }
+
+    let mut formatted = Vec::new();
+    for (line, shown) in lines.iter().zip(&shown) {
+        match line {
+            _ if !shown => {
+                if formatted.last().is_none_or(|previous: &String| previous != "  ...") {
+                    formatted.push("  ...".to_string());
+                }
+            }
+            DiffLine::Same(text) => formatted.push(format!("  {}", text)),
+            DiffLine::Removed(text) => formatted.push(format!("- {}", text)),
+            DiffLine::Added(text) => formatted.push(format!("+ {}", text)),
+        }
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{ExecutionStatus, StepStatus};
+    use std::time::{Duration, SystemTime};
+
+    fn step(name: &str, index: usize, iteration: u32, output: &str) -> StepResult {
+        StepResult {
+            status: StepStatus::Success,
+            output: Some(output.to_string()),
+            skip_reason: None,
+            iteration,
+            ..StepResult::skipped(name, index, "")
+        }
+    }
+
+    fn result(steps: Vec<StepResult>) -> WorkflowResult {
+        WorkflowResult {
+            workflow_name: "diff".to_string(),
+            run_id: "run".to_string(),
+            status: ExecutionStatus::Success,
+            steps,
+            start_time: SystemTime::UNIX_EPOCH,
+            end_time: SystemTime::UNIX_EPOCH,
+            total_duration: Duration::ZERO,
+            total_tokens_used: 0,
+            total_cost_usd: 0.0,
+            loops: vec![],
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb", "a\nb"), [DiffLine::Same("a"), DiffLine::Same("b")]);
+        assert_eq!(diff_lines("", "a"), [DiffLine::Added("a")]);
+        assert_eq!(
+            diff_lines("a\nb\nc\nd", "b\nc\ne\nd"),
+            [
+                DiffLine::Removed("a"),
+                DiffLine::Same("b"),
+                DiffLine::Same("c"),
+                DiffLine::Added("e"),
+                DiffLine::Same("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_diff() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8";
+
+        assert_eq!(
+            format_diff(&diff_lines(old, new), 1),
+            ["  ...", "  4", "- 5", "+ five", "  6", "  ..."]
+        );
+        assert!(format_diff(&diff_lines(old, old), 1).iter().all(|line| line == "  ..."));
+    }
+
+    #[test]
+    fn test_pair_steps() {
+        // 比較元はループを2回、比較先は1回で抜けた
+        let base = result(vec![step("plan", 0, 0, "p"), step("review", 1, 0, "r1"), step("review", 1, 1, "r2")]);
+        let target = result(vec![step("review", 1, 0, "r1"), step("plan", 0, 0, "P")]);
+
+        let pairs = pair_steps(&base, &target);
+        let names: Vec<_> = pairs
+            .iter()
+            .map(|(a, b)| (a.map(|step| step.iteration), b.map(|step| step.iteration)))
+            .collect();
+        assert_eq!(names, [(Some(0), Some(0)), (Some(0), Some(0)), (Some(1), None)]);
+
+        assert!(!same_outcome(pairs[0].0.unwrap(), pairs[0].1.unwrap()));
+        assert!(same_outcome(pairs[1].0.unwrap(), pairs[1].1.unwrap()));
+    }
+}
@@ -0,0 +1,131 @@
+//! `adw run` の進捗表示
+//!
+//! # 責務
+//!
+//! ストリーミング実行のイベントを受け取り、ステップ名を付けて標準エラー出力に表示する。
+//! 並列実行中のステップの出力が混ざらないよう、テキストはステップごとに行単位でまとめて表示します。
+//!
+//! ```text
+//! [implement] 変更内容を確認します。
+//! [implement] tool: Bash {"command":"cargo test"}
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::engine::ProgressSink;
+use crate::provider::ProviderEvent;
+
+/// ツール呼び出しの入力を表示する最大文字数
+const MAX_TOOL_INPUT_CHARS: usize = 80;
+
+/// 標準エラー出力に進捗を表示する [`ProgressSink`]
+#[derive(Debug, Default)]
+pub struct StderrProgress {
+    /// ステップごとの、まだ改行が来ていないテキスト
+    pending: Mutex<HashMap<String, String>>,
+}
+
+impl StderrProgress {
+    /// 新しい進捗表示を生成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// イベントを表示する行に変換
+    fn lines_for(&self, step_name: &str, event: &ProviderEvent) -> Vec<String> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+
+        match event {
+            ProviderEvent::TextDelta(text) => {
+                let buffer = pending.entry(step_name.to_string()).or_default();
+                buffer.push_str(text);
+
+                let Some(end) = buffer.rfind('\n') else {
+                    return Vec::new();
+                };
+                let complete: String = buffer.drain(..=end).collect();
+                complete
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| format!("[{}] {}", step_name, line))
+                    .collect()
+            }
+            ProviderEvent::ToolCall { name, input } => {
+                let mut lines = flush(&mut pending, step_name);
+                lines.push(format!("[{}] tool: {} {}", step_name, name, truncate(&input.to_string())));
+                lines
+            }
+            ProviderEvent::Usage(_) => Vec::new(),
+            ProviderEvent::Completed(_) => flush(&mut pending, step_name),
+        }
+    }
+}
+
+impl ProgressSink for StderrProgress {
+    fn on_event(&self, step_name: &str, event: &ProviderEvent) {
+        for line in self.lines_for(step_name, event) {
+            eprintln!("{}", line);
+        }
+    }
+}
+
+/// ステップの未表示のテキストを取り出す
+fn flush(pending: &mut HashMap<String, String>, step_name: &str) -> Vec<String> {
+    match pending.remove(step_name) {
+        Some(rest) if !rest.trim().is_empty() => vec![format!("[{}] {}", step_name, rest.trim_end())],
+        _ => Vec::new(),
+    }
+}
+
+/// 表示用に文字数を制限
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TOOL_INPUT_CHARS {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(MAX_TOOL_INPUT_CHARS).collect();
+    format!("{}…", head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{ProviderResponse, StopReason, TokenUsage};
+    use serde_json::json;
+
+    #[test]
+    fn test_lines_are_buffered_per_step() {
+        let progress = StderrProgress::new();
+        let text = |s: &str| ProviderEvent::TextDelta(s.to_string());
+
+        assert!(progress.lines_for("plan", &text("計画を")).is_empty());
+        assert!(progress.lines_for("review", &text("レビュー中")).is_empty());
+        assert_eq!(progress.lines_for("plan", &text("立てます。\n次に")), vec!["[plan] 計画を立てます。"]);
+
+        let tool = ProviderEvent::ToolCall {
+            name: "Bash".to_string(),
+            input: json!({ "command": "ls" }),
+        };
+        assert_eq!(
+            progress.lines_for("plan", &tool),
+            vec!["[plan] 次に", r#"[plan] tool: Bash {"command":"ls"}"#]
+        );
+
+        let completed = ProviderEvent::Completed(ProviderResponse {
+            content: String::new(),
+            token_usage: TokenUsage::default(),
+            stop_reason: StopReason::EndTurn,
+            model: "test-model".to_string(),
+            cached: false,
+        });
+        assert_eq!(progress.lines_for("review", &completed), vec!["[review] レビュー中"]);
+        assert!(progress.lines_for("review", &completed).is_empty());
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short"), "short");
+        let long = "あ".repeat(MAX_TOOL_INPUT_CHARS + 5);
+        assert_eq!(truncate(&long).chars().count(), MAX_TOOL_INPUT_CHARS + 1);
+    }
+}
@@ -15,13 +15,69 @@
 //!
 //! - [`workflow`][]: ワークフロー全体の定義（ドメインモデル）
 //! - [`step`][]: 各ステップの定義（ドメインモデル）
+//! - [`providers`][]: ワークフローで定義するプロバイダー（`[providers.<name>]`）
+//! - [`models`][]: プロバイダーごとのモデル名の対応表（`[models.<provider>]`）
+//! - [`prices`][]: モデルごとのトークン単価（`[prices."<model>"]`）
+//! - [`user`][]: ユーザー設定ファイル（`~/.config/adw/config.toml`）
+//! - [`command`][]: コマンドプロバイダー（任意の CLI エージェント）の定義
+//! - [`condition`][]: ステップの実行条件（`when`）
+//! - [`retry`][]: ステップのリトライの方針（`retry`）
+//! - [`fallback`][]: ステップのフォールバック先（`fallback`）
+//! - [`budget`][]: ワークフローとステップの予算（`[budget]` / `budget`）
+//! - [`loops`][]: ステップ範囲の繰り返し（`[[loops]]`）
+//! - [`schema`][]: ステップ出力の JSON Schema（`output_schema`）
+//! - [`template`][]: プロンプト中のプレースホルダー（`{{steps.plan.output}}` 等）
+//! - [`validate`][]: 全エラーを位置情報付きで収集する一括バリデーション
 //!
 //! ## 内部実装（非公開）
 //!
 //! - `dto`: TOML デシリアライズ用の DTO（腐敗防止層）
 //!   - 外部には公開されず、ドメインモデル経由でのみアクセス可能
 //!   - TOML の生データとドメインモデルを分離し、バリデーションを担当
+//! - `graph`: ステップの依存関係（`depends_on`）の解決と循環検出
+//! - `fingerprint`: 定義の同一性の判定に使うハッシュ値（クレート内部の関数）
 
+pub mod budget;
+pub mod command;
+pub mod condition;
+pub mod fallback;
 mod dto;
+mod graph;
+pub mod loops;
+pub mod models;
+pub mod prices;
+pub mod providers;
+pub mod retry;
+pub mod schema;
 pub mod step;
+pub mod template;
+pub mod user;
+pub mod validate;
 pub mod workflow;
+
+/// テキストのハッシュ値（16進数16桁）
+///
+/// 定義が変更されていないかの判定や、応答キャッシュのキーに使います。Rust のバージョンによらず同じ値になるよう、
+/// 標準ライブラリのハッシャーではなく FNV-1a（64ビット）で計算します。暗号学的な強度はありません。
+pub(crate) fn fingerprint(text: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = text
+        .bytes()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(PRIME));
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        // FNV-1a の既知の値
+        assert_eq!(fingerprint(""), "cbf29ce484222325");
+        assert_eq!(fingerprint("a"), "af63dc4c8601ec8c");
+        assert_ne!(fingerprint("plan"), fingerprint("plan "));
+    }
+}
@@ -0,0 +1,144 @@
+//! ワークフローとステップの予算（`[budget]` / `budget`）
+//!
+//! # 責務
+//!
+//! ワークフロー全体（`[budget]`）とステップ（`[[steps]]` の `budget`）に記述された
+//! トークン数・コスト・経過時間の上限を検証済みの型 [`Budget`] として表現する。
+//! 上限の判定と実行の停止は実行エンジンが行う。
+//!
+//! # 記述形式
+//!
+//! | キー               | 内容                                                        |
+//! |-------------------|-------------------------------------------------------------|
+//! | `max_tokens_total` | 入力と出力を合わせたトークン数の上限（1以上）                      |
+//! | `max_cost_usd`     | コストの上限（米ドル、[`select_price`](super::prices::select_price) で計算） |
+//! | `max_wall_time`    | 経過時間の上限（秒、1以上。ステップはリトライとフォールバックを含む）   |
+//!
+//! いずれのキーも省略でき、省略した上限は判定しません。
+//!
+//! ```toml
+//! [budget]
+//! max_tokens_total = 200000
+//! max_cost_usd = 2.5
+//! max_wall_time = 1800
+//!
+//! [[steps]]
+//! name = "plan"
+//! # ...
+//! budget = { max_tokens_total = 50000, max_wall_time = 300 }
+//! ```
+
+use std::time::Duration;
+
+use super::dto::BudgetDto;
+
+/// トークン数・コスト・経過時間の上限（ドメインモデル）
+///
+/// 既定値（[`Default`]）はすべて無制限です。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budget {
+    /// トークン数の上限
+    max_tokens_total: Option<u64>,
+    /// コストの上限（米ドル）
+    max_cost_usd: Option<f64>,
+    /// 経過時間の上限
+    max_wall_time: Option<Duration>,
+}
+
+impl Budget {
+    /// トークン数の上限を取得
+    pub fn max_tokens_total(&self) -> Option<u64> {
+        self.max_tokens_total
+    }
+
+    /// コストの上限（米ドル）を取得
+    pub fn max_cost_usd(&self) -> Option<f64> {
+        self.max_cost_usd
+    }
+
+    /// 経過時間の上限を取得
+    pub fn max_wall_time(&self) -> Option<Duration> {
+        self.max_wall_time
+    }
+
+    /// 上限が1つも指定されていないか
+    pub fn is_unlimited(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `subject`: エラーメッセージで予算を指す名前（`[budget]`、`ステップ 'plan' の budget` など）
+/// - `dto`: `budget` テーブル
+pub(super) fn parse_budget(subject: &str, dto: &BudgetDto) -> Result<Budget, String> {
+    let invalid = |detail: &str| format!("{} の {}", subject, detail);
+
+    if dto.max_tokens_total == Some(0) {
+        return Err(invalid("max_tokens_total は1以上を指定してください"));
+    }
+    if dto.max_cost_usd.is_some_and(|cost| !cost.is_finite() || cost < 0.0) {
+        return Err(invalid("max_cost_usd は0以上を指定してください"));
+    }
+    if dto.max_wall_time == Some(0) {
+        return Err(invalid("max_wall_time は1以上の秒数を指定してください"));
+    }
+
+    Ok(Budget {
+        max_tokens_total: dto.max_tokens_total,
+        max_cost_usd: dto.max_cost_usd,
+        max_wall_time: dto.max_wall_time.map(Duration::from_secs),
+    })
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<Budget> for BudgetDto {
+    fn from(budget: Budget) -> Self {
+        BudgetDto {
+            max_tokens_total: budget.max_tokens_total,
+            max_cost_usd: budget.max_cost_usd,
+            max_wall_time: budget.max_wall_time.map(|limit| limit.as_secs()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(toml: &str) -> BudgetDto {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        let budget = parse_budget("[budget]", &dto("max_tokens_total = 1000\nmax_cost_usd = 0.5\nmax_wall_time = 60")).unwrap();
+
+        assert_eq!(budget.max_tokens_total(), Some(1000));
+        assert_eq!(budget.max_cost_usd(), Some(0.5));
+        assert_eq!(budget.max_wall_time(), Some(Duration::from_secs(60)));
+        assert!(!budget.is_unlimited());
+
+        // DTO に戻して再度読み込める
+        assert_eq!(parse_budget("[budget]", &BudgetDto::from(budget.clone())).unwrap(), budget);
+
+        // 省略時は無制限
+        assert!(parse_budget("[budget]", &BudgetDto::default()).unwrap().is_unlimited());
+    }
+
+    #[test]
+    fn test_parse_budget_errors() {
+        let cases = [
+            ("max_tokens_total = 0", "ステップ 'plan' の budget の max_tokens_total は1以上を指定してください"),
+            ("max_cost_usd = -0.1", "max_cost_usd は0以上を指定してください"),
+            ("max_wall_time = 0", "max_wall_time は1以上の秒数を指定してください"),
+        ];
+
+        for (toml, expected) in cases {
+            let err = parse_budget("ステップ 'plan' の budget", &dto(toml)).unwrap_err();
+            assert!(err.contains(expected), "{} does not contain {}", err, expected);
+        }
+    }
+}
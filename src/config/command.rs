@@ -0,0 +1,437 @@
+//! コマンドプロバイダーの定義（`[providers.<name>]`）
+//!
+//! # 責務
+//!
+//! 任意の CLI エージェントを呼び出す方法（コマンド・引数・プロンプトの渡し方・出力の解釈・
+//! エラーパターン）を TOML から読み込み、検証済みの [`CommandSpec`] として表現する。
+//! 実際の呼び出しは [`CommandProvider`](crate::provider::command::CommandProvider) が行う。
+//!
+//! `type` を省略した `[providers.<name>]` はコマンドプロバイダーです（[`super::providers`] 参照）。
+//!
+//! # 記述形式
+//!
+//! ```toml
+//! [providers.my-agent]
+//! command = "my-agent"
+//! args = ["run", "--model", "{model}", "--json"]
+//! prompt_input = "stdin"                         # argv（既定） / stdin
+//! output = { format = "jsonl", text = "/delta", tool = "/tool/name", input_tokens = "/usage/input", output_tokens = "/usage/output" }
+//! models = { heavy = "agent-large", light = "agent-small" }
+//! errors = [
+//!     { pattern = "(?i)unauthorized", kind = "authentication" },
+//!     { pattern = "(?i)rate limit", kind = "rate_limit" },
+//! ]
+//!
+//! [[steps]]
+//! name = "triage"
+//! provider = "my-agent"
+//! # ...
+//! ```
+//!
+//! ## 引数のプレースホルダー
+//!
+//! | プレースホルダー    | 展開される値                                               |
+//! |---------------------|------------------------------------------------------------|
+//! | `{prompt}`          | プロンプト（`{system_prompt}` がなければシステムプロンプトを結合） |
+//! | `{system_prompt}`   | システムプロンプト                                          |
+//! | `{model}`           | `models` のモデル名（未指定のティアはティア名）              |
+//!
+//! `prompt_input = "argv"` で `args` に `{prompt}` がない場合、プロンプトは最後の引数として渡します。
+//!
+//! ## 出力形式
+//!
+//! - `raw`: 標準出力全体が本文
+//! - `json`: 標準出力全体を JSON として解釈し、`text` のポインターが指す値が本文
+//! - `jsonl`: 1行1イベントの JSON。`text` が指す文字列を連結したものが本文で、
+//!   `tool` が指す文字列があるイベントはツール呼び出しとして扱う（逐次の進捗表示に対応）
+
+use regex::Regex;
+
+use super::dto::{CommandOutputDto, ErrorPatternDto, ProviderDto};
+use super::providers::TierModels;
+use super::step::ModelTier;
+use crate::provider::classify::{ErrorKind, ErrorPattern};
+
+/// プロンプトのプレースホルダー
+pub const PROMPT_PLACEHOLDER: &str = "{prompt}";
+/// システムプロンプトのプレースホルダー
+pub const SYSTEM_PROMPT_PLACEHOLDER: &str = "{system_prompt}";
+/// モデル名のプレースホルダー
+pub const MODEL_PLACEHOLDER: &str = "{model}";
+
+/// コマンドプロバイダーの定義（ドメインモデル）
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    /// プロバイダー名（小文字）
+    name: String,
+    /// 実行するコマンド
+    command: String,
+    /// 引数のテンプレート
+    args: Vec<String>,
+    /// プロンプトの渡し方
+    prompt_input: PromptInput,
+    /// 出力の解釈方法
+    output: CommandOutput,
+    /// モデルティアごとのモデル名
+    models: TierModels,
+    /// 標準エラー出力のエラーパターン
+    errors: Vec<ErrorPattern>,
+}
+
+impl CommandSpec {
+    /// プロバイダー名を取得
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 実行するコマンドを取得
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// 引数のテンプレートを取得
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// プロンプトの渡し方を取得
+    pub fn prompt_input(&self) -> PromptInput {
+        self.prompt_input
+    }
+
+    /// 出力の解釈方法を取得
+    pub fn output(&self) -> &CommandOutput {
+        &self.output
+    }
+
+    /// 標準エラー出力のエラーパターンを取得
+    pub fn errors(&self) -> &[ErrorPattern] {
+        &self.errors
+    }
+
+    /// モデルティアに対応するモデル名を取得（未指定のティアはティア名）
+    pub fn model(&self, tier: &ModelTier) -> &str {
+        self.models.get(tier).unwrap_or(tier.as_str())
+    }
+
+    /// システムプロンプトを専用の引数（`{system_prompt}`）で渡すか
+    pub fn has_system_prompt_arg(&self) -> bool {
+        self.args.iter().any(|arg| arg.contains(SYSTEM_PROMPT_PLACEHOLDER))
+    }
+
+    /// プロンプトを引数（`{prompt}`）で渡すか
+    pub fn has_prompt_arg(&self) -> bool {
+        self.args.iter().any(|arg| arg.contains(PROMPT_PLACEHOLDER))
+    }
+}
+
+/// プロンプトの渡し方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptInput {
+    /// コマンドライン引数で渡す
+    Argv,
+    /// 標準入力で渡す
+    Stdin,
+}
+
+impl PromptInput {
+    /// 設定ファイルで使用する名前を取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptInput::Argv => "argv",
+            PromptInput::Stdin => "stdin",
+        }
+    }
+}
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 標準出力全体が本文
+    Raw,
+    /// 標準出力全体が1つの JSON
+    Json,
+    /// 1行1イベントの JSON
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// 設定ファイルで使用する名前を取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Raw => "raw",
+            OutputFormat::Json => "json",
+            OutputFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// コマンド出力の解釈方法
+///
+/// ポインターは JSON ポインター（`/result/text` の形式、`""` は全体）です。
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    format: OutputFormat,
+    text: Option<String>,
+    tool: Option<String>,
+    input_tokens: Option<String>,
+    output_tokens: Option<String>,
+}
+
+impl CommandOutput {
+    /// 出力形式を取得
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// 本文の JSON ポインターを取得（`raw` では `None`）
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// ツール名の JSON ポインターを取得（`jsonl` のみ）
+    pub fn tool(&self) -> Option<&str> {
+        self.tool.as_deref()
+    }
+
+    /// 入力トークン数の JSON ポインターを取得
+    pub fn input_tokens(&self) -> Option<&str> {
+        self.input_tokens.as_deref()
+    }
+
+    /// 出力トークン数の JSON ポインターを取得
+    pub fn output_tokens(&self) -> Option<&str> {
+        self.output_tokens.as_deref()
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `name`: プロバイダー名（`[providers.<name>]` のキー）
+/// - `dto`: プロバイダーの定義
+pub(super) fn parse_command_spec(name: &str, dto: &ProviderDto) -> Result<CommandSpec, String> {
+    let invalid = |detail: String| format!("プロバイダー '{}' の定義が不正です: {}", name, detail);
+
+    let command = match &dto.command {
+        Some(command) if !command.trim().is_empty() => command.clone(),
+        Some(_) => return Err(invalid("command が空です".to_string())),
+        None => return Err(invalid("必須キー 'command' がありません".to_string())),
+    };
+
+    let prompt_input = match dto.prompt_input.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("argv") => PromptInput::Argv,
+        Some("stdin") => PromptInput::Stdin,
+        Some(other) => {
+            return Err(invalid(format!("不正な prompt_input: '{}' (有効な値: argv, stdin)", other)));
+        }
+    };
+
+    let output = match &dto.output {
+        None => CommandOutput {
+            format: OutputFormat::Raw,
+            text: None,
+            tool: None,
+            input_tokens: None,
+            output_tokens: None,
+        },
+        Some(output) => parse_output(output).map_err(invalid)?,
+    };
+
+    let errors = dto
+        .errors
+        .iter()
+        .map(parse_error_pattern)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid)?;
+
+    let spec = CommandSpec {
+        name: name.to_lowercase(),
+        command,
+        args: dto.args.clone(),
+        prompt_input,
+        output,
+        models: TierModels::from_dto(dto.models.as_ref()),
+        errors,
+    };
+
+    if spec.prompt_input == PromptInput::Stdin && spec.has_prompt_arg() {
+        return Err(invalid(format!(
+            "prompt_input = \"stdin\" の場合、args に {} は使えません",
+            PROMPT_PLACEHOLDER
+        )));
+    }
+
+    Ok(spec)
+}
+
+/// 出力の解釈方法の変換
+fn parse_output(dto: &CommandOutputDto) -> Result<CommandOutput, String> {
+    let format = match dto.format.to_lowercase().as_str() {
+        "raw" => OutputFormat::Raw,
+        "json" => OutputFormat::Json,
+        "jsonl" => OutputFormat::Jsonl,
+        other => return Err(format!("不正な output.format: '{}' (有効な値: raw, json, jsonl)", other)),
+    };
+
+    let pointers = [
+        ("text", &dto.text),
+        ("tool", &dto.tool),
+        ("input_tokens", &dto.input_tokens),
+        ("output_tokens", &dto.output_tokens),
+    ];
+    for (key, pointer) in pointers {
+        match pointer {
+            Some(pointer) if format == OutputFormat::Raw => {
+                return Err(format!("output.{} は format = \"raw\" では使えません（'{}'）", key, pointer));
+            }
+            Some(pointer) if !pointer.is_empty() && !pointer.starts_with('/') => {
+                return Err(format!("output.{} は '/' で始まる JSON ポインターで指定してください: '{}'", key, pointer));
+            }
+            _ => {}
+        }
+    }
+    if format != OutputFormat::Raw && dto.text.is_none() {
+        return Err(format!("format = \"{}\" の場合は output.text が必要です", format.as_str()));
+    }
+    if format != OutputFormat::Jsonl && dto.tool.is_some() {
+        return Err("output.tool は format = \"jsonl\" でのみ使えます".to_string());
+    }
+
+    Ok(CommandOutput {
+        format,
+        text: dto.text.clone(),
+        tool: dto.tool.clone(),
+        input_tokens: dto.input_tokens.clone(),
+        output_tokens: dto.output_tokens.clone(),
+    })
+}
+
+/// エラーパターンの変換
+fn parse_error_pattern(dto: &ErrorPatternDto) -> Result<ErrorPattern, String> {
+    let regex = Regex::new(&dto.pattern)
+        .map_err(|e| format!("errors の正規表現が不正です: '{}': {}", dto.pattern, e))?;
+    let kind = ErrorKind::parse(&dto.kind).ok_or_else(|| {
+        format!(
+            "errors の不正な kind: '{}' (有効な値: authentication, rate_limit, timeout, error)",
+            dto.kind
+        )
+    })?;
+    Ok(ErrorPattern::new(regex, kind))
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<CommandSpec> for ProviderDto {
+    fn from(spec: CommandSpec) -> Self {
+        let output = match spec.output.format {
+            OutputFormat::Raw => None,
+            format => Some(CommandOutputDto {
+                format: format.as_str().to_string(),
+                text: spec.output.text,
+                tool: spec.output.tool,
+                input_tokens: spec.output.input_tokens,
+                output_tokens: spec.output.output_tokens,
+            }),
+        };
+        ProviderDto {
+            command: Some(spec.command),
+            args: spec.args,
+            prompt_input: match spec.prompt_input {
+                PromptInput::Argv => None,
+                PromptInput::Stdin => Some(PromptInput::Stdin.as_str().to_string()),
+            },
+            output,
+            models: spec.models.into_dto(),
+            errors: spec
+                .errors
+                .iter()
+                .map(|pattern| ErrorPatternDto {
+                    pattern: pattern.regex().as_str().to_string(),
+                    kind: pattern.kind().as_str().to_string(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(toml: &str) -> ProviderDto {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_command_spec() {
+        let spec = parse_command_spec(
+            "My-Agent",
+            &dto(r#"
+command = "my-agent"
+args = ["run", "--model", "{model}", "--system", "{system_prompt}"]
+prompt_input = "stdin"
+output = { format = "jsonl", text = "/delta", tool = "/tool/name" }
+models = { heavy = "agent-large" }
+errors = [{ pattern = "(?i)unauthorized", kind = "authentication" }]
+"#),
+        )
+        .unwrap();
+
+        assert_eq!(spec.name(), "my-agent");
+        assert_eq!(spec.command(), "my-agent");
+        assert_eq!(spec.prompt_input(), PromptInput::Stdin);
+        assert_eq!(spec.output().format(), OutputFormat::Jsonl);
+        assert_eq!(spec.output().tool(), Some("/tool/name"));
+        assert_eq!(spec.model(&ModelTier::Heavy), "agent-large");
+        assert_eq!(spec.model(&ModelTier::Light), "light");
+        assert!(spec.has_system_prompt_arg());
+        assert!(!spec.has_prompt_arg());
+        assert_eq!(spec.errors()[0].kind(), ErrorKind::Authentication);
+
+        // DTO に戻して再度読み込める
+        let roundtrip = parse_command_spec("my-agent", &ProviderDto::from(spec)).unwrap();
+        assert_eq!(roundtrip.prompt_input(), PromptInput::Stdin);
+        assert_eq!(roundtrip.output().text(), Some("/delta"));
+        assert_eq!(roundtrip.errors()[0].regex().as_str(), "(?i)unauthorized");
+
+        // 最小の定義は argv / raw
+        let spec = parse_command_spec("echo", &dto(r#"command = "echo""#)).unwrap();
+        assert_eq!(spec.prompt_input(), PromptInput::Argv);
+        assert_eq!(spec.output().format(), OutputFormat::Raw);
+    }
+
+    #[test]
+    fn test_parse_command_spec_errors() {
+        let cases = [
+            (r#"command = " ""#, "command が空です"),
+            (r#"args = ["run"]"#, "必須キー 'command' がありません"),
+            (r#"command = "a"
+prompt_input = "file""#, "不正な prompt_input"),
+            (r#"command = "a"
+prompt_input = "stdin"
+args = ["{prompt}"]"#, "args に {prompt} は使えません"),
+            (r#"command = "a"
+output = { format = "xml" }"#, "不正な output.format"),
+            (r#"command = "a"
+output = { format = "json" }"#, "output.text が必要です"),
+            (r#"command = "a"
+output = { format = "raw", text = "/text" }"#, "format = \"raw\" では使えません"),
+            (r#"command = "a"
+output = { format = "json", text = "result" }"#, "JSON ポインター"),
+            (r#"command = "a"
+output = { format = "json", text = "/result", tool = "/tool" }"#, "output.tool は format = \"jsonl\""),
+            (r#"command = "a"
+errors = [{ pattern = "(", kind = "error" }]"#, "正規表現が不正です"),
+            (r#"command = "a"
+errors = [{ pattern = "x", kind = "fatal" }]"#, "不正な kind"),
+        ];
+
+        for (toml, expected) in cases {
+            let err = parse_command_spec("agent", &dto(toml)).unwrap_err();
+            assert!(err.contains("プロバイダー 'agent' の定義が不正です"), "{}", err);
+            assert!(err.contains(expected), "{} does not contain {}", err, expected);
+        }
+    }
+}
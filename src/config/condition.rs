@@ -0,0 +1,328 @@
+//! ステップの実行条件（`when`）
+//!
+//! # 責務
+//!
+//! `[[steps]]` の `when` に記述された条件を検証済みの型 [`Condition`] として表現する。
+//! 条件の評価（先行ステップの結果との照合）は実行エンジンが行う。
+//!
+//! # 記述形式
+//!
+//! `step` で先行ステップを指定し、以下のいずれか1つの判定を記述します。
+//! `negate = true` で判定を反転します。
+//!
+//! | キー               | 判定内容                                             |
+//! |-------------------|-----------------------------------------------------|
+//! | `contains`        | 出力に文字列が含まれる                                 |
+//! | `matches`         | 出力が正規表現にマッチする                              |
+//! | `json` + `equals` | 出力を JSON として解釈し、パスの値が `equals` と等しい     |
+//! | `status`          | ステップの実行ステータス（`success` / `failed` / `skipped`） |
+//!
+//! ```toml
+//! [[steps]]
+//! name = "fix"
+//! system_prompt = "レビュー指摘を修正してください"
+//! provider = "anthropic"
+//! model_tier = "heavy"
+//! when = { step = "review", contains = "LGTM", negate = true }
+//! ```
+//!
+//! JSON のパスは JSON Pointer（`/verdict/score`）またはドット区切り（`$.verdict.score`）で指定します。
+
+use std::fmt;
+
+use regex::Regex;
+
+use super::dto::ConditionDto;
+
+/// ステップの実行条件（ドメインモデル）
+#[derive(Debug, Clone)]
+pub struct Condition {
+    /// 判定対象のステップ名
+    step: String,
+    /// 判定内容
+    test: ConditionTest,
+    /// 判定を反転するか
+    negate: bool,
+}
+
+/// 条件の判定内容
+#[derive(Debug, Clone)]
+pub enum ConditionTest {
+    /// 出力に文字列が含まれる
+    Contains(String),
+    /// 出力が正規表現にマッチする
+    Matches(Regex),
+    /// 出力を JSON として解釈し、指定パス（JSON Pointer）の値が等しい
+    JsonEquals {
+        /// JSON Pointer 形式のパス
+        pointer: String,
+        /// 期待する値
+        value: serde_json::Value,
+    },
+    /// ステップの実行ステータスが一致する
+    Status(ExpectedStatus),
+}
+
+/// 条件で判定するステップの実行ステータス
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedStatus {
+    /// 成功（リトライ後の成功を含む）
+    Success,
+    /// 失敗
+    Failed,
+    /// スキップ
+    Skipped,
+}
+
+impl Condition {
+    /// 判定対象のステップ名を取得
+    pub fn step(&self) -> &str {
+        &self.step
+    }
+
+    /// 判定内容を取得
+    pub fn test(&self) -> &ConditionTest {
+        &self.test
+    }
+
+    /// 判定を反転するかを取得
+    pub fn negate(&self) -> bool {
+        self.negate
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negate {
+            write!(f, "not ")?;
+        }
+        match &self.test {
+            ConditionTest::Contains(text) => {
+                write!(f, "steps.{}.output contains {:?}", self.step, text)
+            }
+            ConditionTest::Matches(regex) => {
+                write!(f, "steps.{}.output matches /{}/", self.step, regex.as_str())
+            }
+            ConditionTest::JsonEquals { pointer, value } => {
+                write!(f, "steps.{}.output{} == {}", self.step, pointer, value)
+            }
+            ConditionTest::Status(status) => {
+                write!(f, "steps.{}.status == {}", self.step, status.as_str())
+            }
+        }
+    }
+}
+
+impl ExpectedStatus {
+    /// 設定ファイルで使用する名前を取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExpectedStatus::Success => "success",
+            ExpectedStatus::Failed => "failed",
+            ExpectedStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// `context` はエラーメッセージの主語（例: `"ステップ 'fix' の when "`）です。
+pub(super) fn parse_condition(context: &str, dto: &ConditionDto) -> Result<Condition, String> {
+    let invalid = |detail: String| format!("{}が不正です: {}", context, detail);
+
+    if dto.step.trim().is_empty() {
+        return Err(invalid("'step' が空です".to_string()));
+    }
+
+    let mut tests = Vec::new();
+    if let Some(text) = &dto.contains {
+        tests.push(ConditionTest::Contains(text.clone()));
+    }
+    if let Some(pattern) = &dto.matches {
+        let regex = Regex::new(pattern)
+            .map_err(|e| invalid(format!("正規表現 '{}' を解釈できません: {}", pattern, e)))?;
+        tests.push(ConditionTest::Matches(regex));
+    }
+    match (&dto.json, &dto.equals) {
+        (Some(path), Some(expected)) => {
+            let value = serde_json::to_value(expected)
+                .map_err(|e| invalid(format!("'equals' の値を変換できません: {}", e)))?;
+            tests.push(ConditionTest::JsonEquals {
+                pointer: json_pointer(path),
+                value,
+            });
+        }
+        (Some(_), None) => return Err(invalid("'json' には 'equals' が必要です".to_string())),
+        (None, Some(_)) => return Err(invalid("'equals' には 'json' が必要です".to_string())),
+        (None, None) => {}
+    }
+    if let Some(status) = &dto.status {
+        let status = match status.to_lowercase().as_str() {
+            "success" => ExpectedStatus::Success,
+            "failed" => ExpectedStatus::Failed,
+            "skipped" => ExpectedStatus::Skipped,
+            _ => {
+                return Err(invalid(format!(
+                    "不正なステータス: '{}' (有効な値: success, failed, skipped)",
+                    status
+                )));
+            }
+        };
+        tests.push(ConditionTest::Status(status));
+    }
+
+    if tests.len() != 1 {
+        return Err(invalid(
+            "'contains', 'matches', 'json', 'status' のいずれか1つを指定してください".to_string(),
+        ));
+    }
+
+    Ok(Condition {
+        step: dto.step.clone(),
+        test: tests.remove(0),
+        negate: dto.negate,
+    })
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<Condition> for ConditionDto {
+    fn from(condition: Condition) -> Self {
+        let mut dto = ConditionDto {
+            step: condition.step,
+            negate: condition.negate,
+            ..Default::default()
+        };
+        match condition.test {
+            ConditionTest::Contains(text) => dto.contains = Some(text),
+            ConditionTest::Matches(regex) => dto.matches = Some(regex.as_str().to_string()),
+            ConditionTest::JsonEquals { pointer, value } => {
+                dto.json = Some(pointer);
+                dto.equals = toml::Value::try_from(value).ok();
+            }
+            ConditionTest::Status(status) => dto.status = Some(status.as_str().to_string()),
+        }
+        dto
+    }
+}
+
+/// ドット区切りのパス（`$.a.b` / `a.b`）を JSON Pointer（`/a/b`）に変換
+pub(super) fn json_pointer(path: &str) -> String {
+    if path.starts_with('/') || path.is_empty() {
+        return path.to_string();
+    }
+
+    let path = path.strip_prefix('$').unwrap_or(path);
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(step: &str) -> ConditionDto {
+        ConditionDto {
+            step: step.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_contains() {
+        let condition = parse_condition(
+            "ステップ 'fix' の when ",
+            &ConditionDto {
+                contains: Some("LGTM".to_string()),
+                negate: true,
+                ..dto("review")
+            },
+        )
+        .unwrap();
+
+        assert_eq!(condition.step(), "review");
+        assert!(condition.negate());
+        assert!(matches!(condition.test(), ConditionTest::Contains(text) if text == "LGTM"));
+        assert_eq!(condition.to_string(), "not steps.review.output contains \"LGTM\"");
+    }
+
+    #[test]
+    fn test_parse_json_equals() {
+        let condition = parse_condition(
+            "ステップ 'fix' の when ",
+            &ConditionDto {
+                json: Some("$.verdict.result".to_string()),
+                equals: Some(toml::Value::String("reject".to_string())),
+                ..dto("review")
+            },
+        )
+        .unwrap();
+
+        match condition.test() {
+            ConditionTest::JsonEquals { pointer, value } => {
+                assert_eq!(pointer, "/verdict/result");
+                assert_eq!(value, &serde_json::json!("reject"));
+            }
+            other => panic!("Expected JsonEquals, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_status_and_regex() {
+        let condition = parse_condition(
+            "ステップ 'fix' の when ",
+            &ConditionDto {
+                status: Some("Skipped".to_string()),
+                ..dto("review")
+            },
+        )
+        .unwrap();
+        assert!(matches!(condition.test(), ConditionTest::Status(ExpectedStatus::Skipped)));
+
+        let err = parse_condition(
+            "ステップ 'fix' の when ",
+            &ConditionDto {
+                matches: Some("(unclosed".to_string()),
+                ..dto("review")
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("正規表現"));
+    }
+
+    #[test]
+    fn test_parse_requires_exactly_one_test() {
+        let err = parse_condition("ステップ 'fix' の when ", &dto("review")).unwrap_err();
+        assert!(err.contains("いずれか1つ"));
+
+        let err = parse_condition(
+            "ステップ 'fix' の when ",
+            &ConditionDto {
+                contains: Some("LGTM".to_string()),
+                status: Some("success".to_string()),
+                ..dto("review")
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("いずれか1つ"));
+
+        let err = parse_condition(
+            "ステップ 'fix' の when ",
+            &ConditionDto {
+                json: Some("/verdict".to_string()),
+                ..dto("review")
+            },
+        )
+        .unwrap_err();
+        assert!(err.contains("'equals' が必要"));
+    }
+
+    #[test]
+    fn test_json_pointer_conversion() {
+        assert_eq!(json_pointer("/a/0"), "/a/0");
+        assert_eq!(json_pointer("$.a.b"), "/a/b");
+        assert_eq!(json_pointer("a.0"), "/a/0");
+        assert_eq!(json_pointer("$"), "");
+    }
+}
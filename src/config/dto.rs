@@ -22,11 +22,15 @@
 //! Workflow (ドメインモデル)
 //! ```
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use toml::Spanned;
 
 /// ワークフロー DTO
 ///
-/// TOML の `[workflow]` セクションと `[[steps]]` 配列をデシリアライズ/シリアライズします。
+/// TOML の `[workflow]` セクション、`[[steps]]` 配列、`[[loops]]` 配列と
+/// `[providers.<name>]`・`[models.<provider>]`・`[prices."<model>"]`・`[budget]` テーブルをデシリアライズ/シリアライズします。
 ///
 /// **注**: この構造体は config モジュール内部の実装詳細です。
 /// 外部からは [`Workflow`](super::workflow::Workflow) を使用してください。
@@ -36,6 +40,21 @@ pub(super) struct WorkflowDto {
     pub(super) workflow: WorkflowMetadataDto,
     /// ステップの配列
     pub(super) steps: Vec<WorkflowStepDto>,
+    /// ステップ範囲の繰り返し (オプション)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) loops: Vec<LoopDto>,
+    /// プロバイダーの定義 (オプション、キーはプロバイダー名)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(super) providers: BTreeMap<String, ProviderDto>,
+    /// モデルティアに対応するモデル名 (オプション、キーはプロバイダー名)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(super) models: BTreeMap<String, TierModelsDto>,
+    /// トークン単価 (オプション、キーはモデル名)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(super) prices: BTreeMap<String, PriceDto>,
+    /// ワークフロー全体の予算 (オプション)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) budget: Option<BudgetDto>,
 }
 
 /// ワークフローメタデータ DTO
@@ -52,7 +71,7 @@ pub(super) struct WorkflowMetadataDto {
 }
 
 /// ワークフローステップ DTO
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub(super) struct WorkflowStepDto {
     /// ステップ名 (必須)
     pub(super) name: String,
@@ -62,12 +81,350 @@ pub(super) struct WorkflowStepDto {
     pub(super) provider: String,
     /// モデルティア (必須)
     pub(super) model_tier: String,
+    /// モデル名 (オプション、モデルティアの対応より優先)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) model: Option<String>,
     /// タイムアウト秒数 (オプション)
     #[serde(default)]
     pub(super) timeout: Option<u64>,
     /// リトライ回数 (オプション)
     #[serde(default)]
     pub(super) retry_count: Option<u32>,
+    /// リトライの待機時間と対象 (オプション、未指定時は既定値)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) retry: Option<RetryDto>,
+    /// ステップへの入力テンプレート (オプション、未指定時は前ステップの出力)
+    #[serde(default)]
+    pub(super) input: Option<String>,
+    /// 実行条件 (オプション、未指定時は常に実行)
+    #[serde(default)]
+    pub(super) when: Option<ConditionDto>,
+    /// 依存するステップ名 (オプション、未指定時は直前のステップ)
+    #[serde(default)]
+    pub(super) depends_on: Option<Vec<String>>,
+    /// 出力の JSON Schema (オプション、インラインテーブルまたはファイルパス)
+    #[serde(default)]
+    pub(super) output_schema: Option<OutputSchemaDto>,
+    /// 出力がスキーマに一致しない場合の修正依頼の回数 (オプション)
+    #[serde(default)]
+    pub(super) repair_attempts: Option<u32>,
+    /// フォールバック先のプロバイダー (オプション、記述順に試す)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) fallback: Vec<FallbackDto>,
+    /// ステップの予算 (オプション)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) budget: Option<BudgetDto>,
+}
+
+/// リトライの方針 DTO
+///
+/// `retry = { initial_delay = 1, multiplier = 2, max_delay = 30, jitter = 0.2, retry_on = ["rate_limit"] }`
+/// の形式で記述します（秒数は小数も指定できます）。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct RetryDto {
+    /// 最初のリトライまでの待機秒数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) initial_delay: Option<f64>,
+    /// リトライごとの待機時間の倍率
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) multiplier: Option<f64>,
+    /// 待機秒数の上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) max_delay: Option<f64>,
+    /// 待機時間を短くする割合の上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) jitter: Option<f64>,
+    /// リトライするエラーの種類
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) retry_on: Option<Vec<String>>,
+}
+
+/// 予算 DTO
+///
+/// ワークフロー全体は `[budget]` テーブル、ステップは
+/// `budget = { max_tokens_total = 50000, max_cost_usd = 0.5, max_wall_time = 300 }` の形式で記述します。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct BudgetDto {
+    /// トークン数の上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) max_tokens_total: Option<u64>,
+    /// コストの上限（米ドル）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) max_cost_usd: Option<f64>,
+    /// 経過時間の上限（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) max_wall_time: Option<u64>,
+}
+
+/// フォールバック先 DTO
+///
+/// `fallback = [{ provider = "openai", model_tier = "medium" }]` の形式で記述します。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct FallbackDto {
+    /// プロバイダー (必須)
+    pub(super) provider: String,
+    /// モデルティア (オプション、未指定時はステップのモデルティア)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) model_tier: Option<String>,
+    /// モデル名 (オプション、モデルティアの対応より優先)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) model: Option<String>,
+}
+
+/// 出力スキーマ DTO
+///
+/// 文字列はスキーマファイルのパス、テーブルはインラインの JSON Schema として扱います。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(super) enum OutputSchemaDto {
+    /// JSON Schema ファイルのパス
+    Path(String),
+    /// インラインの JSON Schema
+    Inline(toml::Table),
+}
+
+/// 実行条件 DTO
+///
+/// `when = { step = "review", contains = "LGTM", negate = true }` の形式で記述します。
+/// 判定キーの組み合わせの検証は [`Condition`](super::condition::Condition) への変換時に行います。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ConditionDto {
+    /// 判定対象のステップ名 (必須)
+    pub(super) step: String,
+    /// 出力に含まれる文字列
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) contains: Option<String>,
+    /// 出力にマッチする正規表現
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) matches: Option<String>,
+    /// JSON のパス（`equals` と組み合わせる）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) json: Option<String>,
+    /// `json` のパスの期待値
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) equals: Option<toml::Value>,
+    /// ステップの実行ステータス
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) status: Option<String>,
+    /// 判定を反転する
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(super) negate: bool,
+}
+
+/// ループ DTO
+///
+/// `[[loops]]` 配列の要素です。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct LoopDto {
+    /// ループ名 (必須)
+    pub(super) name: String,
+    /// 範囲の最初のステップ名 (必須)
+    pub(super) from: String,
+    /// 範囲の最後のステップ名 (必須)
+    pub(super) to: String,
+    /// 最大実行回数 (必須、1回目を含む)
+    pub(super) max_iterations: u32,
+    /// 終了条件 (必須)
+    pub(super) until: ConditionDto,
+}
+
+/// プロバイダー定義 DTO
+///
+/// `[providers.<name>]` テーブルです。`type` で種類を選び、種類ごとのキーを指定します。
+/// 値の検証は [`ProviderDefinition`](super::providers::ProviderDefinition) への変換時に行います。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ProviderDto {
+    /// 種類 (`command` / `openai-compatible`、未指定時は `command`)
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub(super) kind: Option<String>,
+    /// モデルティアごとのモデル名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) models: Option<TierModelsDto>,
+
+    // --- type = "command" ---
+    /// 実行するコマンド (必須)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) command: Option<String>,
+    /// 引数のテンプレート (`{prompt}`, `{system_prompt}`, `{model}` を展開)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) args: Vec<String>,
+    /// プロンプトの渡し方 (`argv` / `stdin`、未指定時は `argv`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) prompt_input: Option<String>,
+    /// 出力の解釈方法 (未指定時はテキストのまま)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) output: Option<CommandOutputDto>,
+    /// 標準エラー出力のエラーパターン
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) errors: Vec<ErrorPatternDto>,
+
+    // --- type = "openai-compatible" ---
+    /// API のベース URL (必須、例: `http://localhost:11434/v1`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) base_url: Option<String>,
+    /// API キーを読み込む環境変数名 (未指定時は認証なし)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) api_key_env: Option<String>,
+    /// 最大出力トークン数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) max_tokens: Option<u32>,
+}
+
+/// コマンド出力の解釈方法 DTO
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct CommandOutputDto {
+    /// 出力形式 (`raw` / `json` / `jsonl`)
+    pub(super) format: String,
+    /// 本文（`jsonl` では各イベントのテキスト）の JSON ポインター
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) text: Option<String>,
+    /// ツール名の JSON ポインター（`jsonl` のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) tool: Option<String>,
+    /// 入力トークン数の JSON ポインター
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) input_tokens: Option<String>,
+    /// 出力トークン数の JSON ポインター
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) output_tokens: Option<String>,
+}
+
+/// モデルティアごとのモデル名 DTO
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct TierModelsDto {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) heavy: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) medium: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) light: Option<String>,
+}
+
+/// トークン単価 DTO（100 万トークンあたりの米ドル）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct PriceDto {
+    /// 入力トークンの単価 (必須)
+    pub(super) input: f64,
+    /// 出力トークンの単価 (必須)
+    pub(super) output: f64,
+    /// キャッシュから読み込んだ入力トークンの単価 (オプション、未指定時は入力の単価)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) cached_input: Option<f64>,
+    /// キャッシュに書き込んだ入力トークンの単価 (オプション、未指定時は入力の単価)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) cache_write: Option<f64>,
+}
+
+/// エラーパターン DTO
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct ErrorPatternDto {
+    /// 標準エラー出力にマッチする正規表現 (必須)
+    pub(super) pattern: String,
+    /// エラーの種類 (`authentication` / `rate_limit` / `timeout` / `error`)
+    pub(super) kind: String,
+}
+
+/// ユーザー設定 DTO（`~/.config/adw/config.toml`）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct UserConfigDto {
+    /// モデルティアに対応するモデル名 (オプション、キーはプロバイダー名)
+    #[serde(default)]
+    pub(super) models: BTreeMap<String, TierModelsDto>,
+    /// トークン単価 (オプション、キーはモデル名)
+    #[serde(default)]
+    pub(super) prices: BTreeMap<String, PriceDto>,
+}
+
+/// 位置情報付きワークフロー DTO（`validate` 用）
+///
+/// [`WorkflowDto`] と同じ TOML を読み込みますが、検証対象のキーを
+/// [`Spanned`] で保持し、エラー箇所をソース上の位置として報告できるようにします。
+/// 必須キーの欠落もエラーとして収集するため、全フィールドを `Option` とします。
+#[derive(Debug, Deserialize)]
+pub(super) struct SpannedWorkflowDto {
+    /// ワークフローのメタデータ
+    #[serde(default)]
+    pub(super) workflow: Option<Spanned<SpannedWorkflowMetadataDto>>,
+    /// ステップの配列
+    #[serde(default)]
+    pub(super) steps: Vec<Spanned<SpannedWorkflowStepDto>>,
+    /// ループの配列
+    #[serde(default)]
+    pub(super) loops: Vec<Spanned<LoopDto>>,
+    /// プロバイダーの定義
+    #[serde(default)]
+    pub(super) providers: BTreeMap<String, Spanned<ProviderDto>>,
+    /// モデルティアに対応するモデル名
+    #[serde(default)]
+    pub(super) models: BTreeMap<String, Spanned<TierModelsDto>>,
+    /// トークン単価
+    #[serde(default)]
+    pub(super) prices: BTreeMap<String, Spanned<PriceDto>>,
+    /// ワークフロー全体の予算
+    #[serde(default)]
+    pub(super) budget: Option<Spanned<BudgetDto>>,
+}
+
+/// 位置情報付きワークフローメタデータ DTO
+#[derive(Debug, Deserialize)]
+pub(super) struct SpannedWorkflowMetadataDto {
+    /// ワークフロー名
+    #[serde(default)]
+    pub(super) name: Option<Spanned<String>>,
+}
+
+/// 位置情報付きワークフローステップ DTO
+#[derive(Debug, Deserialize)]
+pub(super) struct SpannedWorkflowStepDto {
+    /// ステップ名
+    #[serde(default)]
+    pub(super) name: Option<Spanned<String>>,
+    /// システムプロンプト
+    #[serde(default)]
+    pub(super) system_prompt: Option<Spanned<String>>,
+    /// プロバイダー
+    #[serde(default)]
+    pub(super) provider: Option<Spanned<String>>,
+    /// モデルティア
+    #[serde(default)]
+    pub(super) model_tier: Option<Spanned<String>>,
+    /// モデル名
+    #[serde(default)]
+    pub(super) model: Option<Spanned<String>>,
+    /// リトライの方針
+    #[serde(default)]
+    pub(super) retry: Option<Spanned<RetryDto>>,
+    /// 入力テンプレート
+    #[serde(default)]
+    pub(super) input: Option<Spanned<String>>,
+    /// 実行条件
+    #[serde(default)]
+    pub(super) when: Option<Spanned<ConditionDto>>,
+    /// 依存するステップ名
+    #[serde(default)]
+    pub(super) depends_on: Option<Spanned<Vec<String>>>,
+    /// 出力の JSON Schema
+    #[serde(default)]
+    pub(super) output_schema: Option<Spanned<OutputSchemaDto>>,
+    /// 修正依頼の回数
+    #[serde(default)]
+    pub(super) repair_attempts: Option<Spanned<u32>>,
+    /// フォールバック先のプロバイダー
+    #[serde(default)]
+    pub(super) fallback: Option<Spanned<Vec<FallbackDto>>>,
+    /// ステップの予算
+    #[serde(default)]
+    pub(super) budget: Option<Spanned<BudgetDto>>,
 }
 
 #[cfg(test)]
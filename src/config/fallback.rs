@@ -0,0 +1,176 @@
+//! ステップのフォールバック先（`fallback`）
+//!
+//! # 責務
+//!
+//! `[[steps]]` の `fallback` に記述された代替のプロバイダーを検証済みの型 [`Fallback`] として表現する。
+//! 切り替えの判定（[`ProviderError::triggers_fallback`](crate::error::ProviderError::triggers_fallback)）と
+//! 実行は実行エンジンが行う。
+//!
+//! # 記述形式
+//!
+//! プロバイダーがレート制限に達した、CLI がインストールされていない等の理由で使えない場合に、
+//! 記述した順に代替のプロバイダーを試します。
+//!
+//! | キー          | 内容                                              |
+//! |--------------|---------------------------------------------------|
+//! | `provider`   | プロバイダー名（必須）                                |
+//! | `model_tier` | モデルティア（省略時はステップの `model_tier`）          |
+//! | `model`      | モデル名（省略時はモデルティアから解決）                 |
+//!
+//! ```toml
+//! [[steps]]
+//! name = "plan"
+//! system_prompt = "実装計画を作成してください"
+//! provider = "anthropic"
+//! model_tier = "heavy"
+//! fallback = [
+//!     { provider = "openai", model_tier = "medium" },
+//!     { provider = "anthropic-api" },
+//! ]
+//! ```
+
+use crate::provider::ProviderRegistry;
+use super::dto::FallbackDto;
+use super::step::{self, ModelTier, Provider};
+
+/// ステップのフォールバック先（ドメインモデル）
+#[derive(Debug, Clone)]
+pub struct Fallback {
+    /// プロバイダー
+    provider: Provider,
+    /// モデルティア（`None` の場合はステップのモデルティア）
+    model_tier: Option<ModelTier>,
+    /// モデル名
+    model: Option<String>,
+}
+
+impl Fallback {
+    /// プロバイダーを取得
+    pub fn provider(&self) -> &Provider {
+        &self.provider
+    }
+
+    /// モデルティアを取得（`None` の場合はステップのモデルティアを使う）
+    pub fn model_tier(&self) -> Option<&ModelTier> {
+        self.model_tier.as_ref()
+    }
+
+    /// モデル名を取得
+    ///
+    /// `Some` の場合、モデルティアに対応するモデル名より優先して使われます。
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `step_name`: フォールバック先を記述したステップ名
+/// - `dtos`: `fallback` の配列
+/// - `registry`: 使用できるプロバイダーの登録簿
+pub(super) fn parse_fallbacks(
+    step_name: &str,
+    dtos: &[FallbackDto],
+    registry: &ProviderRegistry,
+) -> Result<Vec<Fallback>, String> {
+    dtos.iter()
+        .enumerate()
+        .map(|(position, dto)| {
+            let invalid = |detail: String| format!("ステップ '{}' の fallback[{}] の{}", step_name, position, detail);
+
+            if !registry.contains(&dto.provider) {
+                return Err(invalid(format!(
+                    "不正なプロバイダー: '{}' (有効な値: {})",
+                    dto.provider,
+                    registry.names().collect::<Vec<_>>().join(", ")
+                )));
+            }
+            let model_tier = dto
+                .model_tier
+                .as_deref()
+                .map(|tier| {
+                    step::parse_model_tier(step_name, tier).map_err(|_| {
+                        invalid(format!("不正なモデルティア: '{}' (有効な値: heavy, medium, light)", tier))
+                    })
+                })
+                .transpose()?;
+            if dto.model.as_deref().is_some_and(|model| model.trim().is_empty()) {
+                return Err(invalid(" model が空です".to_string()));
+            }
+
+            Ok(Fallback {
+                provider: Provider::new(&dto.provider),
+                model_tier,
+                model: dto.model.clone(),
+            })
+        })
+        .collect()
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<Fallback> for FallbackDto {
+    fn from(fallback: Fallback) -> Self {
+        FallbackDto {
+            provider: fallback.provider.as_str().to_string(),
+            model_tier: fallback.model_tier.map(|tier| tier.as_str().to_string()),
+            model: fallback.model,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dtos(toml: &str) -> Vec<FallbackDto> {
+        #[derive(serde::Deserialize)]
+        struct Wrapper {
+            fallback: Vec<FallbackDto>,
+        }
+        toml::from_str::<Wrapper>(toml).unwrap().fallback
+    }
+
+    #[test]
+    fn test_parse_fallbacks() {
+        let fallbacks = parse_fallbacks(
+            "plan",
+            &dtos(r#"fallback = [
+                { provider = "OpenAI", model_tier = "Medium" },
+                { provider = "anthropic-api", model = "claude-opus-4-1" },
+            ]"#),
+            &ProviderRegistry::builtin(),
+        )
+        .unwrap();
+
+        assert_eq!(fallbacks.len(), 2);
+        assert_eq!(fallbacks[0].provider().as_str(), "openai");
+        assert!(matches!(fallbacks[0].model_tier(), Some(ModelTier::Medium)));
+        assert_eq!(fallbacks[0].model(), None);
+        assert!(fallbacks[1].model_tier().is_none());
+        assert_eq!(fallbacks[1].model(), Some("claude-opus-4-1"));
+
+        // DTO に戻すと名前は小文字に正規化される
+        let dto = FallbackDto::from(fallbacks[0].clone());
+        assert_eq!(dto.provider, "openai");
+        assert_eq!(dto.model_tier.as_deref(), Some("medium"));
+    }
+
+    #[test]
+    fn test_parse_fallbacks_errors() {
+        let cases = [
+            (r#"fallback = [{ provider = "gemini" }]"#, "ステップ 'plan' の fallback[0] の不正なプロバイダー: 'gemini'"),
+            (
+                r#"fallback = [{ provider = "openai" }, { provider = "openai", model_tier = "ultra" }]"#,
+                "ステップ 'plan' の fallback[1] の不正なモデルティア: 'ultra'",
+            ),
+            (r#"fallback = [{ provider = "openai", model = "" }]"#, "ステップ 'plan' の fallback[0] の model が空です"),
+        ];
+
+        for (toml, expected) in cases {
+            let err = parse_fallbacks("plan", &dtos(toml), &ProviderRegistry::builtin()).unwrap_err();
+            assert!(err.contains(expected), "{} does not contain {}", err, expected);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! ステップの依存関係（`depends_on`）
+//!
+//! # 責務
+//!
+//! `[[steps]]` の `depends_on` を解決し、ステップ間の依存関係グラフを検証する。
+//!
+//! - 依存先の解決（存在しないステップ・自己依存の検出）
+//! - 循環依存の検出
+//! - 祖先（直接・間接の依存先）の列挙
+//! - ループ範囲を外から経由する依存の検出
+//!
+//! # 記述形式
+//!
+//! `depends_on` を省略したステップは、直前に定義されたステップに依存します（最初のステップは依存なし）。
+//! `depends_on = []` と明示すると、ワークフロー開始直後から実行できるステップになります。
+//!
+//! ```toml
+//! [[steps]]
+//! name = "security-review"
+//! depends_on = ["implement"]
+//! # ...
+//!
+//! [[steps]]
+//! name = "style-review"
+//! depends_on = ["implement"]   # security-review と並列に実行される
+//! # ...
+//!
+//! [[steps]]
+//! name = "summarize"
+//! depends_on = ["security-review", "style-review"]
+//! # ...
+//! ```
+
+use super::loops::StepLoop;
+
+/// ステップの依存先（インデックス）を解決
+///
+/// # 引数
+///
+/// - `step_name`: 対象のステップ名
+/// - `index`: 対象のステップのインデックス
+/// - `depends_on`: `depends_on` の指定（省略時は `None`）
+/// - `all`: ワークフロー内のすべてのステップ名（定義順）
+///
+/// # 戻り値
+///
+/// 依存先のインデックス（`depends_on` の記述順、重複は除去）
+pub(super) fn resolve_dependencies(
+    step_name: &str,
+    index: usize,
+    depends_on: Option<&[String]>,
+    all: &[&str],
+) -> Result<Vec<usize>, String> {
+    let Some(depends_on) = depends_on else {
+        return Ok(index.checked_sub(1).into_iter().collect());
+    };
+
+    let mut dependencies = Vec::new();
+    for dependency in depends_on {
+        if dependency == step_name {
+            return Err(format!(
+                "ステップ '{}' の depends_on に自分自身が指定されています",
+                step_name
+            ));
+        }
+        let position = all.iter().position(|name| name == dependency).ok_or_else(|| {
+            format!(
+                "ステップ '{}' の depends_on に存在しないステップ '{}' が指定されています",
+                step_name, dependency
+            )
+        })?;
+        if !dependencies.contains(&position) {
+            dependencies.push(position);
+        }
+    }
+    Ok(dependencies)
+}
+
+/// 循環依存を検出
+///
+/// # 戻り値
+///
+/// 循環が見つかった場合、循環を構成するステップのインデックス（始点を末尾にも含む）
+pub(super) fn find_cycle(dependencies: &[Vec<usize>]) -> Option<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        Visiting,
+        Done,
+    }
+
+    fn visit(index: usize, dependencies: &[Vec<usize>], marks: &mut [Mark], path: &mut Vec<usize>) -> Option<Vec<usize>> {
+        marks[index] = Mark::Visiting;
+        path.push(index);
+
+        for &dependency in &dependencies[index] {
+            match marks[dependency] {
+                Mark::Visiting => {
+                    let start = path.iter().position(|&i| i == dependency).unwrap_or(0);
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(dependency);
+                    return Some(cycle);
+                }
+                Mark::Unvisited => {
+                    if let Some(cycle) = visit(dependency, dependencies, marks, path) {
+                        return Some(cycle);
+                    }
+                }
+                Mark::Done => {}
+            }
+        }
+
+        path.pop();
+        marks[index] = Mark::Done;
+        None
+    }
+
+    let mut marks = vec![Mark::Unvisited; dependencies.len()];
+    (0..dependencies.len()).find_map(|index| {
+        (marks[index] == Mark::Unvisited)
+            .then(|| visit(index, dependencies, &mut marks, &mut Vec::new()))
+            .flatten()
+    })
+}
+
+/// 循環依存のエラーメッセージ
+///
+/// 依存の向き（依存元 → 依存先）に並べて表示します。
+pub(super) fn cycle_message(cycle: &[usize], all: &[&str]) -> String {
+    let path: Vec<&str> = cycle.iter().map(|&index| all[index]).collect();
+    format!("ステップの依存関係が循環しています: {}", path.join(" -> "))
+}
+
+/// 指定ステップの祖先（直接・間接の依存先）を列挙
+///
+/// 循環がある場合も終了します。結果はインデックス順です。
+pub(super) fn ancestors(dependencies: &[Vec<usize>], index: usize) -> Vec<usize> {
+    let mut visited = vec![false; dependencies.len()];
+    let mut stack = dependencies[index].clone();
+    while let Some(current) = stack.pop() {
+        if !visited[current] {
+            visited[current] = true;
+            stack.extend(&dependencies[current]);
+        }
+    }
+    (0..dependencies.len()).filter(|&i| visited[i]).collect()
+}
+
+/// ループ範囲のステップが、ループ外のステップを経由してループ内のステップに依存していないか検証
+///
+/// このような依存があると、ループ外のステップはループの完了を、ループはそのステップの完了を
+/// 待つことになり、実行できません。
+pub(super) fn check_loop_dependencies(
+    step_loop: &StepLoop,
+    dependencies: &[Vec<usize>],
+    all: &[&str],
+) -> Result<(), String> {
+    for index in step_loop.start()..=step_loop.end() {
+        for &dependency in &dependencies[index] {
+            if step_loop.contains(dependency) {
+                continue;
+            }
+            if ancestors(dependencies, dependency).iter().any(|&i| step_loop.contains(i)) {
+                return Err(format!(
+                    "ループ '{}' のステップ '{}' が、ループ外のステップ '{}' を経由してループ内のステップに依存しています",
+                    step_loop.name(),
+                    all[index],
+                    all[dependency]
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: &[&str] = &["plan", "implement", "security", "style", "summarize"];
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_resolve_implicit_and_explicit() {
+        assert_eq!(resolve_dependencies("plan", 0, None, STEPS).unwrap(), Vec::<usize>::new());
+        assert_eq!(resolve_dependencies("style", 3, None, STEPS).unwrap(), vec![2]);
+
+        let depends_on = names(&["security", "style", "security"]);
+        assert_eq!(
+            resolve_dependencies("summarize", 4, Some(&depends_on), STEPS).unwrap(),
+            vec![2, 3]
+        );
+        assert!(resolve_dependencies("implement", 1, Some(&[]), STEPS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_invalid() {
+        let err = resolve_dependencies("style", 3, Some(&names(&["lint"])), STEPS).unwrap_err();
+        assert!(err.contains("存在しないステップ 'lint'"));
+
+        let err = resolve_dependencies("style", 3, Some(&names(&["style"])), STEPS).unwrap_err();
+        assert!(err.contains("自分自身"));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let acyclic = vec![vec![], vec![0], vec![1], vec![1], vec![2, 3]];
+        assert!(find_cycle(&acyclic).is_none());
+
+        // plan -> summarize -> style -> plan
+        let cyclic = vec![vec![4], vec![0], vec![1], vec![0], vec![3]];
+        let cycle = find_cycle(&cyclic).unwrap();
+        assert_eq!(cycle, vec![0, 4, 3, 0]);
+        assert_eq!(
+            cycle_message(&cycle, STEPS),
+            "ステップの依存関係が循環しています: plan -> summarize -> style -> plan"
+        );
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let dependencies = vec![vec![], vec![0], vec![1], vec![1], vec![2, 3]];
+        assert_eq!(ancestors(&dependencies, 4), vec![0, 1, 2, 3]);
+        assert_eq!(ancestors(&dependencies, 3), vec![0, 1]);
+        assert!(ancestors(&dependencies, 0).is_empty());
+    }
+}
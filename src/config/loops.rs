@@ -0,0 +1,252 @@
+//! ステップ範囲の繰り返し（`[[loops]]`）
+//!
+//! # 責務
+//!
+//! 「実装 → レビュー → 修正」のように、連続したステップ範囲を終了条件を満たすまで
+//! 繰り返す定義を、検証済みの型 [`StepLoop`] として表現する。
+//!
+//! # 記述形式
+//!
+//! ```toml
+//! [[loops]]
+//! name = "review-fix"
+//! from = "implement"          # 範囲の最初のステップ
+//! to = "review"               # 範囲の最後のステップ
+//! max_iterations = 3          # 最大実行回数（1回目を含む）
+//! until = { step = "review", contains = "LGTM" }
+//! ```
+//!
+//! `to` のステップを実行した後に `until` を評価し、満たされていなければ `from` に戻ります。
+//! `until` の記述形式は [`condition`](super::condition) と同じで、範囲内のステップを参照します。
+//! 2回目以降の実行は、修正回数（revision）として記録されます。
+//!
+//! ループ同士の範囲が重なることはできません。
+
+use super::condition::{self, Condition};
+use super::dto::LoopDto;
+
+/// ステップ範囲の繰り返し（ドメインモデル）
+#[derive(Debug, Clone)]
+pub struct StepLoop {
+    /// ループ名
+    name: String,
+    /// 範囲の最初のステップ名
+    from: String,
+    /// 範囲の最後のステップ名
+    to: String,
+    /// 範囲の最初のステップのインデックス
+    start: usize,
+    /// 範囲の最後のステップのインデックス
+    end: usize,
+    /// 最大実行回数（1回目を含む）
+    max_iterations: u32,
+    /// 終了条件
+    until: Condition,
+}
+
+impl StepLoop {
+    /// ループ名を取得
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 範囲の最初のステップ名を取得
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    /// 範囲の最後のステップ名を取得
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// 範囲の最初のステップのインデックスを取得
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// 範囲の最後のステップのインデックスを取得
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// 最大実行回数（1回目を含む）を取得
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+
+    /// 終了条件を取得
+    pub fn until(&self) -> &Condition {
+        &self.until
+    }
+
+    /// 指定インデックスのステップがループ範囲に含まれるか
+    pub fn contains(&self, index: usize) -> bool {
+        (self.start..=self.end).contains(&index)
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `dto`: ループ定義
+/// - `step_names`: ワークフローのステップ名（定義順）
+pub(super) fn parse_loop(dto: &LoopDto, step_names: &[&str]) -> Result<StepLoop, String> {
+    if dto.name.trim().is_empty() {
+        return Err("ループ名が空です".to_string());
+    }
+
+    let position = |key: &str, step_name: &str| {
+        step_names
+            .iter()
+            .position(|name| *name == step_name)
+            .ok_or_else(|| {
+                format!(
+                    "ループ '{}' の {} に存在しないステップ '{}' が指定されています",
+                    dto.name, key, step_name
+                )
+            })
+    };
+    let start = position("from", &dto.from)?;
+    let end = position("to", &dto.to)?;
+
+    if start > end {
+        return Err(format!(
+            "ループ '{}' の from '{}' が to '{}' より後に定義されています",
+            dto.name, dto.from, dto.to
+        ));
+    }
+
+    if dto.max_iterations == 0 {
+        return Err(format!(
+            "ループ '{}' の max_iterations は1以上を指定してください",
+            dto.name
+        ));
+    }
+
+    let until = condition::parse_condition(&format!("ループ '{}' の until ", dto.name), &dto.until)?;
+    if !step_names[start..=end].contains(&until.step()) {
+        return Err(format!(
+            "ループ '{}' の until はループ範囲内のステップを参照してください: '{}'",
+            dto.name,
+            until.step()
+        ));
+    }
+
+    Ok(StepLoop {
+        name: dto.name.clone(),
+        from: dto.from.clone(),
+        to: dto.to.clone(),
+        start,
+        end,
+        max_iterations: dto.max_iterations,
+        until,
+    })
+}
+
+/// 先に定義されたループと名前・範囲が重複していないか検証
+pub(super) fn check_against_previous(step_loop: &StepLoop, previous: &[StepLoop]) -> Result<(), String> {
+    for other in previous {
+        if other.name == step_loop.name {
+            return Err(format!("重複するループ名があります: '{}'", step_loop.name));
+        }
+        if other.start <= step_loop.end && step_loop.start <= other.end {
+            return Err(format!(
+                "ループ '{}' の範囲がループ '{}' と重なっています",
+                step_loop.name, other.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<StepLoop> for LoopDto {
+    fn from(step_loop: StepLoop) -> Self {
+        LoopDto {
+            name: step_loop.name,
+            from: step_loop.from,
+            to: step_loop.to,
+            max_iterations: step_loop.max_iterations,
+            until: step_loop.until.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::dto::ConditionDto;
+
+    const STEPS: &[&str] = &["plan", "implement", "review", "summarize"];
+
+    fn loop_dto(name: &str, from: &str, to: &str) -> LoopDto {
+        LoopDto {
+            name: name.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            max_iterations: 3,
+            until: ConditionDto {
+                step: to.to_string(),
+                contains: Some("LGTM".to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_loop() {
+        let step_loop = parse_loop(&loop_dto("review-fix", "implement", "review"), STEPS).unwrap();
+
+        assert_eq!(step_loop.name(), "review-fix");
+        assert_eq!(step_loop.start(), 1);
+        assert_eq!(step_loop.end(), 2);
+        assert_eq!(step_loop.max_iterations(), 3);
+        assert_eq!(step_loop.until().step(), "review");
+        assert!(step_loop.contains(1));
+        assert!(!step_loop.contains(3));
+    }
+
+    #[test]
+    fn test_parse_loop_invalid_range() {
+        let err = parse_loop(&loop_dto("l", "implement", "deploy"), STEPS).unwrap_err();
+        assert!(err.contains("存在しないステップ 'deploy'"));
+
+        let err = parse_loop(&loop_dto("l", "review", "implement"), STEPS).unwrap_err();
+        assert!(err.contains("より後に定義"));
+    }
+
+    #[test]
+    fn test_parse_loop_invalid_until() {
+        let mut dto = loop_dto("l", "implement", "review");
+        dto.until.step = "summarize".to_string();
+        let err = parse_loop(&dto, STEPS).unwrap_err();
+        assert!(err.contains("ループ範囲内"));
+
+        let mut dto = loop_dto("l", "implement", "review");
+        dto.until.contains = None;
+        let err = parse_loop(&dto, STEPS).unwrap_err();
+        assert!(err.starts_with("ループ 'l' の until が不正です"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_loop_zero_iterations() {
+        let mut dto = loop_dto("l", "implement", "review");
+        dto.max_iterations = 0;
+        assert!(parse_loop(&dto, STEPS).unwrap_err().contains("max_iterations"));
+    }
+
+    #[test]
+    fn test_check_against_previous() {
+        let first = parse_loop(&loop_dto("a", "plan", "implement"), STEPS).unwrap();
+        let overlapping = parse_loop(&loop_dto("b", "implement", "review"), STEPS).unwrap();
+        let disjoint = parse_loop(&loop_dto("c", "review", "summarize"), STEPS).unwrap();
+        let duplicate = parse_loop(&loop_dto("a", "summarize", "summarize"), STEPS).unwrap();
+
+        let previous = vec![first];
+        assert!(check_against_previous(&overlapping, &previous).unwrap_err().contains("重なっています"));
+        assert!(check_against_previous(&disjoint, &previous).is_ok());
+        assert!(check_against_previous(&duplicate, &previous).unwrap_err().contains("重複するループ名"));
+    }
+}
@@ -0,0 +1,230 @@
+//! プロバイダーごとのモデル名の対応表（`[models.<provider>]`）
+//!
+//! # 責務
+//!
+//! - モデルティアからモデル名への対応をプロバイダーごとに保持する [`ModelTable`] を提供する
+//! - ワークフロー定義とユーザー設定（[`UserConfig`](super::user::UserConfig)）の `[models]` を検証・変換する
+//!
+//! 組み込みのモデル名（[`model_tier`](crate::provider::model_tier)）はモデルの更新に追従しないため、
+//! 再コンパイルせずに差し替えられるようにします。
+//!
+//! ```toml
+//! [models.anthropic]
+//! heavy = "claude-opus-4-1"
+//!
+//! [models.openai]
+//! medium = "gpt-4.1"
+//! light = "gpt-4.1-mini"
+//! ```
+//!
+//! # 優先順位
+//!
+//! ステップで使うモデル名は次の順に決まります（[`select_model`]）。
+//!
+//! 1. ステップの `model`
+//! 2. ワークフロー定義の `[models.<provider>]`
+//! 3. ユーザー設定（`~/.config/adw/config.toml`）の `[models.<provider>]`
+//! 4. プロバイダーの既定のモデル名
+
+use std::collections::BTreeMap;
+
+use super::dto::TierModelsDto;
+use super::fallback::Fallback;
+use super::providers::TierModels;
+use super::step::{ModelTier, WorkflowStep};
+
+/// プロバイダーごとのモデル名の対応表
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModelTable {
+    /// プロバイダー名（小文字）ごとのモデル名
+    providers: BTreeMap<String, TierModels>,
+}
+
+impl ModelTable {
+    /// プロバイダーとモデルティアに対応するモデル名を取得
+    ///
+    /// プロバイダー名は大文字小文字を区別しません。
+    pub fn get(&self, provider: &str, tier: &ModelTier) -> Option<&str> {
+        self.providers
+            .get(&provider.to_lowercase())
+            .and_then(|models| models.get(tier))
+    }
+
+    /// 対応表を持つプロバイダー名（小文字）を列挙
+    pub fn providers(&self) -> impl Iterator<Item = &str> {
+        self.providers.keys().map(String::as_str)
+    }
+
+    /// 対応表が空か
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+/// ステップで使うモデル名を決める
+///
+/// ステップの `model`、ワークフロー定義の対応表、ユーザー設定の対応表の順に探します。
+/// いずれにもない場合は `None`（プロバイダーの既定のモデル名を使う）です。
+///
+/// # 引数
+///
+/// - `step`: 実行するステップ
+/// - `workflow_models`: ワークフロー定義の `[models]`
+/// - `user_models`: ユーザー設定の `[models]`
+pub fn select_model<'a>(
+    step: &'a WorkflowStep,
+    workflow_models: &'a ModelTable,
+    user_models: &'a ModelTable,
+) -> Option<&'a str> {
+    select(step.model(), step.provider().as_str(), step.model_tier(), workflow_models, user_models)
+}
+
+/// フォールバック先で使うモデル名を決める
+///
+/// [`select_model`] と同じ順に、フォールバック先の `model` とプロバイダー・モデルティアで探します。
+/// フォールバック先のモデルティアが未指定の場合はステップのモデルティアを使います。
+pub fn select_fallback_model<'a>(
+    step: &'a WorkflowStep,
+    fallback: &'a Fallback,
+    workflow_models: &'a ModelTable,
+    user_models: &'a ModelTable,
+) -> Option<&'a str> {
+    let tier = fallback.model_tier().unwrap_or(step.model_tier());
+    select(fallback.model(), fallback.provider().as_str(), tier, workflow_models, user_models)
+}
+
+fn select<'a>(
+    model: Option<&'a str>,
+    provider: &str,
+    tier: &ModelTier,
+    workflow_models: &'a ModelTable,
+    user_models: &'a ModelTable,
+) -> Option<&'a str> {
+    model
+        .or_else(|| workflow_models.get(provider, tier))
+        .or_else(|| user_models.get(provider, tier))
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `dto`: `[models]` テーブル（キーはプロバイダー名）
+/// - `check_provider`: プロバイダー名の検証（ワークフロー定義では登録済みの名前のみ許可する）
+pub(super) fn parse_model_table(
+    dto: &BTreeMap<String, TierModelsDto>,
+    check_provider: impl Fn(&str) -> Result<(), String>,
+) -> Result<ModelTable, String> {
+    let mut providers = BTreeMap::new();
+    for (provider, models) in dto {
+        providers.insert(provider.to_lowercase(), parse_tier_models(provider, models, &check_provider)?);
+    }
+    Ok(ModelTable { providers })
+}
+
+/// 1つのプロバイダーの対応表の変換
+pub(super) fn parse_tier_models(
+    provider: &str,
+    dto: &TierModelsDto,
+    check_provider: impl Fn(&str) -> Result<(), String>,
+) -> Result<TierModels, String> {
+    check_provider(provider)?;
+    let names = [("heavy", &dto.heavy), ("medium", &dto.medium), ("light", &dto.light)];
+    if let Some((tier, _)) = names
+        .iter()
+        .find(|(_, model)| model.as_deref().is_some_and(|model| model.trim().is_empty()))
+    {
+        return Err(format!("[models.{}] の {} が空です", provider, tier));
+    }
+    Ok(TierModels::from_dto(Some(dto)))
+}
+
+/// `[models]` のプロバイダーが登録されていない場合のエラーメッセージ
+pub(super) fn unknown_provider_message(provider: &str, valid: &[&str]) -> String {
+    format!(
+        "[models.{}] のプロバイダーが登録されていません (有効な値: {})",
+        provider,
+        valid.join(", ")
+    )
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<ModelTable> for BTreeMap<String, TierModelsDto> {
+    fn from(table: ModelTable) -> Self {
+        table
+            .providers
+            .into_iter()
+            .filter_map(|(provider, models)| models.into_dto().map(|dto| (provider, dto)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(toml: &str) -> Result<ModelTable, String> {
+        let dto: BTreeMap<String, TierModelsDto> = toml::from_str(toml).unwrap();
+        parse_model_table(&dto, |provider| {
+            if provider.eq_ignore_ascii_case("anthropic") || provider == "openai" {
+                Ok(())
+            } else {
+                Err(unknown_provider_message(provider, &["anthropic", "openai"]))
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_model_table() {
+        let models = table(r#"
+[Anthropic]
+heavy = "claude-opus-4-1"
+
+[openai]
+light = "gpt-4.1-mini"
+"#)
+        .unwrap();
+
+        assert_eq!(models.get("anthropic", &ModelTier::Heavy), Some("claude-opus-4-1"));
+        assert_eq!(models.get("ANTHROPIC", &ModelTier::Heavy), Some("claude-opus-4-1"));
+        assert_eq!(models.get("anthropic", &ModelTier::Light), None);
+        assert_eq!(models.get("openai", &ModelTier::Light), Some("gpt-4.1-mini"));
+        assert_eq!(models.providers().collect::<Vec<_>>(), ["anthropic", "openai"]);
+
+        // DTO に戻しても同じ内容
+        let dto: BTreeMap<String, TierModelsDto> = models.clone().into();
+        assert_eq!(parse_model_table(&dto, |_| Ok(())).unwrap(), models);
+    }
+
+    #[test]
+    fn test_parse_model_table_errors() {
+        let err = table("[gemini]\nheavy = \"gemini-2.5-pro\"").unwrap_err();
+        assert!(err.contains("[models.gemini] のプロバイダーが登録されていません"), "{}", err);
+
+        let err = table("[openai]\nmedium = \" \"").unwrap_err();
+        assert!(err.contains("[models.openai] の medium が空です"), "{}", err);
+    }
+
+    #[test]
+    fn test_select_model_precedence() {
+        let step = |model: Option<&str>| {
+            let mut dto = crate::config::dto::WorkflowStepDto {
+                name: "plan".to_string(),
+                system_prompt: "Plan".to_string(),
+                provider: "anthropic".to_string(),
+                model_tier: "heavy".to_string(),
+                ..Default::default()
+            };
+            dto.model = model.map(str::to_string);
+            WorkflowStep::try_from(dto).unwrap()
+        };
+        let workflow_models = table("[anthropic]\nheavy = \"workflow-model\"").unwrap();
+        let user_models = table("[anthropic]\nheavy = \"user-model\"\nlight = \"user-light\"").unwrap();
+        let empty = ModelTable::default();
+
+        assert_eq!(select_model(&step(Some("step-model")), &workflow_models, &user_models), Some("step-model"));
+        assert_eq!(select_model(&step(None), &workflow_models, &user_models), Some("workflow-model"));
+        assert_eq!(select_model(&step(None), &empty, &user_models), Some("user-model"));
+        assert_eq!(select_model(&step(None), &empty, &empty), None);
+    }
+}
@@ -0,0 +1,359 @@
+//! モデルごとのトークン単価（`[prices."<model>"]`）
+//!
+//! # 責務
+//!
+//! - モデル名からトークン単価 [`ModelPrice`] を引く価格表 [`PriceTable`] を提供する
+//! - ワークフロー定義とユーザー設定（[`UserConfig`](super::user::UserConfig)）の `[prices]` を検証・変換する
+//! - トークン使用量から米ドル建てのコストを計算する
+//!
+//! 単価は 100 万トークンあたりの米ドルで指定します。
+//!
+//! ```toml
+//! [prices."claude-sonnet-4-5"]
+//! input = 3.0
+//! output = 15.0
+//! cached_input = 0.3   # キャッシュから読み込んだ入力トークンの単価（省略時は input と同じ）
+//! cache_write = 3.75   # キャッシュに書き込んだ入力トークンの単価（省略時は input と同じ）
+//!
+//! [prices."qwen2.5-coder"]
+//! input = 0
+//! output = 0
+//! ```
+//!
+//! # モデル名の照合
+//!
+//! ステップで実際に使ったモデル名（プロバイダーが返した名前、なければ解決したモデル名）で価格表を引きます。
+//! 完全に一致する名前がなければ、日付やスナップショットの接尾辞（`-20250929`・`-2024-08-06`・`-0613`・`-latest`）を
+//! 除いた名前を使います（`claude-sonnet-4-5` の単価は `claude-sonnet-4-5-20250929` にも適用されます）。
+//! `o1-pro` や `claude-opus-4-5` のように別のモデルを表す接尾辞には、`o1` や `claude-opus-4` の単価を適用しません。
+//!
+//! 価格は次の順に探します（[`select_price`]）。
+//!
+//! 1. ワークフロー定義の `[prices]`
+//! 2. ユーザー設定（`~/.config/adw/config.toml`）の `[prices]`
+//! 3. 組み込みの単価（組み込みのモデル名のみ。実際の価格と異なる場合は上書きしてください）
+
+use std::collections::BTreeMap;
+
+use crate::provider::TokenUsage;
+use super::dto::PriceDto;
+
+/// 100 万トークン
+const TOKENS_PER_UNIT: f64 = 1_000_000.0;
+
+/// 組み込みの単価の行（モデル名, 入力, 出力, キャッシュ入力, キャッシュ書き込み）
+type BuiltinPrice = (&'static str, f64, f64, Option<f64>, Option<f64>);
+
+/// 組み込みの単価
+const BUILTIN_PRICES: &[BuiltinPrice] = &[
+    ("claude-opus-4", 15.0, 75.0, Some(1.5), Some(18.75)),
+    ("claude-opus-4-0", 15.0, 75.0, Some(1.5), Some(18.75)),
+    ("claude-opus-4-1", 15.0, 75.0, Some(1.5), Some(18.75)),
+    ("claude-opus-4-5", 5.0, 25.0, Some(0.5), Some(6.25)),
+    ("claude-sonnet-4", 3.0, 15.0, Some(0.3), Some(3.75)),
+    ("claude-sonnet-4-0", 3.0, 15.0, Some(0.3), Some(3.75)),
+    ("claude-sonnet-4-5", 3.0, 15.0, Some(0.3), Some(3.75)),
+    ("claude-haiku", 0.8, 4.0, Some(0.08), Some(1.0)),
+    ("claude-haiku-4-5", 1.0, 5.0, Some(0.1), Some(1.25)),
+    ("o1", 15.0, 60.0, Some(7.5), None),
+    ("o1-mini", 1.1, 4.4, Some(0.55), None),
+    ("gpt-4o", 2.5, 10.0, Some(1.25), None),
+    ("gpt-4o-mini", 0.15, 0.6, Some(0.075), None),
+];
+
+/// 1つのモデルのトークン単価（100 万トークンあたりの米ドル）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    /// 入力トークンの単価
+    input: f64,
+    /// 出力トークンの単価
+    output: f64,
+    /// キャッシュから読み込んだ入力トークンの単価（`None` の場合は入力と同じ）
+    cached_input: Option<f64>,
+    /// キャッシュに書き込んだ入力トークンの単価（`None` の場合は入力と同じ）
+    cache_write: Option<f64>,
+}
+
+impl ModelPrice {
+    /// 入力トークンの単価を取得
+    pub fn input(&self) -> f64 {
+        self.input
+    }
+
+    /// 出力トークンの単価を取得
+    pub fn output(&self) -> f64 {
+        self.output
+    }
+
+    /// キャッシュから読み込んだ入力トークンの単価を取得（未指定の場合は入力の単価）
+    pub fn cached_input(&self) -> f64 {
+        self.cached_input.unwrap_or(self.input)
+    }
+
+    /// キャッシュに書き込んだ入力トークンの単価を取得（未指定の場合は入力の単価）
+    pub fn cache_write(&self) -> f64 {
+        self.cache_write.unwrap_or(self.input)
+    }
+
+    /// トークン使用量のコスト（米ドル）
+    ///
+    /// キャッシュから読み込んだ・キャッシュに書き込んだ入力トークンはそれぞれの単価で計算します。
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// use melted_adw::config::prices::{select_price, PriceTable};
+    /// use melted_adw::provider::TokenUsage;
+    ///
+    /// let empty = PriceTable::default();
+    /// let price = select_price("claude-sonnet-4-5", &empty, &empty).unwrap();
+    /// let usage = TokenUsage { input_tokens: 1_000_000, output_tokens: 100_000, ..Default::default() };
+    /// assert!((price.cost(&usage) - 4.5).abs() < 1e-9);
+    ///
+    /// // キャッシュからの読み込みは入力の 1/10 の単価
+    /// let cached = TokenUsage { cache_read_tokens: 1_000_000, ..Default::default() };
+    /// assert!((price.cost(&cached) - 0.3).abs() < 1e-9);
+    /// ```
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (f64::from(usage.input_tokens) * self.input
+            + f64::from(usage.cache_read_tokens) * self.cached_input()
+            + f64::from(usage.cache_creation_tokens) * self.cache_write()
+            + f64::from(usage.output_tokens) * self.output)
+            / TOKENS_PER_UNIT
+    }
+
+    /// 入力トークンのみのコスト（米ドル、実行前の見積もりに使う）
+    pub fn input_cost(&self, input_tokens: u64) -> f64 {
+        input_tokens as f64 * self.input / TOKENS_PER_UNIT
+    }
+}
+
+/// モデル名ごとのトークン単価の価格表
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceTable {
+    /// モデル名ごとの単価
+    models: BTreeMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    /// モデル名に対応する単価を取得
+    ///
+    /// 完全に一致する名前がなければ、日付やスナップショットの接尾辞を除いた名前の単価です。
+    pub fn get(&self, model: &str) -> Option<&ModelPrice> {
+        self.models
+            .get(model)
+            .or_else(|| self.models.iter().find(|(name, _)| matches_snapshot(name, model)).map(|(_, price)| price))
+    }
+
+    /// 価格表を持つモデル名を列挙
+    pub fn models(&self) -> impl Iterator<Item = &str> {
+        self.models.keys().map(String::as_str)
+    }
+
+    /// 価格表が空か
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+/// モデル名の単価を決める
+///
+/// ワークフロー定義の価格表、ユーザー設定の価格表、組み込みの単価の順に探します。
+///
+/// # 引数
+///
+/// - `model`: ステップで使ったモデル名
+/// - `workflow_prices`: ワークフロー定義の `[prices]`
+/// - `user_prices`: ユーザー設定の `[prices]`
+pub fn select_price(model: &str, workflow_prices: &PriceTable, user_prices: &PriceTable) -> Option<ModelPrice> {
+    workflow_prices
+        .get(model)
+        .or_else(|| user_prices.get(model))
+        .copied()
+        .or_else(|| builtin_price(model))
+}
+
+/// 組み込みの単価
+fn builtin_price(model: &str) -> Option<ModelPrice> {
+    BUILTIN_PRICES
+        .iter()
+        .find(|(name, ..)| *name == model)
+        .or_else(|| BUILTIN_PRICES.iter().find(|(name, ..)| matches_snapshot(name, model)))
+        .map(|&(_, input, output, cached_input, cache_write)| ModelPrice {
+            input,
+            output,
+            cached_input,
+            cache_write,
+        })
+}
+
+/// `model` が `name` に日付やスナップショットの接尾辞を付けた名前か
+///
+/// 接尾辞は `-YYYYMMDD`・`-YYYY-MM-DD`・`-MMDD`・`-latest` のいずれかです。
+fn matches_snapshot(name: &str, model: &str) -> bool {
+    let Some(suffix) = model.strip_prefix(name).and_then(|rest| rest.strip_prefix('-')) else {
+        return false;
+    };
+    let digits = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+
+    match suffix.split('-').collect::<Vec<_>>().as_slice() {
+        ["latest"] => true,
+        [date] => digits(date, 8) || digits(date, 4),
+        [year, month, day] => digits(year, 4) && digits(month, 2) && digits(day, 2),
+        _ => false,
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `dto`: `[prices]` テーブル（キーはモデル名）
+pub(super) fn parse_price_table(dto: &BTreeMap<String, PriceDto>) -> Result<PriceTable, String> {
+    let mut models = BTreeMap::new();
+    for (model, price) in dto {
+        models.insert(model.clone(), parse_model_price(model, price)?);
+    }
+    Ok(PriceTable { models })
+}
+
+/// 1つのモデルの単価の変換
+pub(super) fn parse_model_price(model: &str, dto: &PriceDto) -> Result<ModelPrice, String> {
+    if model.trim().is_empty() {
+        return Err("[prices] のモデル名が空です".to_string());
+    }
+    let rates = [
+        ("input", Some(dto.input)),
+        ("output", Some(dto.output)),
+        ("cached_input", dto.cached_input),
+        ("cache_write", dto.cache_write),
+    ];
+    if let Some((key, _)) = rates
+        .iter()
+        .find(|(_, rate)| rate.is_some_and(|rate| !rate.is_finite() || rate < 0.0))
+    {
+        return Err(format!("[prices.\"{}\"] の {} は0以上を指定してください", model, key));
+    }
+
+    Ok(ModelPrice {
+        input: dto.input,
+        output: dto.output,
+        cached_input: dto.cached_input,
+        cache_write: dto.cache_write,
+    })
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<PriceTable> for BTreeMap<String, PriceDto> {
+    fn from(table: PriceTable) -> Self {
+        table
+            .models
+            .into_iter()
+            .map(|(model, price)| {
+                let dto = PriceDto {
+                    input: price.input,
+                    output: price.output,
+                    cached_input: price.cached_input,
+                    cache_write: price.cache_write,
+                };
+                (model, dto)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(toml: &str) -> Result<PriceTable, String> {
+        let dto: BTreeMap<String, PriceDto> = toml::from_str(toml).unwrap();
+        parse_price_table(&dto)
+    }
+
+    #[test]
+    fn test_parse_price_table() {
+        let prices = table(r#"
+["claude-sonnet-4-5"]
+input = 3
+output = 15.0
+cached_input = 0.3
+cache_write = 3.75
+
+["claude-sonnet-4-5-20250929"]
+input = 2.0
+output = 10.0
+"#)
+        .unwrap();
+
+        // 完全一致、日付やスナップショットの接尾辞を除いた名前の順
+        assert_eq!(prices.get("claude-sonnet-4-5-20250929").map(ModelPrice::input), Some(2.0));
+        assert_eq!(prices.get("claude-sonnet-4-5-20251001").map(ModelPrice::input), Some(3.0));
+        assert_eq!(prices.get("claude-sonnet-4-5-latest").map(ModelPrice::input), Some(3.0));
+        assert_eq!(prices.get("claude-sonnet-4-50"), None);
+        assert_eq!(prices.get("claude-sonnet-4-5-1m"), None);
+        assert_eq!(prices.get("claude-sonnet-4-5").map(ModelPrice::cached_input), Some(0.3));
+        assert_eq!(prices.get("claude-sonnet-4-5-20250929").map(ModelPrice::cached_input), Some(2.0));
+        assert_eq!(prices.get("claude-sonnet-4-5").map(ModelPrice::cache_write), Some(3.75));
+
+        // 入力 100 万 + キャッシュ読み込み 100 万 + キャッシュ書き込み 100 万 + 出力 10 万トークン
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_read_tokens: 1_000_000,
+            cache_creation_tokens: 1_000_000,
+            reasoning_tokens: 50_000,
+        };
+        let cost = prices.get("claude-sonnet-4-5").unwrap().cost(&usage);
+        assert!((cost - (3.0 + 0.3 + 3.75 + 1.5)).abs() < 1e-9, "{}", cost);
+
+        // DTO に戻しても同じ内容
+        let dto: BTreeMap<String, PriceDto> = prices.clone().into();
+        assert_eq!(parse_price_table(&dto).unwrap(), prices);
+    }
+
+    #[test]
+    fn test_parse_price_table_errors() {
+        let err = table("[\"gpt-4.1\"]\ninput = -1\noutput = 8").unwrap_err();
+        assert!(err.contains("[prices.\"gpt-4.1\"] の input は0以上を指定してください"), "{}", err);
+
+        let err = table("[\" \"]\ninput = 1\noutput = 1").unwrap_err();
+        assert!(err.contains("[prices] のモデル名が空です"), "{}", err);
+    }
+
+    #[test]
+    fn test_select_price_precedence() {
+        let workflow_prices = table("[\"gpt-4o\"]\ninput = 1\noutput = 2").unwrap();
+        let user_prices = table("[\"gpt-4o\"]\ninput = 5\noutput = 6\n\n[local]\ninput = 0\noutput = 0").unwrap();
+        let empty = PriceTable::default();
+
+        assert_eq!(select_price("gpt-4o", &workflow_prices, &user_prices).map(|p| p.input()), Some(1.0));
+        assert_eq!(select_price("gpt-4o", &empty, &user_prices).map(|p| p.input()), Some(5.0));
+        assert_eq!(select_price("local", &workflow_prices, &user_prices).map(|p| p.output()), Some(0.0));
+
+        // 組み込みの単価（日付やスナップショットの接尾辞を除いた名前）
+        assert_eq!(select_price("gpt-4o-mini-2024-07-18", &empty, &empty).map(|p| p.input()), Some(0.15));
+        assert_eq!(select_price("claude-opus-4-20250514", &empty, &empty).map(|p| p.output()), Some(75.0));
+        assert_eq!(select_price("claude-opus-4-1-20250805", &empty, &empty).map(|p| p.output()), Some(75.0));
+        assert_eq!(select_price("claude-opus-4-5", &empty, &empty).map(|p| p.output()), Some(25.0));
+        assert_eq!(select_price("llama3.2:3b", &empty, &empty), None);
+
+        // 別のモデルを表す接尾辞には適用しない
+        assert_eq!(select_price("o1-pro", &empty, &empty), None);
+        assert_eq!(select_price("claude-opus-4-6", &empty, &empty), None);
+        assert_eq!(select_price("gpt-4o-audio-preview", &empty, &empty), None);
+    }
+
+    #[test]
+    fn test_matches_snapshot() {
+        assert!(matches_snapshot("claude-opus-4", "claude-opus-4-20250514"));
+        assert!(matches_snapshot("gpt-4o", "gpt-4o-2024-08-06"));
+        assert!(matches_snapshot("gpt-4", "gpt-4-0613"));
+        assert!(matches_snapshot("gpt-4o", "gpt-4o-latest"));
+        assert!(!matches_snapshot("gpt-4o", "gpt-4o"));
+        assert!(!matches_snapshot("o1", "o1-pro"));
+        assert!(!matches_snapshot("o1", "o1-mini-2024-09-12"));
+        assert!(!matches_snapshot("claude-opus-4", "claude-opus-4-5"));
+        assert!(!matches_snapshot("gpt-4o", "gpt-4o-2024-08"));
+    }
+}
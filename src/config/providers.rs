@@ -0,0 +1,345 @@
+//! ワークフローで定義するプロバイダー（`[providers.<name>]`）
+//!
+//! # 責務
+//!
+//! - `type` に応じてプロバイダーの定義を検証し、[`ProviderDefinition`] に変換する
+//! - OpenAI 互換 API（Ollama・llama.cpp server 等）の定義 [`OpenAiCompatibleSpec`] を表現する
+//! - モデルティアごとのモデル名 [`TierModels`] を表現する
+//!
+//! # 種類
+//!
+//! | `type`              | 内容                                         | 定義                  |
+//! |---------------------|----------------------------------------------|-----------------------|
+//! | `command`（既定）    | 任意の CLI エージェントを起動する              | [`CommandSpec`]       |
+//! | `openai-compatible` | OpenAI 互換の Chat Completions API を呼び出す | [`OpenAiCompatibleSpec`] |
+//!
+//! ```toml
+//! [providers.local]
+//! type = "openai-compatible"
+//! base_url = "http://localhost:11434/v1"
+//! models = { light = "llama3.2:3b", medium = "qwen2.5-coder:14b" }
+//!
+//! [[steps]]
+//! name = "commit-message"
+//! provider = "local"
+//! model_tier = "light"
+//! # ...
+//! ```
+//!
+//! OpenAI 互換 API はモデル名の既定値を持たないため、ステップで使うティアのモデル名を
+//! `models` に指定する必要があります（読み込み時に検証します）。
+//! ステップの `model` やワークフローの `[models.<name>]` で指定することもできます。
+
+use super::command::{self, CommandSpec};
+use super::dto::{ProviderDto, TierModelsDto};
+use super::step::ModelTier;
+
+/// `type = "command"`
+const KIND_COMMAND: &str = "command";
+/// `type = "openai-compatible"`
+const KIND_OPENAI_COMPATIBLE: &str = "openai-compatible";
+
+/// ワークフローで定義したプロバイダー
+#[derive(Debug, Clone)]
+pub enum ProviderDefinition {
+    /// 任意の CLI エージェント（`type = "command"`）
+    Command(CommandSpec),
+    /// OpenAI 互換 API（`type = "openai-compatible"`）
+    OpenAiCompatible(OpenAiCompatibleSpec),
+}
+
+impl ProviderDefinition {
+    /// プロバイダー名（小文字）を取得
+    pub fn name(&self) -> &str {
+        match self {
+            ProviderDefinition::Command(spec) => spec.name(),
+            ProviderDefinition::OpenAiCompatible(spec) => spec.name(),
+        }
+    }
+}
+
+/// OpenAI 互換 API の定義
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatibleSpec {
+    /// プロバイダー名（小文字）
+    name: String,
+    /// API のベース URL（`/chat/completions` の手前まで）
+    base_url: String,
+    /// API キーを読み込む環境変数名
+    api_key_env: Option<String>,
+    /// 最大出力トークン数
+    max_tokens: Option<u32>,
+    /// モデルティアごとのモデル名
+    models: TierModels,
+}
+
+impl OpenAiCompatibleSpec {
+    /// プロバイダー名を取得
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// API のベース URL を取得
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// API キーを読み込む環境変数名を取得（`None` の場合は認証なし）
+    pub fn api_key_env(&self) -> Option<&str> {
+        self.api_key_env.as_deref()
+    }
+
+    /// 最大出力トークン数を取得
+    pub fn max_tokens(&self) -> Option<u32> {
+        self.max_tokens
+    }
+
+    /// モデルティアごとのモデル名を取得
+    pub fn models(&self) -> &TierModels {
+        &self.models
+    }
+}
+
+/// モデルティアごとのモデル名
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TierModels {
+    heavy: Option<String>,
+    medium: Option<String>,
+    light: Option<String>,
+}
+
+impl TierModels {
+    /// モデルティアに対応するモデル名を取得
+    pub fn get(&self, tier: &ModelTier) -> Option<&str> {
+        match tier {
+            ModelTier::Heavy => self.heavy.as_deref(),
+            ModelTier::Medium => self.medium.as_deref(),
+            ModelTier::Light => self.light.as_deref(),
+        }
+    }
+
+    /// モデル名が1つも指定されていないか
+    pub fn is_empty(&self) -> bool {
+        self.heavy.is_none() && self.medium.is_none() && self.light.is_none()
+    }
+
+    pub(super) fn from_dto(dto: Option<&TierModelsDto>) -> Self {
+        dto.map_or_else(Self::default, |dto| Self {
+            heavy: dto.heavy.clone(),
+            medium: dto.medium.clone(),
+            light: dto.light.clone(),
+        })
+    }
+
+    pub(super) fn into_dto(self) -> Option<TierModelsDto> {
+        (!self.is_empty()).then_some(TierModelsDto {
+            heavy: self.heavy,
+            medium: self.medium,
+            light: self.light,
+        })
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `name`: プロバイダー名（`[providers.<name>]` のキー）
+/// - `dto`: プロバイダーの定義
+pub(super) fn parse_provider_definition(name: &str, dto: &ProviderDto) -> Result<ProviderDefinition, String> {
+    if name.trim().is_empty() {
+        return Err("プロバイダー名が空です".to_string());
+    }
+
+    let kind = dto.kind.as_deref().map(str::to_lowercase);
+    match kind.as_deref().unwrap_or(KIND_COMMAND) {
+        KIND_COMMAND => {
+            reject_keys(name, KIND_COMMAND, &[
+                ("base_url", dto.base_url.is_some()),
+                ("api_key_env", dto.api_key_env.is_some()),
+                ("max_tokens", dto.max_tokens.is_some()),
+            ])?;
+            command::parse_command_spec(name, dto).map(ProviderDefinition::Command)
+        }
+        KIND_OPENAI_COMPATIBLE => {
+            reject_keys(name, KIND_OPENAI_COMPATIBLE, &[
+                ("command", dto.command.is_some()),
+                ("args", !dto.args.is_empty()),
+                ("prompt_input", dto.prompt_input.is_some()),
+                ("output", dto.output.is_some()),
+                ("errors", !dto.errors.is_empty()),
+            ])?;
+            parse_openai_compatible_spec(name, dto).map(ProviderDefinition::OpenAiCompatible)
+        }
+        other => Err(format!(
+            "プロバイダー '{}' の定義が不正です: 不正な type: '{}' (有効な値: {}, {})",
+            name, other, KIND_COMMAND, KIND_OPENAI_COMPATIBLE
+        )),
+    }
+}
+
+/// 種類に合わないキーを拒否
+fn reject_keys(name: &str, kind: &str, keys: &[(&str, bool)]) -> Result<(), String> {
+    match keys.iter().find(|(_, present)| *present) {
+        Some((key, _)) => Err(format!(
+            "プロバイダー '{}' の定義が不正です: {} は type = \"{}\" では使えません",
+            name, key, kind
+        )),
+        None => Ok(()),
+    }
+}
+
+/// OpenAI 互換 API の定義の変換
+fn parse_openai_compatible_spec(name: &str, dto: &ProviderDto) -> Result<OpenAiCompatibleSpec, String> {
+    let invalid = |detail: &str| format!("プロバイダー '{}' の定義が不正です: {}", name, detail);
+
+    let base_url = match &dto.base_url {
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => url.clone(),
+        Some(_) => return Err(invalid("base_url は http:// または https:// で始めてください")),
+        None => return Err(invalid("必須キー 'base_url' がありません")),
+    };
+    if dto.api_key_env.as_deref().is_some_and(|env| env.trim().is_empty()) {
+        return Err(invalid("api_key_env が空です"));
+    }
+    if dto.max_tokens == Some(0) {
+        return Err(invalid("max_tokens は1以上を指定してください"));
+    }
+    let models = TierModels::from_dto(dto.models.as_ref());
+    if models.is_empty() {
+        return Err(invalid("models にモデル名を指定してください"));
+    }
+
+    Ok(OpenAiCompatibleSpec {
+        name: name.to_lowercase(),
+        base_url,
+        api_key_env: dto.api_key_env.clone(),
+        max_tokens: dto.max_tokens,
+        models,
+    })
+}
+
+/// ステップのモデルティアにモデル名があるか検証
+///
+/// OpenAI 互換 API はモデル名の既定値を持たないため、未指定のティアはエラーです。
+/// ステップの `model` やワークフローの `[models]` でモデル名を指定したステップには使いません。
+pub(super) fn check_step_model(
+    step_name: &str,
+    tier: &ModelTier,
+    definition: &ProviderDefinition,
+) -> Result<(), String> {
+    check_tier_model(&format!("ステップ '{}'", step_name), tier, definition)
+}
+
+/// フォールバック先のモデルティアにモデル名があるか検証
+///
+/// [`check_step_model`] と同じ検証を `fallback[position]` に対して行います。
+pub(super) fn check_fallback_model(
+    step_name: &str,
+    position: usize,
+    tier: &ModelTier,
+    definition: &ProviderDefinition,
+) -> Result<(), String> {
+    check_tier_model(&format!("ステップ '{}' の fallback[{}]", step_name, position), tier, definition)
+}
+
+fn check_tier_model(subject: &str, tier: &ModelTier, definition: &ProviderDefinition) -> Result<(), String> {
+    match definition {
+        ProviderDefinition::OpenAiCompatible(spec) if spec.models.get(tier).is_none() => Err(format!(
+            "{} のモデルティア '{}' に対応するモデルがプロバイダー '{}' の models にありません",
+            subject,
+            tier.as_str(),
+            spec.name
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<ProviderDefinition> for ProviderDto {
+    fn from(definition: ProviderDefinition) -> Self {
+        match definition {
+            ProviderDefinition::Command(spec) => spec.into(),
+            ProviderDefinition::OpenAiCompatible(spec) => ProviderDto {
+                kind: Some(KIND_OPENAI_COMPATIBLE.to_string()),
+                models: spec.models.into_dto(),
+                base_url: Some(spec.base_url),
+                api_key_env: spec.api_key_env,
+                max_tokens: spec.max_tokens,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(toml: &str) -> ProviderDto {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_openai_compatible() {
+        let definition = parse_provider_definition(
+            "Local",
+            &dto(r#"
+type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+api_key_env = "LOCAL_API_KEY"
+max_tokens = 512
+models = { light = "llama3.2:3b" }
+"#),
+        )
+        .unwrap();
+
+        let ProviderDefinition::OpenAiCompatible(spec) = &definition else {
+            panic!("Expected OpenAiCompatible");
+        };
+        assert_eq!(spec.name(), "local");
+        assert_eq!(spec.base_url(), "http://localhost:11434/v1");
+        assert_eq!(spec.api_key_env(), Some("LOCAL_API_KEY"));
+        assert_eq!(spec.max_tokens(), Some(512));
+        assert_eq!(spec.models().get(&ModelTier::Light), Some("llama3.2:3b"));
+        assert_eq!(spec.models().get(&ModelTier::Heavy), None);
+
+        assert!(check_step_model("triage", &ModelTier::Light, &definition).is_ok());
+        let err = check_step_model("triage", &ModelTier::Heavy, &definition).unwrap_err();
+        assert!(err.contains("モデルティア 'heavy'"), "{}", err);
+        let err = check_fallback_model("triage", 1, &ModelTier::Medium, &definition).unwrap_err();
+        assert!(err.contains("ステップ 'triage' の fallback[1] のモデルティア 'medium'"), "{}", err);
+
+        // DTO に戻して再度読み込める
+        let restored = parse_provider_definition("local", &ProviderDto::from(definition)).unwrap();
+        assert!(matches!(restored, ProviderDefinition::OpenAiCompatible(spec) if spec.max_tokens() == Some(512)));
+
+        // type 省略時はコマンドプロバイダー
+        let definition = parse_provider_definition("agent", &dto(r#"command = "agent""#)).unwrap();
+        assert!(matches!(definition, ProviderDefinition::Command(_)));
+    }
+
+    #[test]
+    fn test_parse_provider_definition_errors() {
+        let cases = [
+            (r#"type = "grpc""#, "不正な type: 'grpc'"),
+            (r#"type = "openai-compatible"
+models = { light = "m" }"#, "必須キー 'base_url' がありません"),
+            (r#"type = "openai-compatible"
+base_url = "localhost:11434"
+models = { light = "m" }"#, "http:// または https://"),
+            (r#"type = "openai-compatible"
+base_url = "http://localhost:11434/v1""#, "models にモデル名を指定してください"),
+            (r#"type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+command = "ollama"
+models = { light = "m" }"#, "command は type = \"openai-compatible\" では使えません"),
+            (r#"command = "agent"
+base_url = "http://localhost""#, "base_url は type = \"command\" では使えません"),
+        ];
+
+        for (toml, expected) in cases {
+            let err = parse_provider_definition("local", &dto(toml)).unwrap_err();
+            assert!(err.contains(expected), "{} does not contain {}", err, expected);
+        }
+    }
+}
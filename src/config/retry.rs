@@ -0,0 +1,273 @@
+//! ステップの再試行の方針（`retry`）
+//!
+//! # 責務
+//!
+//! `[[steps]]` の `retry` に記述された待機時間と再試行の対象を検証済みの型 [`RetryPolicy`] として表現する。
+//! 再試行の回数は `retry_count` で指定し、エラーの分類
+//! （[`ExecutionError::retry_class`](crate::engine::ExecutionError::retry_class)）と待機は実行エンジンが行う。
+//!
+//! # 記述形式
+//!
+//! 待機時間は秒数で指定し、再試行のたびに `multiplier` 倍（`max_delay` まで）になります。
+//! `jitter` を指定すると、待機時間をその割合までランダムに短くし、並列に実行したステップの再試行が重ならないようにします。
+//!
+//! | キー             | 内容                                  | 既定値 |
+//! |-----------------|---------------------------------------|--------|
+//! | `initial_delay` | 最初の再試行までの待機秒数               | 1      |
+//! | `multiplier`    | 再試行ごとの待機時間の倍率（1以上）        | 2      |
+//! | `max_delay`     | 待機秒数の上限                          | 30     |
+//! | `jitter`        | 待機時間を短くする割合の上限（0〜1）       | 0.2    |
+//! | `retry_on`      | 再試行するエラーの種類                   | すべて  |
+//!
+//! `retry_on` には `rate_limit`（レート制限）、`timeout`（タイムアウト）、
+//! `invalid_response`（不正な応答・スキーマ不一致）、`transient`（CLI の異常終了・HTTP 5xx・通信エラー）を指定できます。
+//! CLI が見つからない・認証に失敗した等のエラーは指定にかかわらず再試行しません。
+//!
+//! ```toml
+//! [[steps]]
+//! name = "plan"
+//! # ...
+//! retry_count = 4
+//! retry = { initial_delay = 2, multiplier = 3, max_delay = 60, jitter = 0.5, retry_on = ["rate_limit", "timeout"] }
+//! ```
+
+use std::time::Duration;
+
+use crate::error::RetryClass;
+use super::dto::RetryDto;
+
+/// 最初の再試行までの待機秒数の既定値
+pub const DEFAULT_INITIAL_DELAY_SECS: f64 = 1.0;
+/// 再試行ごとの待機時間の倍率の既定値
+pub const DEFAULT_MULTIPLIER: f64 = 2.0;
+/// 待機秒数の上限の既定値
+pub const DEFAULT_MAX_DELAY_SECS: f64 = 30.0;
+/// 待機時間を短くする割合の上限の既定値
+pub const DEFAULT_JITTER: f64 = 0.2;
+
+/// ステップの再試行の方針（ドメインモデル）
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 最初の再試行までの待機時間
+    initial_delay: Duration,
+    /// 再試行ごとの待機時間の倍率
+    multiplier: f64,
+    /// 待機時間の上限
+    max_delay: Duration,
+    /// 待機時間を短くする割合の上限（0〜1）
+    jitter: f64,
+    /// 再試行するエラーの種類
+    retry_on: Vec<RetryClass>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs_f64(DEFAULT_INITIAL_DELAY_SECS),
+            multiplier: DEFAULT_MULTIPLIER,
+            max_delay: Duration::from_secs_f64(DEFAULT_MAX_DELAY_SECS),
+            jitter: DEFAULT_JITTER,
+            retry_on: RetryClass::ALL.to_vec(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 最初の再試行までの待機時間を取得
+    pub fn initial_delay(&self) -> Duration {
+        self.initial_delay
+    }
+
+    /// 再試行ごとの待機時間の倍率を取得
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// 待機時間の上限を取得
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    /// 待機時間を短くする割合の上限を取得
+    pub fn jitter(&self) -> f64 {
+        self.jitter
+    }
+
+    /// 再試行するエラーの種類を取得
+    pub fn retry_on(&self) -> &[RetryClass] {
+        &self.retry_on
+    }
+
+    /// 指定した種類のエラーを再試行するか
+    ///
+    /// 再試行で解消しないエラー（`None`）は再試行しません。
+    pub fn retries_on(&self, class: Option<RetryClass>) -> bool {
+        class.is_some_and(|class| self.retry_on.contains(&class))
+    }
+
+    /// 再試行までの待機時間
+    ///
+    /// # 引数
+    ///
+    /// - `retry`: 何回目の再試行か（0始まり）
+    /// - `sample`: ジッターに使う `[0, 1)` の乱数
+    ///
+    /// # 例
+    ///
+    /// ```
+    /// use melted_adw::config::retry::RetryPolicy;
+    /// use std::time::Duration;
+    ///
+    /// let policy = RetryPolicy::default();
+    /// assert_eq!(policy.delay(0, 0.0), Duration::from_secs(1));
+    /// assert_eq!(policy.delay(2, 0.0), Duration::from_secs(4));
+    /// assert_eq!(policy.delay(10, 0.0), Duration::from_secs(30));
+    /// ```
+    pub fn delay(&self, retry: u32, sample: f64) -> Duration {
+        let exponent = i32::try_from(retry).unwrap_or(i32::MAX);
+        let backoff = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = backoff.min(self.max_delay.as_secs_f64());
+        // 秒数の丸めで Duration の範囲を超えた場合は上限の待機時間とする
+        Duration::try_from_secs_f64(capped * (1.0 - self.jitter * sample.clamp(0.0, 1.0))).unwrap_or(self.max_delay)
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `step_name`: `retry` を記述したステップ名
+/// - `dto`: `retry` テーブル
+pub(super) fn parse_retry_policy(step_name: &str, dto: &RetryDto) -> Result<RetryPolicy, String> {
+    let invalid = |detail: String| format!("ステップ '{}' の retry の {}", step_name, detail);
+    // 負の値・非有限の値・Duration の範囲を超える値はエラー
+    let seconds = |key: &str, value: Option<f64>, default: f64| {
+        Duration::try_from_secs_f64(value.unwrap_or(default))
+            .map_err(|_| invalid(format!("{} は0以上の秒数を指定してください", key)))
+    };
+
+    let initial_delay = seconds("initial_delay", dto.initial_delay, DEFAULT_INITIAL_DELAY_SECS)?;
+    let max_delay = seconds("max_delay", dto.max_delay, DEFAULT_MAX_DELAY_SECS)?;
+    if max_delay < initial_delay {
+        return Err(invalid("max_delay は initial_delay 以上を指定してください".to_string()));
+    }
+    let multiplier = dto.multiplier.unwrap_or(DEFAULT_MULTIPLIER);
+    if !multiplier.is_finite() || multiplier < 1.0 {
+        return Err(invalid("multiplier は1以上を指定してください".to_string()));
+    }
+    let jitter = dto.jitter.unwrap_or(DEFAULT_JITTER);
+    if !(0.0..=1.0).contains(&jitter) {
+        return Err(invalid("jitter は0以上1以下を指定してください".to_string()));
+    }
+    let retry_on = match &dto.retry_on {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                parse_retry_class(name).ok_or_else(|| {
+                    invalid(format!("retry_on が不正です: '{}' (有効な値: {})", name, valid_retry_classes()))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => RetryClass::ALL.to_vec(),
+    };
+
+    Ok(RetryPolicy {
+        initial_delay,
+        multiplier,
+        max_delay,
+        jitter,
+        retry_on,
+    })
+}
+
+/// `retry_on` の名前の変換（大文字小文字を区別しない）
+fn parse_retry_class(name: &str) -> Option<RetryClass> {
+    RetryClass::ALL
+        .into_iter()
+        .find(|class| class.as_str().eq_ignore_ascii_case(name))
+}
+
+/// `retry_on` の有効な値
+fn valid_retry_classes() -> String {
+    RetryClass::ALL.map(|class| class.as_str()).join(", ")
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<RetryPolicy> for RetryDto {
+    fn from(policy: RetryPolicy) -> Self {
+        RetryDto {
+            initial_delay: Some(policy.initial_delay.as_secs_f64()),
+            multiplier: Some(policy.multiplier),
+            max_delay: Some(policy.max_delay.as_secs_f64()),
+            jitter: Some(policy.jitter),
+            retry_on: Some(policy.retry_on.iter().map(|class| class.as_str().to_string()).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(toml: &str) -> RetryDto {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_retry_policy() {
+        let policy = parse_retry_policy(
+            "plan",
+            &dto(r#"
+initial_delay = 2
+multiplier = 3.0
+max_delay = 10
+jitter = 0.5
+retry_on = ["rate_limit", "TIMEOUT"]
+"#),
+        )
+        .unwrap();
+
+        assert_eq!(policy.retry_on(), [RetryClass::RateLimit, RetryClass::Timeout]);
+        assert!(policy.retries_on(Some(RetryClass::Timeout)));
+        assert!(!policy.retries_on(Some(RetryClass::Transient)));
+        assert!(!policy.retries_on(None));
+
+        // 2秒、6秒、上限の10秒。ジッターは最大で半分まで短くする
+        assert_eq!(policy.delay(0, 0.0), Duration::from_secs(2));
+        assert_eq!(policy.delay(1, 0.0), Duration::from_secs(6));
+        assert_eq!(policy.delay(2, 0.0), Duration::from_secs(10));
+        assert_eq!(policy.delay(1, 1.0), Duration::from_secs(3));
+
+        // DTO に戻して再度読み込める
+        let restored = parse_retry_policy("plan", &RetryDto::from(policy.clone())).unwrap();
+        assert_eq!(restored, policy);
+
+        // 省略時は既定値
+        assert_eq!(parse_retry_policy("plan", &RetryDto::default()).unwrap(), RetryPolicy::default());
+    }
+
+    #[test]
+    fn test_parse_retry_policy_errors() {
+        let cases = [
+            ("initial_delay = -1", "ステップ 'plan' の retry の initial_delay は0以上の秒数を指定してください"),
+            ("max_delay = 1e20", "max_delay は0以上の秒数を指定してください"),
+            ("initial_delay = 5\nmax_delay = 1", "max_delay は initial_delay 以上を指定してください"),
+            ("multiplier = 0.5", "multiplier は1以上を指定してください"),
+            ("jitter = 1.5", "jitter は0以上1以下を指定してください"),
+            (r#"retry_on = ["auth"]"#, "retry_on が不正です: 'auth' (有効な値: rate_limit, timeout, invalid_response, transient)"),
+        ];
+
+        for (toml, expected) in cases {
+            let err = parse_retry_policy("plan", &dto(toml)).unwrap_err();
+            assert!(err.contains(expected), "{} does not contain {}", err, expected);
+        }
+    }
+
+    #[test]
+    fn test_delay_with_huge_max_delay() {
+        let policy = parse_retry_policy("plan", &dto("initial_delay = 1e18\nmultiplier = 10.0\nmax_delay = 1.8e19")).unwrap();
+
+        assert_eq!(policy.delay(0, 0.0), Duration::from_secs(1_000_000_000_000_000_000));
+        assert_eq!(policy.delay(u32::MAX, 0.0), Duration::from_secs_f64(1.8e19));
+    }
+}
@@ -0,0 +1,186 @@
+//! ステップ出力の JSON Schema（`output_schema`）
+//!
+//! # 責務
+//!
+//! `[[steps]]` の `output_schema` に記述された JSON Schema を読み込み、コンパイル済みの
+//! [`OutputSchema`] として表現する。出力の抽出と修正依頼（再実行）は実行エンジンが行う。
+//!
+//! # 記述形式
+//!
+//! スキーマは TOML のインラインテーブル、または JSON ファイルのパスで指定します。
+//! 相対パスはワークフローファイルのあるディレクトリを基準に解決されます。
+//!
+//! ```toml
+//! [[steps]]
+//! name = "review"
+//! # ...
+//! output_schema = { type = "object", required = ["verdict"], properties = { verdict = { enum = ["approve", "reject"] } } }
+//!
+//! [[steps]]
+//! name = "triage"
+//! # ...
+//! output_schema = "schemas/triage.json"
+//! repair_attempts = 1
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::dto::OutputSchemaDto;
+
+/// 出力の JSON Schema（ドメインモデル）
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    /// スキーマファイルのパス（記述されたまま。インライン指定の場合は `None`）
+    path: Option<PathBuf>,
+    /// スキーマ本体
+    schema: Value,
+    /// コンパイル済みのバリデーター
+    validator: jsonschema::Validator,
+}
+
+impl OutputSchema {
+    /// スキーマファイルのパスを取得（インライン指定の場合は `None`）
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// スキーマ本体を取得
+    pub fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    /// 値がスキーマに一致するか検証
+    ///
+    /// # 戻り値
+    ///
+    /// 一致しない場合、違反箇所ごとのメッセージ（`"/verdict: ..."` の形式）
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<String>> {
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(instance)
+            .map(|error| {
+                let location = error.instance_path().to_string();
+                let location = if location.is_empty() { "/" } else { location.as_str() };
+                format!("{}: {}", location, error)
+            })
+            .collect();
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// DTO からドメインモデルへの変換
+///
+/// # 引数
+///
+/// - `step_name`: エラーメッセージ用のステップ名
+/// - `dto`: `output_schema` の記述
+/// - `base_dir`: 相対パスの基準ディレクトリ
+pub(super) fn parse_output_schema(
+    step_name: &str,
+    dto: &OutputSchemaDto,
+    base_dir: &Path,
+) -> Result<OutputSchema, String> {
+    let invalid = |detail: String| format!("ステップ '{}' の output_schema が不正です: {}", step_name, detail);
+
+    let (path, schema) = match dto {
+        OutputSchemaDto::Path(path) => {
+            let resolved = base_dir.join(path);
+            let content = std::fs::read_to_string(&resolved).map_err(|e| {
+                invalid(format!("ファイル '{}' を読み込めません: {}", resolved.display(), e))
+            })?;
+            let schema: Value = serde_json::from_str(&content).map_err(|e| {
+                invalid(format!("ファイル '{}' を JSON として解釈できません: {}", resolved.display(), e))
+            })?;
+            (Some(PathBuf::from(path)), schema)
+        }
+        OutputSchemaDto::Inline(table) => {
+            let schema = serde_json::to_value(table).map_err(|e| invalid(e.to_string()))?;
+            (None, schema)
+        }
+    };
+
+    let validator = jsonschema::validator_for(&schema).map_err(|e| invalid(e.to_string()))?;
+
+    Ok(OutputSchema {
+        path,
+        schema,
+        validator,
+    })
+}
+
+/// ドメインモデルから DTO への変換（書き込み方向）
+impl From<OutputSchema> for OutputSchemaDto {
+    fn from(schema: OutputSchema) -> Self {
+        match schema.path {
+            Some(path) => OutputSchemaDto::Path(path.to_string_lossy().into_owned()),
+            // JSON の null は TOML で表現できないため、変換できない値は落とす
+            None => OutputSchemaDto::Inline(toml::Table::try_from(schema.schema).unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn inline(toml: &str) -> OutputSchemaDto {
+        OutputSchemaDto::Inline(toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn test_parse_inline_and_validate() {
+        let dto = inline(
+            r#"
+type = "object"
+required = ["verdict"]
+properties = { verdict = { enum = ["approve", "reject"] } }
+"#,
+        );
+        let schema = parse_output_schema("review", &dto, Path::new("")).unwrap();
+
+        assert!(schema.path().is_none());
+        assert!(schema.validate(&json!({ "verdict": "approve" })).is_ok());
+
+        let errors = schema.validate(&json!({ "verdict": "maybe" })).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("/verdict: "));
+
+        let errors = schema.validate(&json!({})).unwrap_err();
+        assert!(errors[0].starts_with("/: "));
+        assert!(errors[0].contains("verdict"));
+    }
+
+    #[test]
+    fn test_parse_file_relative_to_base_dir() {
+        let dir = std::env::temp_dir().join("melted_adw_schema_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("triage.json"), r#"{ "type": "array", "items": { "type": "string" } }"#)
+            .unwrap();
+
+        let dto = OutputSchemaDto::Path("triage.json".to_string());
+        let schema = parse_output_schema("triage", &dto, &dir).unwrap();
+        assert_eq!(schema.path(), Some(Path::new("triage.json")));
+        assert!(schema.validate(&json!(["a", "b"])).is_ok());
+        assert!(schema.validate(&json!([1])).is_err());
+
+        // DTO にはパスが記述されたまま戻る
+        assert!(matches!(OutputSchemaDto::from(schema), OutputSchemaDto::Path(path) if path == "triage.json"));
+
+        let err = parse_output_schema("triage", &OutputSchemaDto::Path("missing.json".to_string()), &dir)
+            .unwrap_err();
+        assert!(err.contains("ステップ 'triage' の output_schema が不正です"));
+        assert!(err.contains("missing.json"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_parse_invalid_schema() {
+        let err = parse_output_schema("review", &inline(r#"type = "objekt""#), Path::new("")).unwrap_err();
+        assert!(err.contains("output_schema が不正です"));
+    }
+}
@@ -5,10 +5,19 @@
 //! Workflowを構成するStepの定義体を提供するモジュール
 //! アプリケーションに対して、[WorkflowStep] を提供する。
 
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
 use crate::error::ConfigError;
+use crate::provider::ProviderRegistry;
+use super::condition::{self, Condition};
+use super::budget::{self, Budget};
+use super::fallback::{self, Fallback};
+use super::retry::{self, RetryPolicy};
 use super::dto::WorkflowStepDto;
+use super::schema::{self, OutputSchema};
+use super::template::Template;
 
 /// ワークフローステップ（ドメインモデル）
 ///
@@ -24,25 +33,51 @@ pub struct WorkflowStep {
     /// ステップ名
     name: String,
     /// システムプロンプト
-    system_prompt: String,
+    system_prompt: Template,
     /// プロバイダー
     provider: Provider,
     /// モデルティア
     model_tier: ModelTier,
+    /// モデル名 (オプション、モデルティアの対応より優先)
+    model: Option<String>,
     /// タイムアウト秒数 (オプション)
     timeout: Option<u64>,
     /// リトライ回数 (オプション)
     retry_count: Option<u32>,
+    /// リトライの方針
+    retry: RetryPolicy,
+    /// 入力テンプレート (オプション)
+    input: Option<Template>,
+    /// 実行条件 (オプション)
+    when: Option<Condition>,
+    /// 依存するステップ名 (オプション)
+    depends_on: Option<Vec<String>>,
+    /// 出力の JSON Schema (オプション)
+    output_schema: Option<OutputSchema>,
+    /// 修正依頼の回数 (オプション)
+    repair_attempts: Option<u32>,
+    /// フォールバック先 (記述順)
+    fallback: Vec<Fallback>,
+    /// 予算
+    budget: Budget,
 }
 
+/// 出力がスキーマに一致しない場合の修正依頼の回数の既定値
+pub const DEFAULT_REPAIR_ATTEMPTS: u32 = 2;
+
 impl WorkflowStep {
     /// ステップ名を取得
     pub fn name(&self) -> &str {
         &self.name
     }
 
-    /// システムプロンプトを取得
+    /// システムプロンプトを取得（プレースホルダー展開前）
     pub fn system_prompt(&self) -> &str {
+        self.system_prompt.source()
+    }
+
+    /// システムプロンプトのテンプレートを取得
+    pub fn system_prompt_template(&self) -> &Template {
         &self.system_prompt
     }
 
@@ -56,6 +91,13 @@ impl WorkflowStep {
         &self.model_tier
     }
 
+    /// モデル名を取得
+    ///
+    /// `Some` の場合、モデルティアに対応するモデル名（`[models]` やユーザー設定）より優先して使われます。
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
     /// タイムアウト秒数を取得
     pub fn timeout(&self) -> Option<u64> {
         self.timeout
@@ -65,6 +107,66 @@ impl WorkflowStep {
     pub fn retry_count(&self) -> Option<u32> {
         self.retry_count
     }
+
+    /// リトライの待機時間と対象を取得（未指定の場合は既定値）
+    pub fn retry(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// 入力テンプレートを取得
+    ///
+    /// `None` の場合、ステップには依存先のステップの出力（依存先がなければ初期入力）が渡されます。
+    pub fn input(&self) -> Option<&Template> {
+        self.input.as_ref()
+    }
+
+    /// 実行条件を取得
+    ///
+    /// `None` の場合、ステップは常に実行されます。
+    pub fn when(&self) -> Option<&Condition> {
+        self.when.as_ref()
+    }
+
+    /// 依存するステップ名を取得
+    ///
+    /// `None` の場合、ステップは直前に定義されたステップに依存します。
+    /// 解決済みの依存先は [`Workflow::dependencies`](super::workflow::Workflow::dependencies) で取得できます。
+    pub fn depends_on(&self) -> Option<&[String]> {
+        self.depends_on.as_deref()
+    }
+
+    /// 出力の JSON Schema を取得
+    ///
+    /// `Some` の場合、実行エンジンは出力から JSON を取り出してスキーマで検証し、
+    /// 解析結果を [`StepResult::structured_output`](crate::engine::StepResult::structured_output) に保持します。
+    pub fn output_schema(&self) -> Option<&OutputSchema> {
+        self.output_schema.as_ref()
+    }
+
+    /// 出力がスキーマに一致しない場合の修正依頼の回数を取得
+    ///
+    /// 未指定の場合は [`DEFAULT_REPAIR_ATTEMPTS`] です。
+    pub fn repair_attempts(&self) -> u32 {
+        self.repair_attempts.unwrap_or(DEFAULT_REPAIR_ATTEMPTS)
+    }
+
+    /// フォールバック先を取得（記述順、未指定の場合は空）
+    pub fn fallback(&self) -> &[Fallback] {
+        &self.fallback
+    }
+
+    /// ステップの予算を取得（未指定の場合は無制限）
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// このステップが参照しているステップ名（システムプロンプト・入力・実行条件の順）
+    pub fn step_references(&self) -> impl Iterator<Item = &str> {
+        self.system_prompt
+            .step_references()
+            .chain(self.input.iter().flat_map(Template::step_references))
+            .chain(self.when.iter().map(Condition::step))
+    }
 }
 
 /// モデルのティア（Heavy/Medium/Light）
@@ -79,84 +181,236 @@ pub enum ModelTier {
     Light,
 }
 
+impl ModelTier {
+    /// 設定ファイルで使用する名前（小文字）を取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelTier::Heavy => "heavy",
+            ModelTier::Medium => "medium",
+            ModelTier::Light => "light",
+        }
+    }
+}
+
 /// AI プロバイダー
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum Provider {
+///
+/// [`ProviderRegistry`] に登録されたプロバイダーの名前（小文字）です。
+/// ワークフローの読み込み時に、登録済みの名前であることを検証します。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Provider(String);
+
+impl Provider {
     /// Anthropic (Claude Code)
-    Anthropic,
+    pub const ANTHROPIC: &'static str = "anthropic";
     /// OpenAI (Codex)
-    OpenAI,
+    pub const OPENAI: &'static str = "openai";
+    /// Anthropic Messages API（HTTP）
+    pub const ANTHROPIC_API: &'static str = "anthropic-api";
+    /// OpenAI Chat Completions API（HTTP）
+    pub const OPENAI_API: &'static str = "openai-api";
+
+    /// プロバイダー名から生成（小文字に正規化）
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self(name.as_ref().to_lowercase())
+    }
+
+    /// 設定ファイルで使用する名前（小文字）を取得
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 /// DTO からドメインモデルへの変換（読み込み方向）
 ///
 /// バリデーションを実施し、不正なデータの場合は [`ConfigError::Validation`] を返します。
+///
+/// `output_schema` のファイルパスはカレントディレクトリを基準に解決し、
+/// プロバイダー名は組み込みのプロバイダー（[`ProviderRegistry::builtin`]）と照合します。
 impl TryFrom<WorkflowStepDto> for WorkflowStep {
     type Error = ConfigError;
 
     fn try_from(dto: WorkflowStepDto) -> Result<Self, Self::Error> {
-        // ステップ名のバリデーション
-        if dto.name.trim().is_empty() {
-            return Err(ConfigError::Validation(
-                "ステップ名が空です".to_string()
-            ));
-        }
-
-        // システムプロンプトのバリデーション
-        if dto.system_prompt.trim().is_empty() {
-            return Err(ConfigError::Validation(
-                format!("ステップ '{}' のシステムプロンプトが空です", dto.name)
-            ));
-        }
-
-        // システムプロンプトの長さチェック（10000文字を上限とする）
-        if dto.system_prompt.len() > 10000 {
-            return Err(ConfigError::Validation(
-                format!("ステップ '{}' のシステムプロンプトが長すぎます（最大10000文字）", dto.name)
-            ));
-        }
-
-        // プロバイダーの変換
-        let provider = match dto.provider.to_lowercase().as_str() {
-            "anthropic" => Provider::Anthropic,
-            "openai" => Provider::OpenAI,
-            _ => {
-                return Err(ConfigError::Validation(
-                    format!(
-                        "ステップ '{}' の不正なプロバイダー: '{}' (有効な値: anthropic, openai)",
-                        dto.name, dto.provider
-                    )
-                ));
-            }
-        };
+        Self::from_dto(dto, Path::new(""), &ProviderRegistry::builtin())
+    }
+}
 
-        // モデルティアの変換
-        let model_tier = match dto.model_tier.to_lowercase().as_str() {
-            "heavy" => ModelTier::Heavy,
-            "medium" => ModelTier::Medium,
-            "light" => ModelTier::Light,
-            _ => {
-                return Err(ConfigError::Validation(
-                    format!(
-                        "ステップ '{}' の不正なモデルティア: '{}' (有効な値: heavy, medium, light)",
-                        dto.name, dto.model_tier
-                    )
-                ));
-            }
-        };
+impl WorkflowStep {
+    /// DTO からドメインモデルへの変換
+    ///
+    /// `output_schema` のパスを `base_dir` 基準で解決し、プロバイダー名を `registry` と照合します。
+    pub(super) fn from_dto(
+        dto: WorkflowStepDto,
+        base_dir: &Path,
+        registry: &ProviderRegistry,
+    ) -> Result<Self, ConfigError> {
+        validate_name(&dto.name).map_err(ConfigError::Validation)?;
+        validate_system_prompt(&dto.name, &dto.system_prompt).map_err(ConfigError::Validation)?;
+        let provider =
+            parse_provider(&dto.name, &dto.provider, registry).map_err(ConfigError::Validation)?;
+        let model_tier =
+            parse_model_tier(&dto.name, &dto.model_tier).map_err(ConfigError::Validation)?;
+        validate_model(&dto.name, dto.model.as_deref()).map_err(ConfigError::Validation)?;
+        let system_prompt = parse_template(&dto.name, "system_prompt", &dto.system_prompt)
+            .map_err(ConfigError::Validation)?;
+        let input = dto
+            .input
+            .as_deref()
+            .map(|input| parse_template(&dto.name, "input", input))
+            .transpose()
+            .map_err(ConfigError::Validation)?;
+        let when = dto
+            .when
+            .as_ref()
+            .map(|when| condition::parse_condition(&when_context(&dto.name), when))
+            .transpose()
+            .map_err(ConfigError::Validation)?;
+        let output_schema = dto
+            .output_schema
+            .as_ref()
+            .map(|output_schema| schema::parse_output_schema(&dto.name, output_schema, base_dir))
+            .transpose()
+            .map_err(ConfigError::Validation)?;
+        validate_repair_attempts(&dto.name, output_schema.is_some(), dto.repair_attempts)
+            .map_err(ConfigError::Validation)?;
+        let retry = dto
+            .retry
+            .as_ref()
+            .map(|retry| retry::parse_retry_policy(&dto.name, retry))
+            .transpose()
+            .map_err(ConfigError::Validation)?
+            .unwrap_or_default();
+        let fallback =
+            fallback::parse_fallbacks(&dto.name, &dto.fallback, registry).map_err(ConfigError::Validation)?;
+        let budget = dto
+            .budget
+            .as_ref()
+            .map(|budget| budget::parse_budget(&budget_subject(&dto.name), budget))
+            .transpose()
+            .map_err(ConfigError::Validation)?
+            .unwrap_or_default();
 
         Ok(WorkflowStep {
             name: dto.name,
-            system_prompt: dto.system_prompt,
+            system_prompt,
             provider,
             model_tier,
+            model: dto.model,
             timeout: dto.timeout,
             retry_count: dto.retry_count,
+            retry,
+            input,
+            when,
+            depends_on: dto.depends_on,
+            output_schema,
+            repair_attempts: dto.repair_attempts,
+            fallback,
+            budget,
         })
     }
 }
 
+// 個別フィールドのバリデーション
+//
+// `TryFrom` による変換と、全エラーを収集する `validate` モジュールの両方から使用します。
+// エラー時はメッセージ文字列を返し、呼び出し側で適切なエラー型に変換します。
+
+/// システムプロンプトの最大長（文字数）
+const MAX_SYSTEM_PROMPT_LEN: usize = 10000;
+
+/// ステップ名のバリデーション
+pub(super) fn validate_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("ステップ名が空です".to_string());
+    }
+    Ok(())
+}
+
+/// システムプロンプトのバリデーション（空・長さ）
+pub(super) fn validate_system_prompt(step_name: &str, system_prompt: &str) -> Result<(), String> {
+    if system_prompt.trim().is_empty() {
+        return Err(format!("ステップ '{}' のシステムプロンプトが空です", step_name));
+    }
+
+    if system_prompt.len() > MAX_SYSTEM_PROMPT_LEN {
+        return Err(format!(
+            "ステップ '{}' のシステムプロンプトが長すぎます（最大{}文字）",
+            step_name, MAX_SYSTEM_PROMPT_LEN
+        ));
+    }
+
+    Ok(())
+}
+
+/// プロバイダー名の変換（大文字小文字を区別しない、`registry` に登録済みの名前のみ）
+pub(super) fn parse_provider(
+    step_name: &str,
+    provider: &str,
+    registry: &ProviderRegistry,
+) -> Result<Provider, String> {
+    if registry.contains(provider) {
+        return Ok(Provider::new(provider));
+    }
+    Err(format!(
+        "ステップ '{}' の不正なプロバイダー: '{}' (有効な値: {})",
+        step_name,
+        provider,
+        registry.names().collect::<Vec<_>>().join(", ")
+    ))
+}
+
+/// モデルティアの変換（大文字小文字を区別しない）
+pub(super) fn parse_model_tier(step_name: &str, model_tier: &str) -> Result<ModelTier, String> {
+    match model_tier.to_lowercase().as_str() {
+        "heavy" => Ok(ModelTier::Heavy),
+        "medium" => Ok(ModelTier::Medium),
+        "light" => Ok(ModelTier::Light),
+        _ => Err(format!(
+            "ステップ '{}' の不正なモデルティア: '{}' (有効な値: heavy, medium, light)",
+            step_name, model_tier
+        )),
+    }
+}
+
+/// モデル名のバリデーション（空文字列は不可）
+pub(super) fn validate_model(step_name: &str, model: Option<&str>) -> Result<(), String> {
+    if model.is_some_and(|model| model.trim().is_empty()) {
+        return Err(format!("ステップ '{}' の model が空です", step_name));
+    }
+    Ok(())
+}
+
+/// テンプレートの解析（`field` はエラーメッセージ用のキー名）
+pub(super) fn parse_template(step_name: &str, field: &str, source: &str) -> Result<Template, String> {
+    Template::parse(source)
+        .map_err(|e| format!("ステップ '{}' の {} が不正です: {}", step_name, field, e))
+}
+
+/// 修正依頼の回数のバリデーション（`output_schema` がない場合は指定不可）
+pub(super) fn validate_repair_attempts(
+    step_name: &str,
+    has_output_schema: bool,
+    repair_attempts: Option<u32>,
+) -> Result<(), String> {
+    if repair_attempts.is_some() && !has_output_schema {
+        return Err(format!(
+            "ステップ '{}' の repair_attempts は output_schema と組み合わせて指定してください",
+            step_name
+        ));
+    }
+    Ok(())
+}
+
+/// 実行条件のエラーメッセージの主語
+pub(super) fn when_context(step_name: &str) -> String {
+    format!("ステップ '{}' の when ", step_name)
+}
+
+/// 予算のエラーメッセージの主語
+pub(super) fn budget_subject(step_name: &str) -> String {
+    format!("ステップ '{}' の budget", step_name)
+}
+
 /// ドメインモデルから DTO への変換（書き込み方向）
 ///
 /// バリデーション済みのドメインモデルから DTO を生成するため、
@@ -164,24 +418,26 @@ impl TryFrom<WorkflowStepDto> for WorkflowStep {
 impl From<WorkflowStep> for WorkflowStepDto {
     fn from(step: WorkflowStep) -> Self {
         // Enum を文字列に変換（serde の lowercase と同じ形式）
-        let provider = match step.provider {
-            Provider::Anthropic => "anthropic".to_string(),
-            Provider::OpenAI => "openai".to_string(),
-        };
+        let provider = step.provider.as_str().to_string();
 
-        let model_tier = match step.model_tier {
-            ModelTier::Heavy => "heavy".to_string(),
-            ModelTier::Medium => "medium".to_string(),
-            ModelTier::Light => "light".to_string(),
-        };
+        let model_tier = step.model_tier.as_str().to_string();
 
         WorkflowStepDto {
             name: step.name,
-            system_prompt: step.system_prompt,
+            system_prompt: step.system_prompt.source().to_string(),
             provider,
             model_tier,
+            model: step.model,
             timeout: step.timeout,
             retry_count: step.retry_count,
+            retry: (step.retry != RetryPolicy::default()).then(|| step.retry.into()),
+            input: step.input.map(|input| input.source().to_string()),
+            when: step.when.map(Into::into),
+            depends_on: step.depends_on,
+            output_schema: step.output_schema.map(Into::into),
+            repair_attempts: step.repair_attempts,
+            fallback: step.fallback.into_iter().map(Into::into).collect(),
+            budget: (!step.budget.is_unlimited()).then(|| step.budget.into()),
         }
     }
 }
@@ -200,6 +456,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: Some(60),
             retry_count: Some(3),
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -227,6 +484,7 @@ mod tests {
                     model_tier: tier.to_string(),
                     timeout: None,
                     retry_count: None,
+                    ..Default::default()
                 };
 
                 let result = WorkflowStep::try_from(dto);
@@ -250,6 +508,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -267,6 +526,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -288,6 +548,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -310,6 +571,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -331,6 +593,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -343,6 +606,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_provider_is_checked_against_registry() {
+        use crate::provider::{ProviderClient, openai::OpenAIClient};
+
+        let dto = WorkflowStepDto {
+            name: "step".to_string(),
+            system_prompt: "prompt".to_string(),
+            provider: "Gemini".to_string(),
+            model_tier: "heavy".to_string(),
+            ..Default::default()
+        };
+
+        // 組み込みのプロバイダーには含まれない
+        let err = WorkflowStep::from_dto(dto.clone(), Path::new(""), &ProviderRegistry::builtin()).unwrap_err();
+        assert!(err.to_string().contains("(有効な値: anthropic, anthropic-api, openai, openai-api)"));
+
+        // 登録すれば使用できる（名前は小文字に正規化される）
+        let registry = ProviderRegistry::builtin().with_provider("gemini", || {
+            Ok(Box::new(OpenAIClient::with_command("gemini")) as Box<dyn ProviderClient>)
+        });
+        let step = WorkflowStep::from_dto(dto, Path::new(""), &registry).unwrap();
+        assert_eq!(step.provider(), &Provider::new("gemini"));
+        assert_eq!(WorkflowStepDto::from(step).provider, "gemini");
+    }
+
     #[test]
     fn test_validation_invalid_model_tier() {
         // 異常系: 不正なモデルティア
@@ -353,6 +641,7 @@ mod tests {
             model_tier: "invalid_tier".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let result = WorkflowStep::try_from(dto);
@@ -365,6 +654,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validation_invalid_template() {
+        // 異常系: 不正なテンプレート変数
+        let dto = WorkflowStepDto {
+            name: "review".to_string(),
+            system_prompt: "Review".to_string(),
+            provider: "anthropic".to_string(),
+            model_tier: "heavy".to_string(),
+            input: Some("{{steps.plan.result}}".to_string()),
+            ..Default::default()
+        };
+
+        match WorkflowStep::try_from(dto) {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("review"));
+                assert!(msg.contains("input"));
+                assert!(msg.contains("不明なテンプレート変数"));
+            }
+            other => panic!("Expected Validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validation_repair_attempts_requires_output_schema() {
+        // 異常系: output_schema のない repair_attempts
+        let dto = WorkflowStepDto {
+            name: "review".to_string(),
+            system_prompt: "Review".to_string(),
+            provider: "anthropic".to_string(),
+            model_tier: "heavy".to_string(),
+            repair_attempts: Some(3),
+            ..Default::default()
+        };
+
+        match WorkflowStep::try_from(dto) {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("repair_attempts"));
+                assert!(msg.contains("output_schema"));
+            }
+            other => panic!("Expected Validation error, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_round_trip_conversion() {
         // 正常系: DTO → ドメインモデル → DTO の往復変換
@@ -373,8 +705,35 @@ mod tests {
             system_prompt: "Test prompt".to_string(),
             provider: "anthropic".to_string(),
             model_tier: "medium".to_string(),
+            model: Some("claude-sonnet-4-5-20250929".to_string()),
             timeout: Some(120),
             retry_count: Some(5),
+            retry: Some(crate::config::dto::RetryDto {
+                initial_delay: Some(0.5),
+                retry_on: Some(vec!["rate_limit".to_string()]),
+                ..Default::default()
+            }),
+            input: Some("{{input}}".to_string()),
+            when: Some(crate::config::dto::ConditionDto {
+                step: "plan".to_string(),
+                json: Some("/verdict".to_string()),
+                equals: Some(toml::Value::Boolean(true)),
+                ..Default::default()
+            }),
+            depends_on: Some(vec!["plan".to_string()]),
+            output_schema: Some(crate::config::dto::OutputSchemaDto::Inline(
+                toml::from_str(r#"type = "object""#).unwrap(),
+            )),
+            repair_attempts: Some(1),
+            fallback: vec![crate::config::dto::FallbackDto {
+                provider: "openai".to_string(),
+                model_tier: Some("light".to_string()),
+                model: None,
+            }],
+            budget: Some(crate::config::dto::BudgetDto {
+                max_tokens_total: Some(5000),
+                ..Default::default()
+            }),
         };
 
         // DTO → ドメインモデル
@@ -388,8 +747,28 @@ mod tests {
         assert_eq!(converted_dto.system_prompt, original_dto.system_prompt);
         assert_eq!(converted_dto.provider, original_dto.provider);
         assert_eq!(converted_dto.model_tier, original_dto.model_tier);
+        assert_eq!(converted_dto.model, original_dto.model);
         assert_eq!(converted_dto.timeout, original_dto.timeout);
         assert_eq!(converted_dto.retry_count, original_dto.retry_count);
+        let retry = converted_dto.retry.unwrap();
+        assert_eq!(retry.initial_delay, Some(0.5));
+        assert_eq!(retry.multiplier, Some(crate::config::retry::DEFAULT_MULTIPLIER));
+        assert_eq!(retry.retry_on, Some(vec!["rate_limit".to_string()]));
+        assert_eq!(converted_dto.input, original_dto.input);
+        assert_eq!(converted_dto.depends_on, original_dto.depends_on);
+        assert_eq!(converted_dto.repair_attempts, original_dto.repair_attempts);
+        assert_eq!(converted_dto.fallback.len(), 1);
+        assert_eq!(converted_dto.fallback[0].provider, "openai");
+        assert_eq!(converted_dto.fallback[0].model_tier.as_deref(), Some("light"));
+        assert_eq!(converted_dto.budget.unwrap().max_tokens_total, Some(5000));
+        assert!(matches!(
+            converted_dto.output_schema,
+            Some(crate::config::dto::OutputSchemaDto::Inline(table)) if table["type"].as_str() == Some("object")
+        ));
+        let when = converted_dto.when.unwrap();
+        assert_eq!(when.step, "plan");
+        assert_eq!(when.json.as_deref(), Some("/verdict"));
+        assert_eq!(when.equals, Some(toml::Value::Boolean(true)));
     }
 
     #[test]
@@ -402,6 +781,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         };
 
         let step = WorkflowStep::try_from(dto).unwrap();
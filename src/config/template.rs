@@ -0,0 +1,266 @@
+//! プロンプトテンプレート
+//!
+//! # 責務
+//!
+//! `system_prompt` / `input` に埋め込まれたプレースホルダーを解析し、
+//! 実行時に値を埋め込む。
+//!
+//! | プレースホルダー             | 展開される値                       |
+//! |-----------------------------|-----------------------------------|
+//! | `{{input}}`                 | ワークフローへの初期入力              |
+//! | `{{workflow.name}}`         | ワークフロー名                      |
+//! | `{{steps.<name>.output}}`   | 先行ステップ `<name>` の出力         |
+//! | `{{steps.<name>.output.<path>}}` | 先行ステップの出力（JSON）のフィールド |
+//!
+//! フィールドはドット区切りのパス（`{{steps.review.output.issues.0.title}}`）で指定します。
+//! 先行ステップに `output_schema` がある場合は検証済みの値を、ない場合は出力を JSON として解釈した値を参照し、
+//! 文字列はそのまま、それ以外は JSON として展開されます。
+//! 波括弧の内側の前後の空白は無視されます（`{{ input }}` も可）。
+//! 未知の変数や閉じられていない `{{` は解析エラーになり、
+//! ステップ参照の妥当性（存在するか・先行ステップか）は
+//! [`Workflow`](super::workflow::Workflow) への変換時に検証されます。
+//!
+//! # 使用例
+//!
+//! ```rust
+//! use melted_adw::config::template::{Template, Variable};
+//!
+//! let template = Template::parse("計画: {{steps.plan.output}}").unwrap();
+//! assert_eq!(template.step_references().collect::<Vec<_>>(), vec!["plan"]);
+//!
+//! let rendered = template.render(|variable| match variable {
+//!     Variable::StepOutput(_) => "1. 設計する".to_string(),
+//!     _ => String::new(),
+//! });
+//! assert_eq!(rendered, "計画: 1. 設計する");
+//! ```
+
+use super::condition;
+
+/// テンプレート変数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Variable {
+    /// `{{input}}`: ワークフローへの初期入力
+    Input,
+    /// `{{workflow.name}}`: ワークフロー名
+    WorkflowName,
+    /// `{{steps.<name>.output}}`: 先行ステップの出力
+    StepOutput(String),
+    /// `{{steps.<name>.output.<path>}}`: 先行ステップの出力（JSON）のフィールド
+    StepOutputField {
+        /// ステップ名
+        step: String,
+        /// JSON Pointer 形式のパス
+        pointer: String,
+    },
+}
+
+/// テンプレートの構成要素
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// そのまま出力する文字列
+    Text(String),
+    /// 実行時に展開する変数
+    Variable(Variable),
+}
+
+/// 解析済みのテンプレート
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// テンプレート文字列を解析
+    ///
+    /// # エラー
+    ///
+    /// 閉じられていない `{{` や未知の変数を含む場合、エラーメッセージを返します。
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+
+            let after_open = &rest[start + 2..];
+            let end = after_open
+                .find("}}")
+                .ok_or_else(|| format!("テンプレートの '{{{{' が閉じられていません: '{}'", &rest[start..]))?;
+
+            segments.push(Segment::Variable(parse_variable(after_open[..end].trim())?));
+            rest = &after_open[end + 2..];
+        }
+
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// 元のテンプレート文字列を取得
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// テンプレートに含まれる変数（出現順）
+    pub fn variables(&self) -> impl Iterator<Item = &Variable> {
+        self.segments.iter().filter_map(|segment| match segment {
+            Segment::Variable(variable) => Some(variable),
+            Segment::Text(_) => None,
+        })
+    }
+
+    /// 参照しているステップ名（出現順）
+    pub fn step_references(&self) -> impl Iterator<Item = &str> {
+        self.variables().filter_map(|variable| match variable {
+            Variable::StepOutput(step_name) => Some(step_name.as_str()),
+            Variable::StepOutputField { step, .. } => Some(step.as_str()),
+            _ => None,
+        })
+    }
+
+    /// 変数を展開した文字列を生成
+    ///
+    /// # 引数
+    ///
+    /// - `resolve`: 変数に対応する値を返す関数
+    pub fn render(&self, resolve: impl Fn(&Variable) -> String) -> String {
+        let mut rendered = String::with_capacity(self.source.len());
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Variable(variable) => rendered.push_str(&resolve(variable)),
+            }
+        }
+        rendered
+    }
+}
+
+/// `{{ }}` の内側を変数に変換
+fn parse_variable(expr: &str) -> Result<Variable, String> {
+    match expr {
+        "input" => Ok(Variable::Input),
+        "workflow.name" => Ok(Variable::WorkflowName),
+        _ => expr
+            .strip_prefix("steps.")
+            .and_then(parse_step_output)
+            .ok_or_else(|| format!("不明なテンプレート変数です: '{{{{{}}}}}'", expr)),
+    }
+}
+
+/// `steps.` に続く `<name>.output` / `<name>.output.<path>` を変数に変換
+fn parse_step_output(rest: &str) -> Option<Variable> {
+    if let Some(step_name) = rest.strip_suffix(".output") {
+        return (!step_name.is_empty()).then(|| Variable::StepOutput(step_name.to_string()));
+    }
+
+    let (step_name, path) = rest.split_once(".output.")?;
+    let pointer = condition::json_pointer(path);
+    (!step_name.is_empty() && !pointer.is_empty()).then(|| Variable::StepOutputField {
+        step: step_name.to_string(),
+        pointer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(variable: &Variable) -> String {
+        match variable {
+            Variable::Input => "INPUT".to_string(),
+            Variable::WorkflowName => "WORKFLOW".to_string(),
+            Variable::StepOutput(name) => format!("OUTPUT({})", name),
+            Variable::StepOutputField { step, pointer } => format!("FIELD({}{})", step, pointer),
+        }
+    }
+
+    #[test]
+    fn test_parse_plain_text() {
+        let template = Template::parse("変数なしのプロンプト").unwrap();
+        assert_eq!(template.variables().count(), 0);
+        assert_eq!(template.render(resolve), "変数なしのプロンプト");
+    }
+
+    #[test]
+    fn test_parse_all_variables() {
+        let template = Template::parse(
+            "{{workflow.name}}: {{ input }} / {{steps.plan.output}}{{steps.my-step.output}}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.variables().cloned().collect::<Vec<_>>(),
+            vec![
+                Variable::WorkflowName,
+                Variable::Input,
+                Variable::StepOutput("plan".to_string()),
+                Variable::StepOutput("my-step".to_string()),
+            ]
+        );
+        assert_eq!(
+            template.step_references().collect::<Vec<_>>(),
+            vec!["plan", "my-step"]
+        );
+        assert_eq!(
+            template.render(resolve),
+            "WORKFLOW: INPUT / OUTPUT(plan)OUTPUT(my-step)"
+        );
+        assert_eq!(template.source(), "{{workflow.name}}: {{ input }} / {{steps.plan.output}}{{steps.my-step.output}}");
+    }
+
+    #[test]
+    fn test_parse_step_output_field() {
+        let template = Template::parse("判定: {{steps.review.output.verdict}} / {{ steps.review.output.issues.0.title }}").unwrap();
+
+        assert_eq!(
+            template.variables().cloned().collect::<Vec<_>>(),
+            vec![
+                Variable::StepOutputField {
+                    step: "review".to_string(),
+                    pointer: "/verdict".to_string(),
+                },
+                Variable::StepOutputField {
+                    step: "review".to_string(),
+                    pointer: "/issues/0/title".to_string(),
+                },
+            ]
+        );
+        assert_eq!(template.step_references().collect::<Vec<_>>(), vec!["review", "review"]);
+        assert_eq!(template.render(resolve), "判定: FIELD(review/verdict) / FIELD(review/issues/0/title)");
+
+        assert!(Template::parse("{{steps.review.output.}}").is_err());
+        assert!(Template::parse("{{steps..output.verdict}}").is_err());
+    }
+
+    #[test]
+    fn test_parse_unknown_variable() {
+        let err = Template::parse("{{steps.plan.result}}").unwrap_err();
+        assert!(err.contains("不明なテンプレート変数"));
+        assert!(err.contains("{{steps.plan.result}}"));
+
+        assert!(Template::parse("{{steps..output}}").is_err());
+        assert!(Template::parse("{{}}").is_err());
+    }
+
+    #[test]
+    fn test_parse_unclosed_braces() {
+        let err = Template::parse("入力: {{input").unwrap_err();
+        assert!(err.contains("閉じられていません"));
+    }
+
+    #[test]
+    fn test_single_braces_are_text() {
+        let template = Template::parse("JSON で {\"ok\": true} を返してください").unwrap();
+        assert_eq!(template.variables().count(), 0);
+        assert_eq!(template.render(resolve), "JSON で {\"ok\": true} を返してください");
+    }
+}
@@ -0,0 +1,172 @@
+//! ユーザー設定ファイル（`~/.config/adw/config.toml`）
+//!
+//! # 責務
+//!
+//! - ワークフローをまたいで使う設定を読み込み、[`UserConfig`] として提供する
+//! - 設定ファイルの既定の場所を解決する
+//!
+//! # 設定ファイル
+//!
+//! 既定の場所は `$XDG_CONFIG_HOME/adw/config.toml`（`XDG_CONFIG_HOME` が未設定の場合は
+//! `~/.config/adw/config.toml`）です。ファイルがなければ既定値を使います。
+//!
+//! ```toml
+//! # モデルティアに対応するモデル名（ワークフロー定義の [models] が優先）
+//! [models.anthropic]
+//! heavy = "claude-opus-4-1"
+//!
+//! [models.local]
+//! light = "llama3.2:3b"
+//!
+//! # トークン単価（100 万トークンあたりの米ドル、ワークフロー定義の [prices] が優先）
+//! [prices."llama3.2:3b"]
+//! input = 0
+//! output = 0
+//! ```
+//!
+//! ワークフローごとに定義するプロバイダーにも対応できるよう、プロバイダー名は登録済みの名前と照合しません。
+
+use std::path::{Path, PathBuf};
+
+use crate::error::ConfigError;
+use super::dto::UserConfigDto;
+use super::models::{self, ModelTable};
+use super::prices::{self, PriceTable};
+
+/// 設定ファイルのディレクトリ名（`$XDG_CONFIG_HOME` 配下）
+const CONFIG_DIR: &str = "adw";
+
+/// 設定ファイル名
+const CONFIG_FILE: &str = "config.toml";
+
+/// ユーザー設定
+#[derive(Debug, Clone, Default)]
+pub struct UserConfig {
+    /// モデルティアに対応するモデル名
+    models: ModelTable,
+    /// モデルごとのトークン単価
+    prices: PriceTable,
+}
+
+impl UserConfig {
+    /// モデルティアに対応するモデル名（`[models.<provider>]`）を取得
+    pub fn models(&self) -> &ModelTable {
+        &self.models
+    }
+
+    /// モデルごとのトークン単価（`[prices."<model>"]`）を取得
+    pub fn prices(&self) -> &PriceTable {
+        &self.prices
+    }
+
+    /// 設定ファイルの既定の場所を取得
+    ///
+    /// `XDG_CONFIG_HOME` と `HOME` のどちらも未設定の場合は `None` です。
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .filter(|dir| !dir.is_empty())
+                    .map(|home| PathBuf::from(home).join(".config"))
+            })?;
+        Some(config_home.join(CONFIG_DIR).join(CONFIG_FILE))
+    }
+
+    /// 既定の場所から設定を読み込む
+    ///
+    /// ファイルがない場合は既定値を返します。
+    ///
+    /// # エラー
+    ///
+    /// - [`ConfigError::FileRead`] - ファイルの読み込みに失敗した場合
+    /// - [`ConfigError::TomlDeserialize`] - TOML として不正な場合
+    /// - [`ConfigError::Validation`] - 設定値が不正な場合
+    pub fn load_default() -> Result<Self, ConfigError> {
+        match Self::default_path() {
+            Some(path) if path.is_file() => Self::from_file(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// ファイルから設定を読み込む
+    ///
+    /// # エラー
+    ///
+    /// [`load_default`](Self::load_default) と同じです（ファイルがない場合も [`ConfigError::FileRead`]）。
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_toml(&content)
+    }
+
+    /// TOML 文字列から設定を読み込む
+    ///
+    /// # エラー
+    ///
+    /// - [`ConfigError::TomlDeserialize`] - TOML として不正な場合
+    /// - [`ConfigError::Validation`] - 設定値が不正な場合
+    pub fn from_toml(toml: &str) -> Result<Self, ConfigError> {
+        let dto: UserConfigDto = toml::from_str(toml)?;
+        let models = models::parse_model_table(&dto.models, |provider| {
+            if provider.trim().is_empty() {
+                return Err("[models] のプロバイダー名が空です".to_string());
+            }
+            Ok(())
+        })
+        .map_err(ConfigError::Validation)?;
+        let prices = prices::parse_price_table(&dto.prices).map_err(ConfigError::Validation)?;
+
+        Ok(Self { models, prices })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::step::ModelTier;
+
+    #[test]
+    fn test_from_toml() {
+        let config = UserConfig::from_toml(r#"
+[models.anthropic]
+heavy = "claude-opus-4-1"
+
+[models.Local]
+light = "llama3.2:3b"
+
+[prices."llama3.2:3b"]
+input = 0
+output = 0
+"#)
+        .unwrap();
+
+        assert_eq!(config.models().get("anthropic", &ModelTier::Heavy), Some("claude-opus-4-1"));
+        assert_eq!(config.models().get("local", &ModelTier::Light), Some("llama3.2:3b"));
+        assert_eq!(config.prices().get("llama3.2:3b").map(|price| price.output()), Some(0.0));
+
+        assert!(UserConfig::from_toml("").unwrap().models().is_empty());
+        assert!(matches!(
+            UserConfig::from_toml("[model.anthropic]\nheavy = \"x\""),
+            Err(ConfigError::TomlDeserialize(_))
+        ));
+        assert!(matches!(
+            UserConfig::from_toml("[models.anthropic]\nheavy = \"\""),
+            Err(ConfigError::Validation(message)) if message.contains("heavy が空です")
+        ));
+    }
+
+    #[test]
+    fn test_from_file() {
+        let dir = std::env::temp_dir().join(format!("adw-user-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, "[models.openai]\nmedium = \"gpt-4.1\"\n").unwrap();
+
+        let config = UserConfig::from_file(&path).unwrap();
+        assert_eq!(config.models().get("openai", &ModelTier::Medium), Some("gpt-4.1"));
+        assert!(matches!(UserConfig::from_file(dir.join("missing.toml")), Err(ConfigError::FileRead(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1095 @@
+//! ワークフロー定義の一括バリデーション
+//!
+//! # 責務
+//!
+//! [`Workflow::from_toml`] は最初のエラーで処理を中断し、ソース上の位置も失われます。
+//! このモジュールは、ワークフロー定義に含まれる **すべての** 問題を収集し、
+//! それぞれに TOML ソース上の位置（行・列）を付与して報告します。
+//!
+//! - 空のワークフロー名・ステップ名
+//! - 重複するステップ名
+//! - 不正なプロバイダー・モデルティア・モデル名
+//! - 不正なプロバイダーの定義（`[providers.<name>]`）・モデル名の対応表（`[models.<provider>]`）・単価（`[prices]`）
+//! - 不正な予算（`[budget]` / `budget`）
+//! - 空または長すぎるシステムプロンプト
+//! - 不正なテンプレート・実行条件、存在しない・後続のステップへの参照
+//! - 読み込めない・不正な出力スキーマ（`output_schema`）
+//! - 存在しないステップへの依存、循環依存
+//! - 不正なループ範囲・終了条件、範囲の重なり
+//! - 必須キーの欠落
+//!
+//! 個々のルールは [`Workflow`] / [`WorkflowStep`](super::step::WorkflowStep) への変換と
+//! 共有しているため、ここで報告されるメッセージは読み込み時のエラーと一致します。
+//!
+//! # 使用例
+//!
+//! ```rust
+//! use melted_adw::config::validate::validate_toml;
+//!
+//! let toml = r#"
+//! [workflow]
+//! name = "example"
+//!
+//! [[steps]]
+//! name = "plan"
+//! system_prompt = ""
+//! provider = "gemini"
+//! model_tier = "heavy"
+//! "#;
+//!
+//! let diagnostics = validate_toml(toml);
+//! assert_eq!(diagnostics.len(), 2);
+//! assert_eq!(diagnostics[0].line, Some(7));
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use toml::Spanned;
+
+use crate::error::{ConfigError, ProviderError};
+use crate::provider::ProviderRegistry;
+use super::dto::{SpannedWorkflowDto, SpannedWorkflowStepDto};
+use super::condition;
+use super::fallback;
+use super::graph;
+use super::loops;
+use super::models::{self, ModelTable};
+use super::budget;
+use super::prices;
+use super::providers::{self, ProviderDefinition};
+use super::retry;
+use super::schema;
+use super::step;
+use super::template::Template;
+use super::workflow::{self, Workflow};
+
+/// バリデーションで検出された問題
+///
+/// 位置が特定できない問題（ファイル全体に関わるもの）は `span` が `None` になります。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// エラーメッセージ
+    pub message: String,
+    /// ソース上のバイト範囲
+    pub span: Option<Range<usize>>,
+    /// 行番号（1始まり）
+    pub line: Option<usize>,
+    /// 列番号（1始まり、文字単位）
+    pub column: Option<usize>,
+}
+
+impl Diagnostic {
+    /// ソース上の位置を付与して生成
+    fn at(source: &str, span: Range<usize>, message: impl Into<String>) -> Self {
+        let (line, column) = line_column(source, span.start);
+        Self {
+            message: message.into(),
+            span: Some(span),
+            line: Some(line),
+            column: Some(column),
+        }
+    }
+
+    /// 位置情報なしで生成
+    fn without_span(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            span: None,
+            line: None,
+            column: None,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// TOML 文字列を検証し、検出したすべての問題を返す
+///
+/// 問題がなければ空の `Vec` を返します。
+/// 結果はソース上の出現順に並びます。
+/// `output_schema` のファイルパスはカレントディレクトリを基準に解決し、
+/// プロバイダー名は組み込みのプロバイダーと照合します。
+pub fn validate_toml(source: &str) -> Vec<Diagnostic> {
+    validate_toml_in_dir(source, Path::new(""))
+}
+
+/// TOML 文字列を検証し、検出したすべての問題を返す（相対パスの基準ディレクトリを指定）
+///
+/// `output_schema` のファイルパスを `base_dir` を基準に解決する点以外は [`validate_toml`] と同じです。
+pub fn validate_toml_in_dir(source: &str, base_dir: &Path) -> Vec<Diagnostic> {
+    validate_toml_with_registry(source, base_dir, &ProviderRegistry::builtin())
+}
+
+/// TOML 文字列を検証し、検出したすべての問題を返す（基準ディレクトリとプロバイダーの登録簿を指定）
+///
+/// プロバイダー名を `registry` と照合する点以外は [`validate_toml_in_dir`] と同じです。
+pub fn validate_toml_with_registry(source: &str, base_dir: &Path, registry: &ProviderRegistry) -> Vec<Diagnostic> {
+    let dto: SpannedWorkflowDto = match toml::from_str(source) {
+        Ok(dto) => dto,
+        Err(e) => return vec![toml_error_diagnostic(source, &e)],
+    };
+
+    let mut diagnostics = Vec::new();
+
+    // [workflow] セクション
+    let workflow_name = match &dto.workflow {
+        None => {
+            diagnostics.push(Diagnostic::at(
+                source,
+                0..0,
+                "[workflow] セクションがありません",
+            ));
+            String::new()
+        }
+        Some(metadata) => match &metadata.get_ref().name {
+            None => {
+                diagnostics.push(Diagnostic::at(
+                    source,
+                    metadata.span(),
+                    "[workflow] に必須キー 'name' がありません",
+                ));
+                String::new()
+            }
+            Some(name) => {
+                if let Err(message) = workflow::validate_workflow_name(name.get_ref()) {
+                    diagnostics.push(Diagnostic::at(source, name.span(), message));
+                }
+                name.get_ref().clone()
+            }
+        },
+    };
+
+    // [providers.<name>] テーブル
+    let mut extended_registry = registry.clone();
+    let mut definitions = Vec::new();
+    for (name, provider_dto) in &dto.providers {
+        let result = providers::parse_provider_definition(name, provider_dto.get_ref()).and_then(|definition| {
+            workflow::check_provider_name(definition.name(), registry)?;
+            Ok(definition)
+        });
+        match result {
+            Ok(definition) => {
+                extended_registry.register_definition(definition.clone());
+                definitions.push(definition);
+            }
+            Err(message) => {
+                diagnostics.push(Diagnostic::at(source, provider_dto.span(), message));
+                // 定義の誤りは上で報告済みのため、ステップの provider の照合では登録済みとして扱う
+                if !registry.contains(name) {
+                    extended_registry.register(name, move || Err(ProviderError::UnknownProvider(String::new())));
+                }
+            }
+        }
+    }
+
+    // [models.<provider>] テーブル
+    let mut valid_models = std::collections::BTreeMap::new();
+    for (provider, models_dto) in &dto.models {
+        let result = models::parse_tier_models(provider, models_dto.get_ref(), |provider| {
+            workflow::check_models_provider(provider, &extended_registry)
+        });
+        match result {
+            Ok(_) => {
+                valid_models.insert(provider.clone(), models_dto.get_ref().clone());
+            }
+            Err(message) => diagnostics.push(Diagnostic::at(source, models_dto.span(), message)),
+        }
+    }
+    let model_table = models::parse_model_table(&valid_models, |_| Ok(())).unwrap_or_default();
+
+    // [prices."<model>"] テーブル
+    for (model, price_dto) in &dto.prices {
+        if let Err(message) = prices::parse_model_price(model, price_dto.get_ref()) {
+            diagnostics.push(Diagnostic::at(source, price_dto.span(), message));
+        }
+    }
+
+    // [budget] テーブル
+    if let Some(budget_dto) = &dto.budget
+        && let Err(message) = budget::parse_budget("[budget]", budget_dto.get_ref())
+    {
+        diagnostics.push(Diagnostic::at(source, budget_dto.span(), message));
+    }
+
+    // [[steps]] 配列
+    if dto.steps.is_empty() {
+        diagnostics.push(Diagnostic::at(
+            source,
+            0..0,
+            workflow::no_steps_message(&workflow_name),
+        ));
+    }
+
+    // 名前のないステップは空文字列として扱う（欠落は validate_step で報告）
+    let all_names: Vec<&str> = dto
+        .steps
+        .iter()
+        .map(|step| step.get_ref().name.as_ref().map_or("", |name| name.get_ref().as_str()))
+        .collect();
+
+    let mut step_names = HashSet::new();
+    for (index, step_dto) in dto.steps.iter().enumerate() {
+        let context = StepContext {
+            registry: &extended_registry,
+            definitions: &definitions,
+            models: &model_table,
+        };
+        validate_step(source, index, step_dto, base_dir, &context, &mut diagnostics);
+
+        if let Some(name) = &step_dto.get_ref().name
+            && !name.get_ref().trim().is_empty()
+            && !step_names.insert(name.get_ref().as_str())
+        {
+            diagnostics.push(Diagnostic::at(
+                source,
+                name.span(),
+                workflow::duplicate_step_message(&workflow_name, name.get_ref()),
+            ));
+        }
+    }
+
+    // 依存関係（depends_on）とステップ参照
+    let dependencies = validate_dependencies(source, &dto.steps, &all_names, &mut diagnostics);
+    for (index, step_dto) in dto.steps.iter().enumerate() {
+        let ancestors = workflow::ancestor_names(&dependencies, index, &all_names);
+        validate_step_references(source, step_dto, &ancestors, &all_names, &mut diagnostics);
+    }
+
+    // [[loops]] 配列
+    let mut step_loops = Vec::new();
+    for loop_dto in &dto.loops {
+        let result = loops::parse_loop(loop_dto.get_ref(), &all_names).and_then(|step_loop| {
+            loops::check_against_previous(&step_loop, &step_loops)?;
+            graph::check_loop_dependencies(&step_loop, &dependencies, &all_names)?;
+            Ok(step_loop)
+        });
+        match result {
+            Ok(step_loop) => step_loops.push(step_loop),
+            Err(message) => diagnostics.push(Diagnostic::at(source, loop_dto.span(), message)),
+        }
+    }
+
+    // 上記で検出できない問題（型の不一致など）は通常の読み込みで確認する
+    if diagnostics.is_empty()
+        && let Err(e) = Workflow::from_toml_with_registry(source, base_dir, registry)
+    {
+        diagnostics.push(match e {
+            ConfigError::TomlDeserialize(e) => toml_error_diagnostic(source, &e),
+            ConfigError::Validation(message) => Diagnostic::without_span(message),
+            other => Diagnostic::without_span(other.to_string()),
+        });
+    }
+
+    diagnostics.sort_by_key(|d| d.span.as_ref().map_or(0, |span| span.start));
+    diagnostics
+}
+
+/// TOML ファイルを検証する
+///
+/// # エラー
+///
+/// - [`ConfigError::FileRead`] - ファイルの読み込みに失敗した場合
+pub fn validate_file(path: impl AsRef<Path>) -> Result<Vec<Diagnostic>, ConfigError> {
+    validate_file_with_registry(path, &ProviderRegistry::builtin())
+}
+
+/// TOML ファイルを検証する（プロバイダーの登録簿を指定）
+///
+/// # エラー
+///
+/// - [`ConfigError::FileRead`] - ファイルの読み込みに失敗した場合
+pub fn validate_file_with_registry(
+    path: impl AsRef<Path>,
+    registry: &ProviderRegistry,
+) -> Result<Vec<Diagnostic>, ConfigError> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)?;
+    Ok(validate_toml_with_registry(&source, path.parent().unwrap_or(Path::new("")), registry))
+}
+
+/// 検証対象の TOML ファイルを列挙する
+///
+/// `path` がファイルの場合はそのファイルのみを、ディレクトリの場合は
+/// 配下の `.toml` ファイルを再帰的に探索し、パス順にソートして返します。
+///
+/// # エラー
+///
+/// - [`ConfigError::FileRead`] - ディレクトリの走査に失敗した場合
+pub fn find_workflow_files(path: impl AsRef<Path>) -> Result<Vec<PathBuf>, ConfigError> {
+    let path = path.as_ref();
+    let mut files = Vec::new();
+
+    if path.is_dir() {
+        collect_toml_files(path, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    Ok(files)
+}
+
+/// ディレクトリ配下の `.toml` ファイルを再帰的に収集する
+fn collect_toml_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), ConfigError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_toml_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "toml") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// ステップの検証に使うワークフロー全体の定義
+struct StepContext<'a> {
+    /// ワークフローで定義したプロバイダーを含む登録簿
+    registry: &'a ProviderRegistry,
+    /// ワークフローで定義したプロバイダー
+    definitions: &'a [ProviderDefinition],
+    /// モデル名の対応表（`[models.<provider>]`）
+    models: &'a ModelTable,
+}
+
+/// 1つのステップを検証する
+fn validate_step(
+    source: &str,
+    index: usize,
+    step_dto: &Spanned<SpannedWorkflowStepDto>,
+    base_dir: &Path,
+    context: &StepContext<'_>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let dto = step_dto.get_ref();
+
+    // エラーメッセージ用のステップ名（未指定の場合はインデックス）
+    let step_name = match &dto.name {
+        Some(name) => name.get_ref().clone(),
+        None => format!("#{}", index + 1),
+    };
+
+    let mut require = |key: &str, value: &Option<Spanned<String>>| {
+        if value.is_none() {
+            diagnostics.push(Diagnostic::at(
+                source,
+                step_dto.span(),
+                format!("ステップ '{}' に必須キー '{}' がありません", step_name, key),
+            ));
+        }
+    };
+    require("name", &dto.name);
+    require("system_prompt", &dto.system_prompt);
+    require("provider", &dto.provider);
+    require("model_tier", &dto.model_tier);
+
+    if let Some(name) = &dto.name
+        && let Err(message) = step::validate_name(name.get_ref())
+    {
+        diagnostics.push(Diagnostic::at(source, name.span(), message));
+    }
+
+    if let Some(prompt) = &dto.system_prompt {
+        if let Err(message) = step::validate_system_prompt(&step_name, prompt.get_ref()) {
+            diagnostics.push(Diagnostic::at(source, prompt.span(), message));
+        }
+        if let Err(message) = step::parse_template(&step_name, "system_prompt", prompt.get_ref()) {
+            diagnostics.push(Diagnostic::at(source, prompt.span(), message));
+        }
+    }
+
+    if let Some(input) = &dto.input
+        && let Err(message) = step::parse_template(&step_name, "input", input.get_ref())
+    {
+        diagnostics.push(Diagnostic::at(source, input.span(), message));
+    }
+
+    if let Some(when) = &dto.when
+        && let Err(message) = condition::parse_condition(&step::when_context(&step_name), when.get_ref())
+    {
+        diagnostics.push(Diagnostic::at(source, when.span(), message));
+    }
+
+    if let Some(provider) = &dto.provider
+        && let Err(message) = step::parse_provider(&step_name, provider.get_ref(), context.registry)
+    {
+        diagnostics.push(Diagnostic::at(source, provider.span(), message));
+    }
+
+    if let Some(model_tier) = &dto.model_tier {
+        match step::parse_model_tier(&step_name, model_tier.get_ref()) {
+            Ok(tier) => {
+                // ワークフローで定義したプロバイダーのモデル（モデル名を指定した場合は不要）
+                let provider = dto.provider.as_ref().map(|provider| provider.get_ref().to_lowercase());
+                let configured = dto.model.is_some()
+                    || provider.as_deref().is_some_and(|provider| context.models.get(provider, &tier).is_some());
+                let definition = provider.as_deref().and_then(|name| {
+                    context.definitions.iter().find(|definition| definition.name() == name)
+                });
+                if !configured
+                    && let Some(definition) = definition
+                    && let Err(message) = providers::check_step_model(&step_name, &tier, definition)
+                {
+                    diagnostics.push(Diagnostic::at(source, model_tier.span(), message));
+                }
+            }
+            Err(message) => diagnostics.push(Diagnostic::at(source, model_tier.span(), message)),
+        }
+    }
+
+    if let Some(model) = &dto.model
+        && let Err(message) = step::validate_model(&step_name, Some(model.get_ref()))
+    {
+        diagnostics.push(Diagnostic::at(source, model.span(), message));
+    }
+
+    if let Some(retry) = &dto.retry
+        && let Err(message) = retry::parse_retry_policy(&step_name, retry.get_ref())
+    {
+        diagnostics.push(Diagnostic::at(source, retry.span(), message));
+    }
+
+    if let Some(budget) = &dto.budget
+        && let Err(message) = budget::parse_budget(&step::budget_subject(&step_name), budget.get_ref())
+    {
+        diagnostics.push(Diagnostic::at(source, budget.span(), message));
+    }
+
+    if let Some(fallback) = &dto.fallback {
+        match fallback::parse_fallbacks(&step_name, fallback.get_ref(), context.registry) {
+            Ok(fallbacks) => {
+                // ワークフローで定義したプロバイダーのモデル（ステップのティアが不正な場合は検証しない）
+                let step_tier = dto
+                    .model_tier
+                    .as_ref()
+                    .and_then(|model_tier| step::parse_model_tier(&step_name, model_tier.get_ref()).ok());
+                for (position, entry) in fallbacks.iter().enumerate() {
+                    let Some(tier) = entry.model_tier().or(step_tier.as_ref()) else {
+                        continue;
+                    };
+                    let provider = entry.provider().as_str();
+                    let configured = entry.model().is_some() || context.models.get(provider, tier).is_some();
+                    let definition = context.definitions.iter().find(|definition| definition.name() == provider);
+                    if !configured
+                        && let Some(definition) = definition
+                        && let Err(message) = providers::check_fallback_model(&step_name, position, tier, definition)
+                    {
+                        diagnostics.push(Diagnostic::at(source, fallback.span(), message));
+                    }
+                }
+            }
+            Err(message) => diagnostics.push(Diagnostic::at(source, fallback.span(), message)),
+        }
+    }
+
+    if let Some(output_schema) = &dto.output_schema
+        && let Err(message) = schema::parse_output_schema(&step_name, output_schema.get_ref(), base_dir)
+    {
+        diagnostics.push(Diagnostic::at(source, output_schema.span(), message));
+    }
+
+    if let Some(repair_attempts) = &dto.repair_attempts
+        && let Err(message) = step::validate_repair_attempts(
+            &step_name,
+            dto.output_schema.is_some(),
+            Some(*repair_attempts.get_ref()),
+        )
+    {
+        diagnostics.push(Diagnostic::at(source, repair_attempts.span(), message));
+    }
+}
+
+/// ステップの依存先を解決し、循環を検証する
+///
+/// 解決できなかったステップは依存なしとして扱い、ステップごとの依存先を返します。
+fn validate_dependencies(
+    source: &str,
+    steps: &[Spanned<SpannedWorkflowStepDto>],
+    all_names: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<Vec<usize>> {
+    let dependencies: Vec<Vec<usize>> = steps
+        .iter()
+        .enumerate()
+        .map(|(index, step_dto)| {
+            let depends_on = step_dto.get_ref().depends_on.as_ref();
+            graph::resolve_dependencies(
+                all_names[index],
+                index,
+                depends_on.map(|depends_on| depends_on.get_ref().as_slice()),
+                all_names,
+            )
+            .unwrap_or_else(|message| {
+                let span = depends_on.map_or(step_dto.span(), Spanned::span);
+                diagnostics.push(Diagnostic::at(source, span, message));
+                Vec::new()
+            })
+        })
+        .collect();
+
+    if let Some(cycle) = graph::find_cycle(&dependencies) {
+        let step_dto = &steps[cycle[0]];
+        let span = step_dto
+            .get_ref()
+            .depends_on
+            .as_ref()
+            .map_or(step_dto.span(), Spanned::span);
+        diagnostics.push(Diagnostic::at(source, span, graph::cycle_message(&cycle, all_names)));
+    }
+
+    dependencies
+}
+
+/// ステップのテンプレート・実行条件が先行ステップ（依存先の祖先）のみを参照しているか検証する
+///
+/// テンプレート自体の構文エラーは [`validate_step`] で報告済みのため、ここでは無視します。
+fn validate_step_references(
+    source: &str,
+    step_dto: &Spanned<SpannedWorkflowStepDto>,
+    ancestors: &[&str],
+    all_names: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let dto = step_dto.get_ref();
+    let Some(name) = &dto.name else {
+        return;
+    };
+
+    for field in [&dto.system_prompt, &dto.input].into_iter().flatten() {
+        let Ok(template) = Template::parse(field.get_ref()) else {
+            continue;
+        };
+        for referenced in template.step_references() {
+            if let Err(message) =
+                workflow::check_step_reference(name.get_ref(), referenced, ancestors, all_names)
+            {
+                diagnostics.push(Diagnostic::at(source, field.span(), message));
+            }
+        }
+    }
+
+    if let Some(when) = &dto.when
+        && let Err(message) =
+            workflow::check_step_reference(name.get_ref(), &when.get_ref().step, ancestors, all_names)
+    {
+        diagnostics.push(Diagnostic::at(source, when.span(), message));
+    }
+}
+
+/// TOML パースエラーを診断に変換する
+fn toml_error_diagnostic(source: &str, error: &toml::de::Error) -> Diagnostic {
+    let message = error.message().trim().to_string();
+    match error.span() {
+        Some(span) => Diagnostic::at(source, span, message),
+        None => Diagnostic::without_span(message),
+    }
+}
+
+/// バイトオフセットを行・列番号（1始まり）に変換する
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_workflow_has_no_diagnostics() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "plan"
+system_prompt = "Create a plan"
+provider = "anthropic"
+model_tier = "heavy"
+"#;
+
+        assert!(validate_toml(toml).is_empty());
+    }
+
+    #[test]
+    fn test_template_errors_are_located() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan using {{steps.review.output}}"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "review"
+system_prompt = "Review {{steps.design.output}}"
+input = "{{unknown}}"
+provider = "anthropic"
+model_tier = "heavy"
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 3);
+
+        assert!(diagnostics[0].message.contains("先行していないステップ 'review'"));
+        assert_eq!(diagnostics[0].line, Some(7));
+
+        assert!(diagnostics[1].message.contains("存在しないステップ 'design'"));
+        assert_eq!(diagnostics[1].line, Some(13));
+
+        assert!(diagnostics[2].message.contains("不明なテンプレート変数"));
+        assert_eq!(diagnostics[2].line, Some(14));
+    }
+
+    #[test]
+    fn test_loop_errors_are_located() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "implement"
+system_prompt = "Implement"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[loops]]
+name = "review-fix"
+from = "implement"
+to = "review"
+max_iterations = 3
+until = { step = "review", contains = "LGTM" }
+
+[[loops]]
+name = "again"
+from = "review"
+to = "review"
+max_iterations = 2
+until = { step = "review", contains = "LGTM" }
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("重なっています"));
+        assert_eq!(diagnostics[0].line, Some(24));
+    }
+
+    #[test]
+    fn test_dependency_errors_are_located() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+depends_on = ["review"]
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "deploy"
+system_prompt = "Deploy"
+provider = "anthropic"
+model_tier = "heavy"
+depends_on = ["release"]
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert!(diagnostics[0].message.contains("循環しています: plan -> review -> plan"));
+        assert_eq!(diagnostics[0].line, Some(10));
+
+        assert!(diagnostics[1].message.contains("存在しないステップ 'release'"));
+        assert_eq!(diagnostics[1].line, Some(23));
+    }
+
+    #[test]
+    fn test_collects_all_errors_across_steps() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "plan"
+system_prompt = ""
+provider = "gemini"
+model_tier = "heavy"
+
+[[steps]]
+name = "plan"
+system_prompt = "prompt"
+provider = "anthropic"
+model_tier = "ultra"
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 4);
+
+        assert!(diagnostics[0].message.contains("システムプロンプトが空です"));
+        assert_eq!(diagnostics[0].line, Some(7));
+        assert_eq!(diagnostics[0].column, Some(17));
+
+        assert!(diagnostics[1].message.contains("不正なプロバイダー"));
+        assert_eq!(diagnostics[1].line, Some(8));
+
+        assert!(diagnostics[2].message.contains("重複するステップ名"));
+        assert_eq!(diagnostics[2].line, Some(12));
+
+        assert!(diagnostics[3].message.contains("不正なモデルティア"));
+        assert_eq!(diagnostics[3].line, Some(15));
+
+        // 登録簿に追加したプロバイダーは問題にならない
+        let registry = ProviderRegistry::builtin().with_provider("gemini", || {
+            Ok(Box::new(crate::provider::openai::OpenAIClient::new()) as Box<dyn crate::provider::ProviderClient>)
+        });
+        let diagnostics = validate_toml_with_registry(toml, Path::new(""), &registry);
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics.iter().all(|d| !d.message.contains("不正なプロバイダー")));
+
+        let valid = toml.replace("system_prompt = \"\"", "system_prompt = \"p\"")
+            .replacen("name = \"plan\"\nsystem_prompt = \"prompt\"", "name = \"review\"\nsystem_prompt = \"prompt\"", 1)
+            .replace("\"ultra\"", "\"light\"");
+        assert!(validate_toml_with_registry(&valid, Path::new(""), &registry).is_empty());
+    }
+
+    #[test]
+    fn test_command_provider_errors_are_located() {
+        let toml = r#"[workflow]
+name = "command"
+
+[providers.agent]
+command = "agent"
+output = { format = "json" }
+
+[[steps]]
+name = "draft"
+system_prompt = "Draft"
+provider = "agent"
+model_tier = "light"
+"#;
+
+        // 定義の誤りだけを報告し、それを参照するステップは問題にしない
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("output.text が必要です"));
+        assert_eq!(diagnostics[0].line, Some(4));
+
+        let valid = toml.replace(r#"{ format = "json" }"#, r#"{ format = "json", text = "/result" }"#);
+        assert!(validate_toml(&valid).is_empty());
+
+        // OpenAI 互換 API の models にないティアはステップ側で報告する
+        let local = r#"[workflow]
+name = "local"
+
+[providers.local]
+type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+models = { light = "llama3.2:3b" }
+
+[[steps]]
+name = "draft"
+system_prompt = "Draft"
+provider = "local"
+model_tier = "medium"
+"#;
+        let diagnostics = validate_toml(local);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("モデルティア 'medium'"));
+        assert_eq!(diagnostics[0].line, Some(13));
+    }
+
+    #[test]
+    fn test_models_errors_are_located() {
+        let toml = r#"[workflow]
+name = "models"
+
+[models.gemini]
+heavy = "gemini-2.5-pro"
+
+[models.local]
+heavy = "qwen2.5-coder:32b"
+
+[providers.local]
+type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+models = { light = "llama3.2:3b" }
+
+[[steps]]
+name = "draft"
+system_prompt = "Draft"
+provider = "local"
+model_tier = "heavy"
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "anthropic"
+model_tier = "medium"
+model = ""
+"#;
+
+        // [models.local] があるため local の heavy は問題にしない
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("[models.gemini] のプロバイダーが登録されていません"));
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert!(diagnostics[1].message.contains("ステップ 'review' の model が空です"));
+        assert_eq!(diagnostics[1].line, Some(26));
+    }
+
+    #[test]
+    fn test_fallback_errors_are_located() {
+        let toml = r#"[workflow]
+name = "fallback"
+
+[providers.local]
+type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+models = { light = "llama3.2:3b" }
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+fallback = [{ provider = "gemini" }]
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "anthropic"
+model_tier = "heavy"
+fallback = [{ provider = "openai" }, { provider = "local" }]
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("ステップ 'plan' の fallback[0] の不正なプロバイダー: 'gemini'"));
+        assert_eq!(diagnostics[0].line, Some(14));
+        assert!(diagnostics[1].message.contains("ステップ 'review' の fallback[1] のモデルティア 'heavy'"));
+        assert_eq!(diagnostics[1].line, Some(21));
+    }
+
+    #[test]
+    fn test_retry_errors_are_located() {
+        let toml = r#"[workflow]
+name = "retry"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+retry_count = 3
+retry = { initial_delay = 2, retry_on = ["rate_limit", "auth"] }
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("ステップ 'plan' の retry の retry_on が不正です: 'auth'"));
+        assert_eq!(diagnostics[0].line, Some(10));
+    }
+
+    #[test]
+    fn test_budget_errors_are_located() {
+        let toml = r#"[workflow]
+name = "budget"
+
+[budget]
+max_cost_usd = -1
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+budget = { max_tokens_total = 0 }
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+        assert!(diagnostics[0].message.contains("[budget] の max_cost_usd は0以上を指定してください"));
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert!(diagnostics[1].message.contains("ステップ 'plan' の budget の max_tokens_total は1以上を指定してください"));
+        assert_eq!(diagnostics[1].line, Some(12));
+    }
+
+    #[test]
+    fn test_empty_names() {
+        let toml = r#"
+[workflow]
+name = " "
+
+[[steps]]
+name = ""
+system_prompt = "prompt"
+provider = "anthropic"
+model_tier = "heavy"
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].message.contains("ワークフロー名が空です"));
+        assert_eq!(diagnostics[0].line, Some(3));
+        assert!(diagnostics[1].message.contains("ステップ名が空です"));
+        assert_eq!(diagnostics[1].line, Some(6));
+    }
+
+    #[test]
+    fn test_system_prompt_too_long() {
+        let toml = format!(
+            "[workflow]\nname = \"test\"\n\n[[steps]]\nname = \"s\"\nsystem_prompt = \"{}\"\nprovider = \"anthropic\"\nmodel_tier = \"heavy\"\n",
+            "a".repeat(10001)
+        );
+
+        let diagnostics = validate_toml(&toml);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("長すぎます"));
+        assert_eq!(diagnostics[0].line, Some(6));
+    }
+
+    #[test]
+    fn test_missing_required_keys() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "plan"
+provider = "anthropic"
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().any(|d| d.message.contains("'system_prompt'")));
+        assert!(diagnostics.iter().any(|d| d.message.contains("'model_tier'")));
+    }
+
+    #[test]
+    fn test_no_steps() {
+        let toml = r#"
+[workflow]
+name = "test"
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("ステップが定義されていません"));
+    }
+
+    #[test]
+    fn test_toml_syntax_error() {
+        let toml = "[workflow\nname = \"broken\"\n";
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_type_mismatch_in_unchecked_field() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "plan"
+system_prompt = "prompt"
+provider = "anthropic"
+model_tier = "heavy"
+timeout = "soon"
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, Some(10));
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Diagnostic::at("a\nbc", 3..4, "message");
+        assert_eq!(diagnostic.to_string(), "2:2: message");
+        assert_eq!(Diagnostic::without_span("message").to_string(), "message");
+    }
+
+    #[test]
+    fn test_output_schema_errors_are_located() {
+        let toml = r#"
+[workflow]
+name = "test"
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "anthropic"
+model_tier = "heavy"
+output_schema = "schemas/missing.json"
+
+[[steps]]
+name = "summarize"
+system_prompt = "Summarize"
+provider = "anthropic"
+model_tier = "light"
+repair_attempts = 1
+"#;
+
+        let diagnostics = validate_toml(toml);
+        assert_eq!(diagnostics.len(), 2);
+
+        assert!(diagnostics[0].message.contains("ステップ 'review' の output_schema が不正です"));
+        assert_eq!(diagnostics[0].line, Some(10));
+
+        assert!(diagnostics[1].message.contains("repair_attempts は output_schema と組み合わせて"));
+        assert_eq!(diagnostics[1].line, Some(17));
+    }
+
+    #[test]
+    fn test_find_workflow_files_in_directory() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/workflows");
+        let files = find_workflow_files(dir).unwrap();
+
+        assert!(files.iter().any(|f| f.ends_with("example.toml")));
+        assert!(files.iter().any(|f| f.ends_with("examples/review.toml")));
+        assert!(files.iter().all(|f| f.extension().unwrap() == "toml"));
+    }
+
+    #[test]
+    fn test_bundled_workflows_are_valid() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/workflows");
+        for file in find_workflow_files(dir).unwrap() {
+            let diagnostics = validate_file(&file).unwrap();
+            assert!(diagnostics.is_empty(), "{}: {:?}", file.display(), diagnostics);
+        }
+    }
+}
@@ -51,8 +51,15 @@
 use std::path::Path;
 
 use crate::error::ConfigError;
-use super::step::WorkflowStep;
-use super::dto::WorkflowDto;
+use crate::provider::ProviderRegistry;
+use super::budget::{self, Budget};
+use super::graph;
+use super::loops::{self, StepLoop};
+use super::models::{self, ModelTable};
+use super::prices::{self, PriceTable};
+use super::providers::{self, ProviderDefinition};
+use super::step::{Provider, WorkflowStep};
+use super::dto::{ProviderDto, WorkflowDto, WorkflowStepDto};
 
 /// ワークフロー定義（ドメインモデル）
 ///
@@ -73,6 +80,18 @@ pub struct Workflow {
     version: Option<String>,
     /// ステップ配列
     steps: Vec<WorkflowStep>,
+    /// ステップ範囲の繰り返し
+    loops: Vec<StepLoop>,
+    /// ステップごとの依存先インデックス（`depends_on` を解決済み）
+    dependencies: Vec<Vec<usize>>,
+    /// ワークフローで定義したプロバイダー（名前順）
+    providers: Vec<ProviderDefinition>,
+    /// モデルティアに対応するモデル名
+    models: ModelTable,
+    /// モデルごとのトークン単価
+    prices: PriceTable,
+    /// ワークフロー全体の予算
+    budget: Budget,
 }
 
 impl Workflow {
@@ -95,6 +114,106 @@ impl Workflow {
     pub fn steps(&self) -> &[WorkflowStep] {
         &self.steps
     }
+
+    /// ステップ範囲の繰り返しを取得
+    pub fn loops(&self) -> &[StepLoop] {
+        &self.loops
+    }
+
+    /// ワークフローで定義したプロバイダー（`[providers.<name>]`）を取得
+    pub fn providers(&self) -> &[ProviderDefinition] {
+        &self.providers
+    }
+
+    /// モデルティアに対応するモデル名（`[models.<provider>]`）を取得
+    ///
+    /// ステップの `model` より優先度が低く、ユーザー設定より優先されます
+    /// （[`select_model`](super::models::select_model)）。
+    pub fn models(&self) -> &ModelTable {
+        &self.models
+    }
+
+    /// モデルごとのトークン単価（`[prices."<model>"]`）を取得
+    ///
+    /// ユーザー設定・組み込みの単価より優先されます（[`select_price`](super::prices::select_price)）。
+    pub fn prices(&self) -> &PriceTable {
+        &self.prices
+    }
+
+    /// ワークフロー全体の予算（`[budget]`）を取得（未指定の場合は無制限）
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// 指定インデックスのステップが依存するステップのインデックスを取得
+    ///
+    /// `depends_on` 省略時の暗黙の依存（直前のステップ）も含みます。
+    /// 依存関係に循環がないことは読み込み時に検証済みです。
+    pub fn dependencies(&self, index: usize) -> &[usize] {
+        &self.dependencies[index]
+    }
+
+    /// 指定インデックスのステップで終わるループを取得
+    pub fn loop_ending_at(&self, index: usize) -> Option<&StepLoop> {
+        self.loops.iter().find(|step_loop| step_loop.end() == index)
+    }
+
+    /// 指定インデックスのステップを含むループを取得
+    pub fn loop_containing(&self, index: usize) -> Option<&StepLoop> {
+        self.loops.iter().find(|step_loop| step_loop.contains(index))
+    }
+
+    /// 定義全体のハッシュ値を取得
+    ///
+    /// 読み込み済みの定義（ファイルから読み込んだシステムプロンプトを含む）から計算するため、
+    /// TOML の書式やコメントだけの変更では変わりません。
+    pub fn fingerprint(&self) -> String {
+        super::fingerprint(&self.to_string().unwrap_or_default())
+    }
+
+    /// 指定インデックスのステップの定義のハッシュ値を取得
+    ///
+    /// ステップの定義と、ステップを含むループの範囲（`from` / `to`）から計算します。
+    /// 他のステップの変更では変わらないため、完了したステップの出力を再利用できるかの判定に使います。
+    /// ステップが使うモデルとプロバイダーの定義は [`target_fingerprint`](Self::target_fingerprint) で判定します。
+    pub fn step_fingerprint(&self, index: usize) -> String {
+        let step = WorkflowStepDto::from(self.steps[index].clone());
+        let loop_range = self
+            .loop_containing(index)
+            .map(|step_loop| format!("{}..{}", step_loop.from(), step_loop.to()))
+            .unwrap_or_default();
+        let definition = serde_json::to_string(&step).unwrap_or_default();
+        super::fingerprint(&format!("{}\n{}", definition, loop_range))
+    }
+
+    /// 指定インデックスのステップの実行先のハッシュ値を取得
+    ///
+    /// ステップとフォールバック先のプロバイダーごとに、`[models]` で解決したモデル名と
+    /// `[providers]` の定義から計算します。ステップの定義を変えずに `[models]` や `[providers]` を
+    /// 変更した場合に、完了したステップを実行し直すかの判定に使います。ユーザー設定の `[models]` は含みません。
+    pub fn target_fingerprint(&self, index: usize) -> String {
+        let step = &self.steps[index];
+        let no_user_models = ModelTable::default();
+        let targets = std::iter::once((step.provider(), models::select_model(step, &self.models, &no_user_models)))
+            .chain(step.fallback().iter().map(|fallback| {
+                let model = models::select_fallback_model(step, fallback, &self.models, &no_user_models);
+                (fallback.provider(), model)
+            }))
+            .map(|(provider, model)| self.target_definition(provider, model))
+            .collect::<Vec<_>>();
+        super::fingerprint(&targets.join("\n"))
+    }
+
+    /// 実行先のプロバイダー名・モデル名・プロバイダーの定義を1行にまとめる（プライベートメソッド）
+    fn target_definition(&self, provider: &Provider, model: Option<&str>) -> String {
+        let definition = self
+            .providers
+            .iter()
+            .find(|definition| definition.name() == provider.as_str())
+            .and_then(|definition| serde_json::to_string(&ProviderDto::from(definition.clone())).ok())
+            .unwrap_or_default();
+        format!("{} {} {}", provider.as_str(), model.unwrap_or_default(), definition)
+    }
 }
 
 impl Workflow {
@@ -115,8 +234,25 @@ impl Workflow {
     /// * `Ok(Workflow)` - 読み込みに成功した場合
     /// * `Err(ConfigError)` - ファイルの読み込みまたはパースに失敗した場合
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_file_with_registry(path, &ProviderRegistry::builtin())
+    }
+
+    /// TOML ファイルからワークフローを読み込む（プロバイダーの登録簿を指定）
+    ///
+    /// [`from_file`](Self::from_file) と同じですが、各ステップの `provider` を
+    /// 組み込みのプロバイダーではなく `registry` と照合します。
+    ///
+    /// # 引数
+    ///
+    /// * `path` - TOML ファイルのパス
+    /// * `registry` - 使用できるプロバイダーの登録簿
+    pub fn from_file_with_registry(
+        path: impl AsRef<Path>,
+        registry: &ProviderRegistry,
+    ) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
         let content = std::fs::read_to_string(path)?;
-        Self::from_toml(&content)
+        Self::from_toml_with_registry(&content, path.parent().unwrap_or(Path::new("")), registry)
     }
 
     /// TOML 文字列からワークフローを読み込む
@@ -139,6 +275,38 @@ impl Workflow {
         dto.try_into()
     }
 
+    /// TOML 文字列からワークフローを読み込む（相対パスの基準ディレクトリを指定）
+    ///
+    /// [`from_toml`](Self::from_toml) と同じですが、`output_schema` のファイルパスを
+    /// `base_dir` を基準に解決します。[`from_file`](Self::from_file) はファイルのあるディレクトリを使用します。
+    ///
+    /// # 引数
+    ///
+    /// * `toml` - TOML 形式の文字列
+    /// * `base_dir` - 相対パスの基準ディレクトリ
+    pub fn from_toml_in_dir(toml: &str, base_dir: &Path) -> Result<Self, ConfigError> {
+        Self::from_toml_with_registry(toml, base_dir, &ProviderRegistry::builtin())
+    }
+
+    /// TOML 文字列からワークフローを読み込む（基準ディレクトリとプロバイダーの登録簿を指定）
+    ///
+    /// [`from_toml_in_dir`](Self::from_toml_in_dir) と同じですが、各ステップの `provider` を
+    /// `registry` と照合します。
+    ///
+    /// # 引数
+    ///
+    /// * `toml` - TOML 形式の文字列
+    /// * `base_dir` - 相対パスの基準ディレクトリ
+    /// * `registry` - 使用できるプロバイダーの登録簿
+    pub fn from_toml_with_registry(
+        toml: &str,
+        base_dir: &Path,
+        registry: &ProviderRegistry,
+    ) -> Result<Self, ConfigError> {
+        let dto: WorkflowDto = toml::from_str(toml)?;
+        Self::from_dto(dto, base_dir, registry)
+    }
+
     /// ワークフローを TOML 文字列に変換
     ///
     /// # 処理フロー
@@ -187,53 +355,222 @@ impl Workflow {
 /// 1. 各フィールドのバリデーション
 /// 2. ステップの変換（`WorkflowStepDto` → `WorkflowStep`）
 /// 3. `Workflow` の構築
+///
+/// `output_schema` のファイルパスはカレントディレクトリを基準に解決し、
+/// プロバイダー名は組み込みのプロバイダーと照合します。
 impl TryFrom<WorkflowDto> for Workflow {
     type Error = ConfigError;
 
     fn try_from(dto: WorkflowDto) -> Result<Self, Self::Error> {
-        // ワークフロー名のバリデーション
-        if dto.workflow.name.trim().is_empty() {
-            return Err(ConfigError::Validation(
-                "ワークフロー名が空です".to_string()
-            ));
+        Self::from_dto(dto, Path::new(""), &ProviderRegistry::builtin())
+    }
+}
+
+impl Workflow {
+    /// DTO からドメインモデルへの変換
+    ///
+    /// `output_schema` のパスを `base_dir` 基準で解決し、プロバイダー名を `registry` と
+    /// ワークフローで定義したコマンドプロバイダーに照合します。
+    fn from_dto(dto: WorkflowDto, base_dir: &Path, registry: &ProviderRegistry) -> Result<Self, ConfigError> {
+        validate_workflow_name(&dto.workflow.name).map_err(ConfigError::Validation)?;
+
+        // ワークフローで定義したプロバイダーの変換と登録
+        let mut definitions = Vec::new();
+        for (name, provider_dto) in &dto.providers {
+            let definition = providers::parse_provider_definition(name, provider_dto)
+                .map_err(ConfigError::Validation)?;
+            check_provider_name(definition.name(), registry).map_err(ConfigError::Validation)?;
+            definitions.push(definition);
         }
+        let extended_registry;
+        let registry = if definitions.is_empty() {
+            registry
+        } else {
+            extended_registry = registry.clone().with_definitions(&definitions);
+            &extended_registry
+        };
+
+        // モデル名の対応表（プロバイダー名は登録済みの名前のみ）
+        let models = models::parse_model_table(&dto.models, |provider| check_models_provider(provider, registry))
+            .map_err(ConfigError::Validation)?;
+        let prices = prices::parse_price_table(&dto.prices).map_err(ConfigError::Validation)?;
+        let budget = dto
+            .budget
+            .as_ref()
+            .map(|budget| budget::parse_budget("[budget]", budget))
+            .transpose()
+            .map_err(ConfigError::Validation)?
+            .unwrap_or_default();
 
         // ステップリストの非空チェック
         if dto.steps.is_empty() {
-            return Err(ConfigError::Validation(
-                format!("ワークフロー '{}' にステップが定義されていません", dto.workflow.name)
-            ));
+            return Err(ConfigError::Validation(no_steps_message(&dto.workflow.name)));
         }
 
         // 各ステップを変換（バリデーションも同時に実行）
         let steps: Result<Vec<WorkflowStep>, ConfigError> = dto.steps
             .into_iter()
-            .map(WorkflowStep::try_from)
+            .map(|step| WorkflowStep::from_dto(step, base_dir, registry))
             .collect();
         let steps = steps?;
 
+        // 定義したプロバイダーを使うステップのモデル（モデル名を指定した場合は不要）
+        let definition_of = |provider: &Provider| definitions.iter().find(|d| d.name() == provider.as_str());
+        for step in &steps {
+            if step.model().is_none()
+                && models.get(step.provider().as_str(), step.model_tier()).is_none()
+                && let Some(definition) = definition_of(step.provider())
+            {
+                providers::check_step_model(step.name(), step.model_tier(), definition)
+                    .map_err(ConfigError::Validation)?;
+            }
+            for (position, fallback) in step.fallback().iter().enumerate() {
+                let tier = fallback.model_tier().unwrap_or(step.model_tier());
+                if fallback.model().is_none()
+                    && models.get(fallback.provider().as_str(), tier).is_none()
+                    && let Some(definition) = definition_of(fallback.provider())
+                {
+                    providers::check_fallback_model(step.name(), position, tier, definition)
+                        .map_err(ConfigError::Validation)?;
+                }
+            }
+        }
+
         // ステップ名の一意性確認
         let mut step_names = std::collections::HashSet::new();
         for step in &steps {
             if !step_names.insert(step.name()) {
                 return Err(ConfigError::Validation(
-                    format!(
-                        "ワークフロー '{}' に重複するステップ名があります: '{}'",
-                        dto.workflow.name, step.name()
-                    )
+                    duplicate_step_message(&dto.workflow.name, step.name())
                 ));
             }
         }
 
+        // 依存関係の解決と循環チェック
+        let step_names: Vec<&str> = steps.iter().map(|s| s.name()).collect();
+        let dependencies = steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                graph::resolve_dependencies(step.name(), index, step.depends_on(), &step_names)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ConfigError::Validation)?;
+        if let Some(cycle) = graph::find_cycle(&dependencies) {
+            return Err(ConfigError::Validation(graph::cycle_message(&cycle, &step_names)));
+        }
+
+        // テンプレート・実行条件のステップ参照は先行ステップ（依存先の祖先）のみ許可
+        for (index, step) in steps.iter().enumerate() {
+            let ancestors = ancestor_names(&dependencies, index, &step_names);
+            for referenced in step.step_references() {
+                check_step_reference(step.name(), referenced, &ancestors, &step_names)
+                    .map_err(ConfigError::Validation)?;
+            }
+        }
+
+        // ループ範囲の解決と重複チェック
+        let mut step_loops: Vec<StepLoop> = Vec::new();
+        for loop_dto in &dto.loops {
+            let step_loop = loops::parse_loop(loop_dto, &step_names).map_err(ConfigError::Validation)?;
+            loops::check_against_previous(&step_loop, &step_loops).map_err(ConfigError::Validation)?;
+            graph::check_loop_dependencies(&step_loop, &dependencies, &step_names)
+                .map_err(ConfigError::Validation)?;
+            step_loops.push(step_loop);
+        }
+
         Ok(Workflow {
             name: dto.workflow.name,
             description: dto.workflow.description,
             version: dto.workflow.version,
             steps,
+            loops: step_loops,
+            dependencies,
+            providers: definitions,
+            models,
+            prices,
+            budget,
         })
     }
 }
 
+/// ワークフロー名のバリデーション
+pub(super) fn validate_workflow_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("ワークフロー名が空です".to_string());
+    }
+    Ok(())
+}
+
+/// 定義したプロバイダー名のバリデーション（登録済みのプロバイダーとの重複を禁止）
+pub(super) fn check_provider_name(name: &str, registry: &ProviderRegistry) -> Result<(), String> {
+    if registry.contains(name) {
+        return Err(format!(
+            "プロバイダー '{}' は登録済みのプロバイダーと重複しています",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// `[models.<provider>]` のプロバイダー名のバリデーション（`registry` に登録済みの名前のみ）
+pub(super) fn check_models_provider(provider: &str, registry: &ProviderRegistry) -> Result<(), String> {
+    if registry.contains(provider) {
+        return Ok(());
+    }
+    Err(models::unknown_provider_message(provider, &registry.names().collect::<Vec<_>>()))
+}
+
+/// ステップ未定義時のエラーメッセージ
+pub(super) fn no_steps_message(workflow_name: &str) -> String {
+    format!("ワークフロー '{}' にステップが定義されていません", workflow_name)
+}
+
+/// ステップ名重複時のエラーメッセージ
+pub(super) fn duplicate_step_message(workflow_name: &str, step_name: &str) -> String {
+    format!(
+        "ワークフロー '{}' に重複するステップ名があります: '{}'",
+        workflow_name, step_name
+    )
+}
+
+/// 祖先（直接・間接の依存先）のステップ名を取得
+pub(super) fn ancestor_names<'a>(dependencies: &[Vec<usize>], index: usize, all: &[&'a str]) -> Vec<&'a str> {
+    graph::ancestors(dependencies, index)
+        .into_iter()
+        .map(|ancestor| all[ancestor])
+        .collect()
+}
+
+/// テンプレートのステップ参照のバリデーション
+///
+/// # 引数
+///
+/// - `step_name`: 参照元のステップ名
+/// - `referenced`: 参照先のステップ名
+/// - `ancestors`: 参照元より先に完了することが保証されたステップ名（依存先の祖先）
+/// - `all`: ワークフロー内のすべてのステップ名
+pub(super) fn check_step_reference(
+    step_name: &str,
+    referenced: &str,
+    ancestors: &[&str],
+    all: &[&str],
+) -> Result<(), String> {
+    if ancestors.contains(&referenced) {
+        Ok(())
+    } else if all.contains(&referenced) {
+        Err(format!(
+            "ステップ '{}' は先行していないステップ '{}' の出力を参照しています（depends_on を確認してください）",
+            step_name, referenced
+        ))
+    } else {
+        Err(format!(
+            "ステップ '{}' は存在しないステップ '{}' を参照しています",
+            step_name, referenced
+        ))
+    }
+}
+
 /// ドメインモデルから DTO への変換（書き込み方向）
 ///
 /// バリデーション済みのドメインモデルから DTO を生成するため、
@@ -263,6 +600,15 @@ impl From<Workflow> for WorkflowDto {
                 version: workflow.version,
             },
             steps,
+            loops: workflow.loops.into_iter().map(Into::into).collect(),
+            providers: workflow
+                .providers
+                .into_iter()
+                .map(|definition| (definition.name().to_string(), definition.into()))
+                .collect(),
+            models: workflow.models.into(),
+            prices: workflow.prices.into(),
+            budget: (!workflow.budget.is_unlimited()).then(|| workflow.budget.into()),
         }
     }
 }
@@ -280,6 +626,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         }
     }
 
@@ -291,6 +638,11 @@ mod tests {
                 version: Some("1.0.0".to_string()),
             },
             steps,
+            loops: Vec::new(),
+            providers: Default::default(),
+            models: Default::default(),
+            prices: Default::default(),
+            budget: None,
         }
     }
 
@@ -325,6 +677,11 @@ mod tests {
                 version: None,
             },
             steps: vec![create_valid_step_dto("step1")],
+            loops: Vec::new(),
+            providers: Default::default(),
+            models: Default::default(),
+            prices: Default::default(),
+            budget: None,
         };
 
         let result = Workflow::try_from(dto);
@@ -395,6 +752,7 @@ mod tests {
             model_tier: "heavy".to_string(),
             timeout: None,
             retry_count: None,
+            ..Default::default()
         });
 
         let result = Workflow::try_from(dto);
@@ -427,6 +785,73 @@ mod tests {
         }
     }
 
+    fn create_step_dto_with_prompt(name: &str, system_prompt: &str) -> WorkflowStepDto {
+        WorkflowStepDto {
+            system_prompt: system_prompt.to_string(),
+            ..create_valid_step_dto(name)
+        }
+    }
+
+    #[test]
+    fn test_validation_template_references_preceding_step() {
+        // 正常系: 先行ステップの出力を参照
+        let mut review = create_step_dto_with_prompt("review", "Plan: {{steps.plan.output}}");
+        review.input = Some("{{input}} / {{steps.implement.output}}".to_string());
+        let dto = create_valid_workflow_dto(
+            "workflow",
+            vec![
+                create_valid_step_dto("plan"),
+                create_valid_step_dto("implement"),
+                review,
+            ],
+        );
+
+        let workflow = Workflow::try_from(dto).unwrap();
+        let references: Vec<&str> = workflow.steps()[2].step_references().collect();
+        assert_eq!(references, vec!["plan", "implement"]);
+    }
+
+    #[test]
+    fn test_validation_template_references_unknown_step() {
+        // 異常系: 存在しないステップへの参照
+        let dto = create_valid_workflow_dto(
+            "workflow",
+            vec![
+                create_valid_step_dto("plan"),
+                create_step_dto_with_prompt("review", "{{steps.design.output}}"),
+            ],
+        );
+
+        match Workflow::try_from(dto) {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("存在しないステップ"));
+                assert!(msg.contains("design"));
+            }
+            other => panic!("Expected Validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validation_template_references_later_step() {
+        // 異常系: 後続のステップ・自身への参照
+        for prompt in ["{{steps.review.output}}", "{{steps.plan.output}}"] {
+            let dto = create_valid_workflow_dto(
+                "workflow",
+                vec![
+                    create_step_dto_with_prompt("plan", prompt),
+                    create_valid_step_dto("review"),
+                ],
+            );
+
+            match Workflow::try_from(dto) {
+                Err(ConfigError::Validation(msg)) => {
+                    assert!(msg.contains("先行していないステップ"), "{}", msg);
+                }
+                other => panic!("Expected Validation error, got {:?}", other),
+            }
+        }
+    }
+
     #[test]
     fn test_validation_duplicate_among_many_steps() {
         // 異常系: 複数のステップの中に重複がある
@@ -526,6 +951,7 @@ mod tests {
                     model_tier: "heavy".to_string(),
                     timeout: Some(300),
                     retry_count: Some(3),
+                    ..Default::default()
                 },
                 WorkflowStepDto {
                     name: "implement".to_string(),
@@ -534,6 +960,7 @@ mod tests {
                     model_tier: "medium".to_string(),
                     timeout: Some(600),
                     retry_count: Some(5),
+                    ..Default::default()
                 },
                 WorkflowStepDto {
                     name: "review".to_string(),
@@ -542,8 +969,14 @@ mod tests {
                     model_tier: "light".to_string(),
                     timeout: None,
                     retry_count: None,
+                    ..Default::default()
                 },
             ],
+            loops: Vec::new(),
+            providers: Default::default(),
+            models: Default::default(),
+            prices: Default::default(),
+            budget: None,
         };
 
         let result = Workflow::try_from(dto);
@@ -768,4 +1201,352 @@ model_tier = "light"
             _ => panic!("Expected FileRead error"),
         }
     }
+
+    #[test]
+    fn test_loops_from_toml_and_back() {
+        // 正常系: ループ定義の読み込みと書き出し
+        let toml = r#"
+[workflow]
+name = "implement"
+
+[[steps]]
+name = "implement"
+system_prompt = "Implement"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "openai"
+model_tier = "medium"
+
+[[loops]]
+name = "review-fix"
+from = "implement"
+to = "review"
+max_iterations = 3
+until = { step = "review", contains = "LGTM" }
+"#;
+
+        let workflow = Workflow::from_toml(toml).unwrap();
+        assert_eq!(workflow.loops().len(), 1);
+        assert_eq!(workflow.loop_ending_at(1).map(|l| l.name()), Some("review-fix"));
+        assert!(workflow.loop_ending_at(0).is_none());
+        assert_eq!(workflow.loop_containing(0).map(|l| l.max_iterations()), Some(3));
+
+        let restored = Workflow::from_toml(&workflow.to_string().unwrap()).unwrap();
+        assert_eq!(restored.loops()[0].until().to_string(), "steps.review.output contains \"LGTM\"");
+    }
+
+    fn dag_toml(summarize_depends_on: &str) -> String {
+        format!(
+            r#"
+[workflow]
+name = "parallel-review"
+
+[[steps]]
+name = "implement"
+system_prompt = "Implement"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "security"
+system_prompt = "Security review"
+provider = "anthropic"
+model_tier = "medium"
+depends_on = ["implement"]
+
+[[steps]]
+name = "style"
+system_prompt = "Style review"
+provider = "openai"
+model_tier = "light"
+depends_on = ["implement"]
+
+[[steps]]
+name = "summarize"
+system_prompt = "Summarize {{{{steps.security.output}}}} {{{{steps.style.output}}}}"
+provider = "anthropic"
+model_tier = "light"
+depends_on = {}
+"#,
+            summarize_depends_on
+        )
+    }
+
+    #[test]
+    fn test_depends_on_resolves_dependencies() {
+        // 正常系: depends_on の解決（省略時は直前のステップ）
+        let workflow = Workflow::from_toml(&dag_toml(r#"["security", "style"]"#)).unwrap();
+
+        assert!(workflow.dependencies(0).is_empty());
+        assert_eq!(workflow.dependencies(1), &[0]);
+        assert_eq!(workflow.dependencies(2), &[0]);
+        assert_eq!(workflow.dependencies(3), &[1, 2]);
+        assert_eq!(
+            workflow.steps()[3].depends_on(),
+            Some(&["security".to_string(), "style".to_string()][..])
+        );
+
+        let restored = Workflow::from_toml(&workflow.to_string().unwrap()).unwrap();
+        assert_eq!(restored.dependencies(3), &[1, 2]);
+        assert!(restored.steps()[0].depends_on().is_none());
+    }
+
+    #[test]
+    fn test_depends_on_requires_dependency_for_references() {
+        // 異常系: 依存していない並列ステップの出力は参照できない
+        let result = Workflow::from_toml(&dag_toml(r#"["security"]"#));
+
+        match result {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("先行していないステップ 'style'"), "{}", msg);
+            }
+            _ => panic!("Expected Validation error"),
+        }
+    }
+
+    #[test]
+    fn test_depends_on_cycle_is_rejected() {
+        // 異常系: 循環依存
+        let toml = r#"
+[workflow]
+name = "cycle"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+depends_on = ["review"]
+
+[[steps]]
+name = "implement"
+system_prompt = "Implement"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "review"
+system_prompt = "Review"
+provider = "anthropic"
+model_tier = "heavy"
+"#;
+
+        match Workflow::from_toml(toml) {
+            Err(ConfigError::Validation(msg)) => {
+                assert_eq!(msg, "ステップの依存関係が循環しています: plan -> review -> implement -> plan");
+            }
+            _ => panic!("Expected Validation error"),
+        }
+    }
+
+    #[test]
+    fn test_command_providers() {
+        let toml = r#"
+[workflow]
+name = "command"
+
+[providers.Local-Agent]
+command = "local-agent"
+args = ["--model", "{model}"]
+prompt_input = "stdin"
+models = { light = "small" }
+
+[[steps]]
+name = "draft"
+system_prompt = "Draft"
+provider = "local-agent"
+model_tier = "light"
+"#;
+
+        let workflow = Workflow::from_toml(toml).unwrap();
+        assert_eq!(workflow.providers().len(), 1);
+        assert_eq!(workflow.providers()[0].name(), "local-agent");
+        assert_eq!(workflow.steps()[0].provider().as_str(), "local-agent");
+
+        let restored = Workflow::from_toml(&workflow.to_string().unwrap()).unwrap();
+        let ProviderDefinition::Command(spec) = &restored.providers()[0] else {
+            panic!("Expected Command");
+        };
+        assert_eq!(spec.command(), "local-agent");
+        assert_eq!(spec.model(&crate::config::step::ModelTier::Light), "small");
+
+        // 異常系: 組み込みのプロバイダーと同じ名前は定義できない
+        let result = Workflow::from_toml(&toml.replace("[providers.Local-Agent]", "[providers.openai]"));
+        match result {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("プロバイダー 'openai' は登録済みのプロバイダーと重複しています"), "{}", msg);
+            }
+            _ => panic!("Expected Validation error"),
+        }
+    }
+
+    #[test]
+    fn test_openai_compatible_provider_requires_step_model() {
+        let toml = r#"
+[workflow]
+name = "local"
+
+[providers.local]
+type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+models = { light = "llama3.2:3b" }
+
+[[steps]]
+name = "commit-message"
+system_prompt = "Draft a commit message"
+provider = "local"
+model_tier = "light"
+"#;
+
+        let workflow = Workflow::from_toml(toml).unwrap();
+        assert!(matches!(&workflow.providers()[0], ProviderDefinition::OpenAiCompatible(spec)
+            if spec.base_url() == "http://localhost:11434/v1"));
+        let restored = Workflow::from_toml(&workflow.to_string().unwrap()).unwrap();
+        assert_eq!(restored.providers()[0].name(), "local");
+
+        // 異常系: models にないティア
+        match Workflow::from_toml(&toml.replace(r#"model_tier = "light""#, r#"model_tier = "heavy""#)) {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("ステップ 'commit-message' のモデルティア 'heavy'"), "{}", msg);
+            }
+            _ => panic!("Expected Validation error"),
+        }
+
+        // ステップの model や [models] で指定すれば models にないティアも使える
+        let heavy = toml.replace(r#"model_tier = "light""#, r#"model_tier = "heavy""#);
+        let pinned = heavy.replace(r#"model_tier = "heavy""#, "model_tier = \"heavy\"\nmodel = \"qwen2.5-coder:32b\"");
+        assert!(Workflow::from_toml(&pinned).is_ok());
+        let table = heavy + "\n[models.local]\nheavy = \"qwen2.5-coder:32b\"\n";
+        assert!(Workflow::from_toml(&table).is_ok());
+    }
+
+    #[test]
+    fn test_models_table_and_step_model() {
+        let toml = r#"
+[workflow]
+name = "models"
+
+[models.Anthropic]
+heavy = "claude-opus-4-1"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+model = "claude-opus-4-1-20250805"
+"#;
+
+        let workflow = Workflow::from_toml(toml).unwrap();
+        assert_eq!(workflow.models().get("anthropic", &crate::config::step::ModelTier::Heavy), Some("claude-opus-4-1"));
+        assert_eq!(workflow.steps()[0].model(), Some("claude-opus-4-1-20250805"));
+
+        // 書き出して再度読み込める
+        let restored = Workflow::from_toml(&workflow.to_string().unwrap()).unwrap();
+        assert_eq!(restored.models(), workflow.models());
+        assert_eq!(restored.steps()[0].model(), Some("claude-opus-4-1-20250805"));
+
+        // 異常系: 登録されていないプロバイダー、空のモデル名
+        let cases = [
+            (toml.replace("[models.Anthropic]", "[models.gemini]"), "[models.gemini] のプロバイダーが登録されていません"),
+            (toml.replace(r#"model = "claude-opus-4-1-20250805""#, r#"model = """#), "ステップ 'plan' の model が空です"),
+        ];
+        for (toml, expected) in cases {
+            match Workflow::from_toml(&toml) {
+                Err(ConfigError::Validation(msg)) => assert!(msg.contains(expected), "{}", msg),
+                _ => panic!("Expected Validation error: {}", expected),
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_fallback() {
+        let toml = r#"
+[workflow]
+name = "fallback"
+
+[providers.local]
+type = "openai-compatible"
+base_url = "http://localhost:11434/v1"
+models = { light = "llama3.2:3b" }
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+fallback = [{ provider = "openai", model_tier = "medium" }, { provider = "local", model_tier = "light" }]
+"#;
+
+        let workflow = Workflow::from_toml(toml).unwrap();
+        let fallback = workflow.steps()[0].fallback();
+        assert_eq!(fallback.len(), 2);
+        assert_eq!(fallback[1].provider().as_str(), "local");
+
+        // 書き出して再度読み込める
+        let restored = Workflow::from_toml(&workflow.to_string().unwrap()).unwrap();
+        assert_eq!(restored.steps()[0].fallback().len(), 2);
+
+        // 異常系: 定義したプロバイダーの models にないティア（省略時はステップのティア）
+        let heavy = toml.replace(r#"{ provider = "local", model_tier = "light" }"#, r#"{ provider = "local" }"#);
+        match Workflow::from_toml(&heavy) {
+            Err(ConfigError::Validation(msg)) => {
+                assert!(msg.contains("ステップ 'plan' の fallback[1] のモデルティア 'heavy'"), "{}", msg);
+            }
+            _ => panic!("Expected Validation error"),
+        }
+        assert!(Workflow::from_toml(&(heavy + "\n[models.local]\nheavy = \"qwen2.5-coder:32b\"\n")).is_ok());
+    }
+
+    #[test]
+    fn test_fingerprints() {
+        let toml = r#"
+[workflow]
+name = "fingerprint"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "implement"
+system_prompt = "Implement"
+provider = "anthropic"
+model_tier = "medium"
+"#;
+        let workflow = Workflow::from_toml(toml).unwrap();
+
+        // 書式やコメントだけの変更では変わらない
+        let reformatted = Workflow::from_toml(&format!("# comment\n{}", toml.replace("\n\n", "\n"))).unwrap();
+        assert_eq!(reformatted.fingerprint(), workflow.fingerprint());
+
+        // 他のステップの変更ではステップのハッシュ値は変わらない
+        let changed = Workflow::from_toml(&toml.replace("Implement", "Implement carefully")).unwrap();
+        assert_ne!(changed.fingerprint(), workflow.fingerprint());
+        assert_eq!(changed.step_fingerprint(0), workflow.step_fingerprint(0));
+        assert_ne!(changed.step_fingerprint(1), workflow.step_fingerprint(1));
+
+        // [models] や [providers] の変更では、そのプロバイダーを使うステップの実行先のハッシュ値が変わる
+        let models = Workflow::from_toml(&format!("{}\n[models.anthropic]\nheavy = \"claude-opus-4-1\"\n", toml)).unwrap();
+        assert_eq!(models.step_fingerprint(0), workflow.step_fingerprint(0));
+        assert_ne!(models.target_fingerprint(0), workflow.target_fingerprint(0));
+        assert_eq!(models.target_fingerprint(1), workflow.target_fingerprint(1));
+
+        let local = |command: &str| {
+            let toml = format!(
+                "{}\n[providers.local]\ncommand = \"{}\"\n",
+                toml.replacen("\"anthropic\"", "\"local\"", 1),
+                command
+            );
+            Workflow::from_toml(&toml).unwrap()
+        };
+        assert_ne!(local("agent").target_fingerprint(0), local("other-agent").target_fingerprint(0));
+        assert_eq!(local("agent").target_fingerprint(1), local("other-agent").target_fingerprint(1));
+    }
 }
@@ -2,10 +2,13 @@
 //!
 //! # 責務
 //!
-//! - Workflowオブジェクトを受け取り、各Stepを順序立てて実行
+//! - Workflowオブジェクトを受け取り、各Stepを依存関係に従って実行（独立したステップは並列実行）
 //! - ステップ間のデータ受け渡しによる連鎖実行
 //! - プロバイダー（Anthropic/OpenAI）の抽象的な利用
 //! - タイムアウトとリトライの制御
+//! - 予算（トークン数・コスト・経過時間の上限）の制御
+//! - チェックポイントの保存と、失敗した実行の再開
+//! - 実行履歴（プロンプト・出力・実行結果）の保存
 //! - テレメトリー収集のためのデータ記録
 //!
 //! # モジュール構成
@@ -13,6 +16,12 @@
 //! - [`executor`][]: ワークフロー実行エンジン本体
 //! - [`context`][]: ステップ実行コンテキスト（ステップ間データ受け渡し）
 //! - [`result`][]: 実行結果型（ステップ&ワークフロー結果）
+//! - [`progress`][]: ステップ実行中の進捗の通知先
+//! - [`checkpoint`][]: 完了したステップの出力の保存と読み込み（再開に使う）
+//! - [`run_store`][]: 実行ごとのディレクトリへの実行履歴の保存と読み込み
+//! - `budget`: 予算の見積もりと判定（非公開）
+//! - `scheduler`: 依存関係とループに基づく実行順序の制御（非公開）
+//! - `structured`: 構造化出力（`output_schema`）の取り出しと修正依頼（非公開）
 //!
 //! # 使用例
 //!
@@ -49,8 +58,17 @@
 pub mod result;
 pub mod context;
 pub mod executor;
+pub mod progress;
+pub mod checkpoint;
+pub mod run_store;
+mod budget;
+mod scheduler;
+mod structured;
 
 // 公開APIの再エクスポート
-pub use result::{ExecutionError, ExecutionStatus, StepResult, StepStatus, WorkflowResult};
+pub use result::{ExecutionError, ExecutionStatus, LoopResult, StepResult, StepStatus, WorkflowResult};
 pub use context::{ExecutionContext, StepOutput};
 pub use executor::WorkflowExecutor;
+pub use progress::ProgressSink;
+pub use checkpoint::Checkpoint;
+pub use run_store::RunStore;
@@ -0,0 +1,154 @@
+//! 予算（`[budget]` / `budget`）の判定
+//!
+//! # 責務
+//!
+//! - ステップの開始前に、プロンプトの大きさからトークン数を見積もる
+//! - 使用量（開始前は使用量と見積もりの合計）が予算の上限を超えるかを判定する
+//!
+//! 上限を超えた場合の実行の停止と結果の記録は [`WorkflowExecutor`](super::executor::WorkflowExecutor) が担当します。
+
+use std::time::Duration;
+
+use crate::config::budget::Budget;
+use crate::engine::result::ExecutionError;
+
+/// 1トークンあたりのバイト数の目安
+const BYTES_PER_TOKEN: u64 = 4;
+
+/// ワークフロー全体の予算の範囲名
+pub(super) const WORKFLOW_SCOPE: &str = "[budget]";
+
+/// ステップの予算の範囲名
+pub(super) fn step_scope(step_name: &str) -> String {
+    format!("ステップ '{}' の budget", step_name)
+}
+
+/// テキストのトークン数の見積もり
+///
+/// トークナイザーはモデルごとに異なるため、UTF-8 のバイト数を4で割った概算です。
+pub(super) fn estimate_tokens(text: &str) -> u64 {
+    (text.len() as u64).div_ceil(BYTES_PER_TOKEN)
+}
+
+/// 予算の判定に使う使用量
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Usage {
+    /// トークン数
+    pub(super) tokens: u64,
+    /// コスト（米ドル）
+    pub(super) cost_usd: f64,
+    /// 経過時間
+    pub(super) elapsed: Duration,
+}
+
+/// 使用量と次のステップの見積もりの合計が予算の上限を超えるかを判定
+///
+/// 経過時間は上限に達した時点で超過とします。
+///
+/// # 引数
+///
+/// - `budget`: 判定する予算
+/// - `scope`: 予算の範囲名（エラーメッセージに使う）
+/// - `used`: 使用済みの量
+/// - `estimate`: 次のステップの見積もり（ステップの完了後の判定では既定値）
+///
+/// # エラー
+///
+/// 上限を超える場合は [`ExecutionError::BudgetExceeded`]
+pub(super) fn check(budget: &Budget, scope: &str, used: Usage, estimate: Usage) -> Result<(), ExecutionError> {
+    let exceeded = |limit, detail| ExecutionError::BudgetExceeded {
+        scope: scope.to_string(),
+        limit,
+        detail,
+    };
+
+    if let Some(max) = budget.max_tokens_total()
+        && used.tokens.saturating_add(estimate.tokens) > max
+    {
+        let detail = if estimate.tokens > 0 {
+            format!("使用済み {} + 見積もり {} トークン（上限 {}）", used.tokens, estimate.tokens, max)
+        } else {
+            format!("{} トークン（上限 {}）", used.tokens, max)
+        };
+        return Err(exceeded("max_tokens_total", detail));
+    }
+    if let Some(max) = budget.max_cost_usd()
+        && used.cost_usd + estimate.cost_usd > max
+    {
+        let detail = if estimate.cost_usd > 0.0 {
+            format!("使用済み ${:.4} + 見積もり ${:.4}（上限 ${:.4}）", used.cost_usd, estimate.cost_usd, max)
+        } else {
+            format!("${:.4}（上限 ${:.4}）", used.cost_usd, max)
+        };
+        return Err(exceeded("max_cost_usd", detail));
+    }
+    if let Some(max) = budget.max_wall_time()
+        && used.elapsed >= max
+    {
+        return Err(wall_time_exceeded(scope, max));
+    }
+    Ok(())
+}
+
+/// 経過時間の上限に達したことを表すエラー
+pub(super) fn wall_time_exceeded(scope: &str, max: Duration) -> ExecutionError {
+    ExecutionError::BudgetExceeded {
+        scope: scope.to_string(),
+        limit: "max_wall_time",
+        detail: format!("{}秒が経過しました", max.as_secs()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::workflow::Workflow;
+
+    fn budget(toml: &str) -> Budget {
+        let source = format!(
+            "[workflow]\nname = \"budget\"\n\n[budget]\n{}\n\n[[steps]]\nname = \"plan\"\nsystem_prompt = \"Plan\"\n\
+             provider = \"anthropic\"\nmodel_tier = \"heavy\"\n",
+            toml
+        );
+        Workflow::from_toml(&source).unwrap().budget().clone()
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_check() {
+        let budget = budget("max_tokens_total = 1000\nmax_cost_usd = 0.5\nmax_wall_time = 60");
+        let used = Usage { tokens: 900, cost_usd: 0.25, elapsed: Duration::from_secs(10) };
+
+        assert!(check(&budget, WORKFLOW_SCOPE, used, Usage::default()).is_ok());
+        assert!(check(&budget, WORKFLOW_SCOPE, used, Usage { tokens: 100, ..Usage::default() }).is_ok());
+
+        // 見積もりを含めると上限を超える
+        let err = check(&budget, WORKFLOW_SCOPE, used, Usage { tokens: 101, ..Usage::default() }).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "予算超過: [budget] の max_tokens_total を超えました: 使用済み 900 + 見積もり 101 トークン（上限 1000）"
+        );
+
+        let err = check(&budget, &step_scope("plan"), Usage { cost_usd: 0.75, ..used }, Usage::default()).unwrap_err();
+        assert!(
+            matches!(&err, ExecutionError::BudgetExceeded { scope, limit: "max_cost_usd", .. } if scope == "ステップ 'plan' の budget"),
+            "{:?}",
+            err
+        );
+
+        // 経過時間は上限に達した時点で超過
+        let err = check(&budget, WORKFLOW_SCOPE, Usage { elapsed: Duration::from_secs(60), ..used }, Usage::default())
+            .unwrap_err();
+        assert!(matches!(err, ExecutionError::BudgetExceeded { limit: "max_wall_time", .. }), "{:?}", err);
+
+        // 無制限の予算は判定しない
+        let unlimited = Usage { tokens: u64::MAX, cost_usd: f64::MAX, elapsed: Duration::MAX };
+        assert!(check(&Budget::default(), WORKFLOW_SCOPE, unlimited, unlimited).is_ok());
+    }
+}
@@ -0,0 +1,394 @@
+//! 実行の途中経過（チェックポイント）
+//!
+//! # 責務
+//!
+//! - 完了したステップの出力を、実行ごとのディレクトリ（`<runs_dir>/<run_id>/checkpoint.json`）に保存する
+//! - 保存したチェックポイントを読み込み、ワークフロー定義と互換性があるかを判定する
+//!
+//! チェックポイントからの再開（完了したステップの出力の再利用）は
+//! [`WorkflowExecutor::resume_from`](super::executor::WorkflowExecutor::resume_from) が担当します。
+//!
+//! # 保存先
+//!
+//! ```text
+//! .adw/runs/
+//! ├── 20251016-093012-3f2a/
+//! │   └── checkpoint.json
+//! └── 20251016-101544-91c0/
+//!     └── checkpoint.json
+//! ```
+//!
+//! 同じディレクトリに保存する実行履歴（プロンプト・出力・実行結果）は [`run_store`](super::run_store) を参照してください。
+//!
+//! # 互換性
+//!
+//! ワークフロー名が同じで、完了したステップの定義（[`Workflow::step_fingerprint`]）が
+//! 変わっていなければ再開できます。未完了のステップの定義は変更してもかまいません。
+//!
+//! `[models]` や `[providers]` の変更で完了したステップの実行先（[`Workflow::target_fingerprint`]）が
+//! 変わった場合は、そのステップと、それ以降に完了したステップを実行し直します（[`Checkpoint::reusable`]）。
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::workflow::Workflow;
+use crate::engine::context::StepOutput;
+use crate::engine::result::{StepResult, StepStatus};
+use crate::error::CheckpointError;
+
+/// チェックポイントのファイル名
+pub const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// チェックポイントの形式のバージョン
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// 実行の途中経過
+///
+/// ステップが完了するたびに、実行エンジンが [`save`](Self::save) で保存します。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// 形式のバージョン（[`CHECKPOINT_VERSION`]）
+    pub version: u32,
+
+    /// 実行ID
+    pub run_id: String,
+
+    /// 再開元の実行ID（`adw resume` で再開した実行のみ）
+    pub resumed_from: Option<String>,
+
+    /// ワークフロー名
+    pub workflow_name: String,
+
+    /// ワークフロー定義ファイルのパス（`adw resume` が定義を読み込み直すために使う）
+    pub workflow_path: Option<PathBuf>,
+
+    /// ワークフロー定義全体のハッシュ値（[`Workflow::fingerprint`]）
+    pub workflow_fingerprint: String,
+
+    /// 初期入力
+    pub initial_input: Option<String>,
+
+    /// 完了したステップ（完了順）
+    pub steps: Vec<CompletedStep>,
+}
+
+/// 完了したステップの記録
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompletedStep {
+    /// ステップインデックス（0始まり）
+    pub index: usize,
+
+    /// ループ内の繰り返し回数（0始まり、ループ外のステップは0）
+    pub iteration: u32,
+
+    /// 完了したときのステップの定義のハッシュ値（[`Workflow::step_fingerprint`]）
+    pub fingerprint: String,
+
+    /// 完了したときのステップの実行先のハッシュ値（[`Workflow::target_fingerprint`]）
+    #[serde(default)]
+    pub target_fingerprint: String,
+
+    /// ステップの出力
+    pub output: StepOutput,
+
+    /// 出力を生成したプロバイダー名
+    pub provider: Option<String>,
+
+    /// 使用したモデル名
+    pub model: Option<String>,
+
+    /// コスト（米ドル）
+    pub cost_usd: Option<f64>,
+}
+
+impl Checkpoint {
+    /// 完了したステップのない新しいチェックポイントを生成
+    ///
+    /// # 引数
+    ///
+    /// - `run_id`: 実行ID
+    /// - `workflow`: 実行するワークフロー定義
+    /// - `initial_input`: 初期入力
+    pub fn new(run_id: impl Into<String>, workflow: &Workflow, initial_input: Option<String>) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            run_id: run_id.into(),
+            resumed_from: None,
+            workflow_name: workflow.name().to_string(),
+            workflow_path: None,
+            workflow_fingerprint: workflow.fingerprint(),
+            initial_input,
+            steps: Vec::new(),
+        }
+    }
+
+    /// 実行のチェックポイントのパスを取得
+    ///
+    /// # 例
+    ///
+    /// ```rust
+    /// use melted_adw::engine::checkpoint::Checkpoint;
+    /// use std::path::Path;
+    ///
+    /// let path = Checkpoint::path(Path::new(".adw/runs"), "20251016-093012-3f2a");
+    /// assert_eq!(path, Path::new(".adw/runs/20251016-093012-3f2a/checkpoint.json"));
+    /// ```
+    pub fn path(runs_dir: &Path, run_id: &str) -> PathBuf {
+        runs_dir.join(run_id).join(CHECKPOINT_FILE)
+    }
+
+    /// 実行のチェックポイントを読み込む
+    ///
+    /// # エラー
+    ///
+    /// - [`CheckpointError::NotFound`][]: チェックポイントが存在しない（実行IDがパスとして不正な場合を含む）
+    /// - [`CheckpointError::Incompatible`][]: 対応していない形式のバージョン
+    /// - [`CheckpointError::Io`] / [`CheckpointError::Json`][]: 読み込みまたは JSON の変換に失敗
+    pub fn load(runs_dir: &Path, run_id: &str) -> Result<Self, CheckpointError> {
+        let is_plain_name = !run_id.is_empty() && run_id != ".." && !run_id.contains(std::path::is_separator);
+        let path = Self::path(runs_dir, run_id);
+        if !is_plain_name || !path.is_file() {
+            return Err(CheckpointError::NotFound(run_id.to_string()));
+        }
+
+        let checkpoint: Self = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::Incompatible(format!(
+                "チェックポイントの形式（バージョン {}）に対応していません",
+                checkpoint.version
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    /// チェックポイントを `<runs_dir>/<run_id>/checkpoint.json` に保存
+    ///
+    /// 書き込みの途中で中断しても前回の内容が壊れないよう、一時ファイルに書き込んでから置き換えます。
+    pub fn save(&self, runs_dir: &Path) -> Result<(), CheckpointError> {
+        let path = Self::path(runs_dir, &self.run_id);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let temporary = path.with_extension("json.tmp");
+        std::fs::write(&temporary, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(temporary, path)?;
+        Ok(())
+    }
+
+    /// 完了したステップを記録
+    ///
+    /// 出力のない結果（失敗・スキップしたステップ）は記録しません。
+    pub fn record(&mut self, workflow: &Workflow, result: &StepResult) {
+        let Some(content) = &result.output else {
+            return;
+        };
+
+        self.steps.push(CompletedStep {
+            index: result.index,
+            iteration: result.iteration,
+            fingerprint: workflow.step_fingerprint(result.index),
+            target_fingerprint: workflow.target_fingerprint(result.index),
+            output: StepOutput {
+                step_name: result.step_name.clone(),
+                content: content.clone(),
+                token_usage: result.token_usage,
+                execution_time: result.duration,
+                structured_output: result.structured_output.clone(),
+            },
+            provider: result.provider.clone(),
+            model: result.model.clone(),
+            cost_usd: result.cost_usd,
+        });
+    }
+
+    /// 指定したステップの繰り返しの完了記録を取得
+    pub fn completed(&self, index: usize, iteration: u32) -> Option<&CompletedStep> {
+        self.steps
+            .iter()
+            .find(|step| step.index == index && step.iteration == iteration)
+    }
+
+    /// ワークフロー定義からこのチェックポイントを再開できるかを判定
+    ///
+    /// # エラー
+    ///
+    /// ワークフロー名が異なる、または完了したステップの定義が変更・削除された場合は
+    /// [`CheckpointError::Incompatible`]
+    pub fn check_compatible(&self, workflow: &Workflow) -> Result<(), CheckpointError> {
+        if self.workflow_name != workflow.name() {
+            return Err(CheckpointError::Incompatible(format!(
+                "ワークフロー名が異なります（チェックポイント: '{}'、定義: '{}'）",
+                self.workflow_name,
+                workflow.name()
+            )));
+        }
+        if self.workflow_fingerprint == workflow.fingerprint() {
+            return Ok(());
+        }
+
+        for step in &self.steps {
+            let unchanged = workflow.steps().get(step.index).is_some_and(|definition| {
+                definition.name() == step.output.step_name && workflow.step_fingerprint(step.index) == step.fingerprint
+            });
+            if !unchanged {
+                return Err(CheckpointError::Incompatible(format!(
+                    "完了したステップ '{}' の定義が変更されています",
+                    step.output.step_name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// ワークフロー定義で再利用できる完了記録だけを残したチェックポイントを取得
+    ///
+    /// 実行先（[`Workflow::target_fingerprint`]）が変わったステップと、それ以降に完了したステップは
+    /// 変わったステップの出力を使った可能性があるため除きます。
+    /// 互換性（[`check_compatible`](Self::check_compatible)）は判定済みであることを前提とします。
+    pub fn reusable(&self, workflow: &Workflow) -> Checkpoint {
+        let mut checkpoint = self.clone();
+        let changed = self
+            .steps
+            .iter()
+            .position(|step| workflow.target_fingerprint(step.index) != step.target_fingerprint);
+        if let Some(position) = changed {
+            tracing::info!(
+                step = self.steps[position].output.step_name.as_str(),
+                "ステップの実行先が変更されているため、このステップから実行し直します"
+            );
+            checkpoint.steps.truncate(position);
+        }
+        checkpoint
+    }
+}
+
+impl CompletedStep {
+    /// 再利用したステップの結果を生成
+    ///
+    /// リトライ回数等の実行の詳細は保存していないため、[`StepStatus::Success`] として記録します。
+    pub fn to_step_result(&self) -> StepResult {
+        StepResult {
+            step_name: self.output.step_name.clone(),
+            index: self.index,
+            status: StepStatus::Success,
+            output: Some(self.output.content.clone()),
+            token_usage: self.output.token_usage,
+            duration: self.output.execution_time,
+            retry_count: 0,
+            error: None,
+            skip_reason: None,
+            iteration: self.iteration,
+            structured_output: self.output.structured_output.clone(),
+            repair_count: 0,
+            system_prompt_channel: None,
+            provider: self.provider.clone(),
+            model: self.model.clone(),
+            attempt_errors: Vec::new(),
+            cost_usd: self.cost_usd,
+            resumed: true,
+            cache_hit: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::TokenUsage;
+    use std::time::Duration;
+
+    const WORKFLOW: &str = r#"
+[workflow]
+name = "checkpoint"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+
+[[steps]]
+name = "implement"
+system_prompt = "Implement"
+provider = "anthropic"
+model_tier = "medium"
+"#;
+
+    fn plan_result() -> StepResult {
+        StepResult {
+            output: Some("the plan".to_string()),
+            status: StepStatus::Success,
+            skip_reason: None,
+            token_usage: TokenUsage { input_tokens: 10, output_tokens: 20, ..Default::default() },
+            duration: Duration::from_millis(1500),
+            provider: Some("anthropic".to_string()),
+            cost_usd: Some(0.25),
+            ..StepResult::skipped("plan", 0, "")
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let workflow = Workflow::from_toml(WORKFLOW).unwrap();
+        let mut checkpoint = Checkpoint::new("20251016-093012-3f2a", &workflow, Some("requirements".to_string()));
+        checkpoint.record(&workflow, &plan_result());
+        checkpoint.record(&workflow, &StepResult::skipped("implement", 1, "failed"));
+        assert_eq!(checkpoint.steps.len(), 1);
+
+        let dir = std::env::temp_dir().join(format!("adw-checkpoint-test-{}", std::process::id()));
+        checkpoint.save(&dir).unwrap();
+        let loaded = Checkpoint::load(&dir, "20251016-093012-3f2a").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.initial_input.as_deref(), Some("requirements"));
+        let replayed = loaded.completed(0, 0).unwrap().to_step_result();
+        assert_eq!(replayed.output.as_deref(), Some("the plan"));
+        assert_eq!(replayed.token_usage.total(), 30);
+        assert_eq!(replayed.duration, Duration::from_millis(1500));
+        assert_eq!(replayed.cost_usd, Some(0.25));
+        assert!(replayed.resumed);
+        assert!(loaded.completed(1, 0).is_none());
+
+        assert!(matches!(Checkpoint::load(&dir, "missing"), Err(CheckpointError::NotFound(_))));
+        assert!(matches!(Checkpoint::load(&dir, "../etc"), Err(CheckpointError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_check_compatible() {
+        let workflow = Workflow::from_toml(WORKFLOW).unwrap();
+        let mut checkpoint = Checkpoint::new("run", &workflow, None);
+        checkpoint.record(&workflow, &plan_result());
+        assert!(checkpoint.check_compatible(&workflow).is_ok());
+
+        // 未完了のステップの変更は互換
+        let later_changed = Workflow::from_toml(&WORKFLOW.replace("Implement", "Implement carefully")).unwrap();
+        assert!(checkpoint.check_compatible(&later_changed).is_ok());
+
+        // 完了したステップの変更・ワークフロー名の変更は非互換
+        let cases = [
+            (WORKFLOW.replace("\"Plan\"", "\"Plan in detail\""), "完了したステップ 'plan' の定義が変更されています"),
+            (WORKFLOW.replace("name = \"plan\"", "name = \"design\""), "完了したステップ 'plan' の定義が変更されています"),
+            (WORKFLOW.replace("name = \"checkpoint\"", "name = \"other\""), "ワークフロー名が異なります"),
+        ];
+        for (toml, expected) in cases {
+            let changed = Workflow::from_toml(&toml).unwrap();
+            let err = checkpoint.check_compatible(&changed).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_reusable() {
+        let workflow = Workflow::from_toml(WORKFLOW).unwrap();
+        let mut checkpoint = Checkpoint::new("run", &workflow, None);
+        checkpoint.record(&workflow, &plan_result());
+        assert_eq!(checkpoint.reusable(&workflow).steps.len(), 1);
+
+        // 完了したステップのモデルが変わった場合は互換だが、そのステップは再利用しない
+        let models = Workflow::from_toml(&format!("{}\n[models.anthropic]\nheavy = \"claude-opus-4-1\"\n", WORKFLOW)).unwrap();
+        assert!(checkpoint.check_compatible(&models).is_ok());
+        assert!(checkpoint.reusable(&models).steps.is_empty());
+    }
+}
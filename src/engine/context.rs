@@ -27,7 +27,7 @@
 //! let output = StepOutput::new(
 //!     "step1".to_string(),
 //!     "Result from step 1".to_string(),
-//!     TokenUsage { input_tokens: 100, output_tokens: 50 },
+//!     TokenUsage { input_tokens: 100, output_tokens: 50, ..Default::default() },
 //!     Duration::from_secs(2),
 //! );
 //! ctx.record_step_result(output);
@@ -43,6 +43,7 @@
 //! ```
 
 use crate::provider::TokenUsage;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, SystemTime};
 
@@ -76,7 +77,7 @@ pub struct ExecutionContext {
     step_outputs: Vec<StepOutput>,
 
     // テレメトリー情報
-    total_tokens_used: u32,
+    total_tokens_used: u64,
     execution_times: Vec<Duration>,
     retry_counts: HashMap<String, u32>,
 }
@@ -154,14 +155,14 @@ impl ExecutionContext {
     /// let output = StepOutput::new(
     ///     "step1".to_string(),
     ///     "Output content".to_string(),
-    ///     TokenUsage { input_tokens: 10, output_tokens: 20 },
+    ///     TokenUsage { input_tokens: 10, output_tokens: 20, ..Default::default() },
     ///     Duration::from_secs(1),
     /// );
     /// ctx.record_step_result(output);
     /// ```
     pub fn record_step_result(&mut self, output: StepOutput) {
         // テレメトリー情報を更新
-        self.total_tokens_used += output.token_usage.total();
+        self.total_tokens_used = self.total_tokens_used.saturating_add(output.token_usage.total());
         self.execution_times.push(output.execution_time);
 
         // ステップ履歴を更新
@@ -195,7 +196,7 @@ impl ExecutionContext {
     /// let output = StepOutput::new(
     ///     "step1".to_string(),
     ///     "Output from step 1".to_string(),
-    ///     TokenUsage { input_tokens: 10, output_tokens: 20 },
+    ///     TokenUsage { input_tokens: 10, output_tokens: 20, ..Default::default() },
     ///     Duration::from_secs(1),
     /// );
     /// ctx.record_step_result(output);
@@ -211,6 +212,7 @@ impl ExecutionContext {
     /// 特定のステップの出力を取得
     ///
     /// ステップ名を指定して、そのステップの実行結果を取得します。
+    /// ループにより同じステップが複数回実行された場合は、最新の出力を返します。
     ///
     /// # 引数
     ///
@@ -233,7 +235,7 @@ impl ExecutionContext {
     /// let output = StepOutput::new(
     ///     "validation".to_string(),
     ///     "Valid".to_string(),
-    ///     TokenUsage { input_tokens: 5, output_tokens: 10 },
+    ///     TokenUsage { input_tokens: 5, output_tokens: 10, ..Default::default() },
     ///     Duration::from_millis(500),
     /// );
     /// ctx.record_step_result(output);
@@ -245,6 +247,7 @@ impl ExecutionContext {
     pub fn get_step_output(&self, step_name: &str) -> Option<&StepOutput> {
         self.step_outputs
             .iter()
+            .rev()
             .find(|output| output.step_name == step_name)
     }
 
@@ -322,7 +325,7 @@ impl ExecutionContext {
     /// let output1 = StepOutput::new(
     ///     "step1".to_string(),
     ///     "Output 1".to_string(),
-    ///     TokenUsage { input_tokens: 100, output_tokens: 50 },
+    ///     TokenUsage { input_tokens: 100, output_tokens: 50, ..Default::default() },
     ///     Duration::from_secs(1),
     /// );
     /// ctx.record_step_result(output1);
@@ -330,14 +333,14 @@ impl ExecutionContext {
     /// let output2 = StepOutput::new(
     ///     "step2".to_string(),
     ///     "Output 2".to_string(),
-    ///     TokenUsage { input_tokens: 200, output_tokens: 100 },
+    ///     TokenUsage { input_tokens: 200, output_tokens: 100, ..Default::default() },
     ///     Duration::from_secs(2),
     /// );
     /// ctx.record_step_result(output2);
     ///
     /// assert_eq!(ctx.total_tokens(), 450); // 100 + 50 + 200 + 100
     /// ```
-    pub fn total_tokens(&self) -> u32 {
+    pub fn total_tokens(&self) -> u64 {
         self.total_tokens_used
     }
 
@@ -361,7 +364,7 @@ impl ExecutionContext {
     /// let output1 = StepOutput::new(
     ///     "step1".to_string(),
     ///     "Output 1".to_string(),
-    ///     TokenUsage { input_tokens: 10, output_tokens: 10 },
+    ///     TokenUsage { input_tokens: 10, output_tokens: 10, ..Default::default() },
     ///     Duration::from_secs(1),
     /// );
     /// ctx.record_step_result(output1);
@@ -369,7 +372,7 @@ impl ExecutionContext {
     /// let output2 = StepOutput::new(
     ///     "step2".to_string(),
     ///     "Output 2".to_string(),
-    ///     TokenUsage { input_tokens: 10, output_tokens: 10 },
+    ///     TokenUsage { input_tokens: 10, output_tokens: 10, ..Default::default() },
     ///     Duration::from_secs(2),
     /// );
     /// ctx.record_step_result(output2);
@@ -392,12 +395,16 @@ impl ExecutionContext {
 /// - `content`: ステップが生成した出力内容（LLMのレスポンスなど）
 /// - `token_usage`: ステップで使用されたトークン数
 /// - `execution_time`: ステップの実行にかかった時間
-#[derive(Debug, Clone)]
+/// - `structured_output`: 出力から取り出した検証済みの JSON（`output_schema` を指定したステップのみ）
+///
+/// チェックポイント（[`Checkpoint`](crate::engine::checkpoint::Checkpoint)）に保存するため、シリアライズできます。
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepOutput {
     pub step_name: String,
     pub content: String,
     pub token_usage: TokenUsage,
     pub execution_time: Duration,
+    pub structured_output: Option<serde_json::Value>,
 }
 
 impl StepOutput {
@@ -412,7 +419,7 @@ impl StepOutput {
     ///
     /// # 戻り値
     ///
-    /// 初期化された StepOutput インスタンス
+    /// 初期化された StepOutput インスタンス（`structured_output` は `None`）
     ///
     /// # 例
     ///
@@ -427,6 +434,7 @@ impl StepOutput {
     ///     TokenUsage {
     ///         input_tokens: 50,
     ///         output_tokens: 20,
+    ///         ..Default::default()
     ///     },
     ///     Duration::from_millis(500),
     /// );
@@ -446,6 +454,7 @@ impl StepOutput {
             content,
             token_usage,
             execution_time,
+            structured_output: None,
         }
     }
 }
@@ -490,6 +499,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 100,
                 output_tokens: 50,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -519,6 +529,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 10,
                 output_tokens: 5,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -535,6 +546,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 20,
                 output_tokens: 10,
+                ..Default::default()
             },
             Duration::from_secs(2),
         );
@@ -556,6 +568,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 10,
                 output_tokens: 5,
+                ..Default::default()
             },
             Duration::from_millis(500),
         );
@@ -567,6 +580,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 20,
                 output_tokens: 10,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -583,6 +597,19 @@ mod tests {
 
         // 存在しないステップの出力を取得
         assert!(ctx.get_step_output("nonexistent").is_none());
+
+        // 同じステップが再実行された場合は最新の出力
+        ctx.record_step_result(StepOutput::new(
+            "validation".to_string(),
+            "Valid again".to_string(),
+            TokenUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                ..Default::default()
+            },
+            Duration::from_millis(500),
+        ));
+        assert_eq!(ctx.get_step_output("validation").unwrap().content, "Valid again");
     }
 
     /// increment_retry() と get_retry_count() のテスト
@@ -619,6 +646,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 100,
                 output_tokens: 50,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -631,6 +659,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 200,
                 output_tokens: 100,
+                ..Default::default()
             },
             Duration::from_secs(2),
         );
@@ -651,6 +680,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 10,
                 output_tokens: 10,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -663,6 +693,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 10,
                 output_tokens: 10,
+                ..Default::default()
             },
             Duration::from_millis(1500),
         );
@@ -679,6 +710,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 50,
                 output_tokens: 25,
+                ..Default::default()
             },
             Duration::from_millis(750),
         );
@@ -711,6 +743,7 @@ mod tests {
                 TokenUsage {
                     input_tokens,
                     output_tokens,
+                    ..Default::default()
                 },
                 Duration::from_millis(duration_ms),
             );
@@ -750,6 +783,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 100,
                 output_tokens: 50,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -763,6 +797,7 @@ mod tests {
             TokenUsage {
                 input_tokens: 50,
                 output_tokens: 25,
+                ..Default::default()
             },
             Duration::from_secs(1),
         );
@@ -0,0 +1,40 @@
+//! ステップ実行中の進捗の通知先
+//!
+//! # 責務
+//!
+//! プロバイダーのストリーミング実行（[`ProviderClient::execute_stream`](crate::provider::ProviderClient::execute_stream)）
+//! から受け取ったイベントを、ステップ名とともに通知するインターフェース [`ProgressSink`] を定義する。
+//!
+//! 通知先は [`WorkflowExecutor::with_progress_sink`](super::executor::WorkflowExecutor::with_progress_sink)
+//! で設定します。並列実行中のステップからも呼び出されるため、実装はスレッドセーフである必要があります。
+//!
+//! # 例
+//!
+//! ```rust
+//! use melted_adw::engine::ProgressSink;
+//! use melted_adw::provider::ProviderEvent;
+//!
+//! struct ToolLogger;
+//!
+//! impl ProgressSink for ToolLogger {
+//!     fn on_event(&self, step_name: &str, event: &ProviderEvent) {
+//!         if let ProviderEvent::ToolCall { name, .. } = event {
+//!             eprintln!("[{}] tool: {}", step_name, name);
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::provider::ProviderEvent;
+
+/// 進捗イベントの通知先
+pub trait ProgressSink: Send + Sync {
+    /// ステップの実行中にイベントを受け取る
+    ///
+    /// # 引数
+    ///
+    /// - `step_name`: イベントを発生させたステップ名
+    /// - `event`: プロバイダーから受け取ったイベント（出力スキーマの修正依頼を含め、呼び出しごとに
+    ///   [`ProviderEvent::Completed`] で終わる）
+    fn on_event(&self, step_name: &str, event: &ProviderEvent);
+}
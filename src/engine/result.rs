@@ -11,6 +11,7 @@
 //!
 //! - [`WorkflowResult`][]: ワークフロー全体の実行結果（成功/失敗、各ステップの結果、トークン使用量等）
 //! - [`StepResult`][]: 個別ステップの実行結果（出力、トークン使用量、リトライ回数等）
+//! - [`LoopResult`][]: ループ（`[[loops]]`）の実行結果（繰り返し回数、終了条件を満たしたか）
 //! - [`ExecutionStatus`][]: ワークフロー全体の実行ステータス（成功/部分成功/失敗）
 //! - [`StepStatus`][]: 個別ステップの実行ステータス（成功/失敗/リトライ/スキップ）
 //! - [`ExecutionError`][]: ワークフロー実行時のエラー型
@@ -37,9 +38,9 @@
 //! }
 //! ```
 
-use crate::error::{ConfigError, ProviderError};
-use crate::provider::TokenUsage;
-use serde::Serialize;
+use crate::error::{CheckpointError, ConfigError, ProviderError, RetryClass};
+use crate::provider::{SystemPromptChannel, TokenUsage};
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
@@ -62,15 +63,18 @@ use thiserror::Error;
 ///     }
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowResult {
     /// ワークフロー名
     pub workflow_name: String,
 
+    /// 実行ID（テレメトリーとチェックポイントの実行IDと同じ）
+    pub run_id: String,
+
     /// 実行ステータス
     pub status: ExecutionStatus,
 
-    /// 各ステップの実行結果
+    /// 各ステップの実行結果（完了順。並列実行時は定義順と異なる場合があります）
     pub steps: Vec<StepResult>,
 
     /// 実行開始時刻
@@ -83,7 +87,13 @@ pub struct WorkflowResult {
     pub total_duration: Duration,
 
     /// 総トークン使用量
-    pub total_tokens_used: u32,
+    pub total_tokens_used: u64,
+
+    /// 総コスト（米ドル、単価が不明なステップは含まない）
+    pub total_cost_usd: f64,
+
+    /// 各ループの実行結果（ループを抜けた順）
+    pub loops: Vec<LoopResult>,
 
     /// エラーメッセージ（失敗時のみ）
     pub error: Option<String>,
@@ -104,12 +114,15 @@ impl WorkflowResult {
     /// # use std::time::{SystemTime, Duration};
     /// # let result = WorkflowResult {
     /// #     workflow_name: "test".to_string(),
+    /// #     run_id: "run".to_string(),
     /// #     status: ExecutionStatus::Success,
     /// #     steps: vec![],
     /// #     start_time: SystemTime::now(),
     /// #     end_time: SystemTime::now(),
     /// #     total_duration: Duration::from_secs(1),
     /// #     total_tokens_used: 100,
+    /// #     total_cost_usd: 0.0,
+    /// #     loops: vec![],
     /// #     error: None,
     /// # };
     /// let json = result.to_json().unwrap();
@@ -129,6 +142,16 @@ impl WorkflowResult {
         matches!(self.status, ExecutionStatus::Success)
     }
 
+    /// 修正回数（ループの2回目以降の実行回数の合計）
+    ///
+    /// README の KPI「修正回数」に対応し、0 が理想です。
+    pub fn revision_count(&self) -> u32 {
+        self.loops
+            .iter()
+            .map(|step_loop| step_loop.iterations.saturating_sub(1))
+            .sum()
+    }
+
     /// 完了したステップ数
     ///
     /// # 戻り値
@@ -142,12 +165,15 @@ impl WorkflowResult {
     /// # use std::time::{SystemTime, Duration};
     /// # let result = WorkflowResult {
     /// #     workflow_name: "test".to_string(),
+    /// #     run_id: "run".to_string(),
     /// #     status: ExecutionStatus::Success,
     /// #     steps: vec![],
     /// #     start_time: SystemTime::now(),
     /// #     end_time: SystemTime::now(),
     /// #     total_duration: Duration::from_secs(1),
     /// #     total_tokens_used: 100,
+    /// #     total_cost_usd: 0.0,
+    /// #     loops: vec![],
     /// #     error: None,
     /// # };
     /// println!("完了ステップ: {}/{}", result.completed_steps(), result.steps.len());
@@ -166,7 +192,7 @@ impl WorkflowResult {
 ///
 /// 個別のステップの実行結果を表す型です。
 /// 出力、トークン使用量、実行時間、リトライ回数などを含みます。
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepResult {
     /// ステップ名
     pub step_name: String,
@@ -180,7 +206,7 @@ pub struct StepResult {
     /// LLMの出力（成功時のみ）
     pub output: Option<String>,
 
-    /// トークン使用量
+    /// トークン使用量（失敗した試行・フォールバック・修正依頼を含む合計）
     pub token_usage: TokenUsage,
 
     /// 実行時間
@@ -191,12 +217,122 @@ pub struct StepResult {
 
     /// エラーメッセージ（失敗時のみ）
     pub error: Option<String>,
+
+    /// スキップした理由（スキップ時のみ）
+    pub skip_reason: Option<String>,
+
+    /// ループ内の繰り返し回数（0始まり、ループ外のステップは0）
+    pub iteration: u32,
+
+    /// 出力から取り出した JSON（`output_schema` を指定したステップの成功時のみ、検証済み）
+    pub structured_output: Option<serde_json::Value>,
+
+    /// 出力がスキーマに一致せず、修正を依頼した回数
+    pub repair_count: u32,
+
+    /// システムプロンプトの渡し方（成功時のみ）
+    pub system_prompt_channel: Option<SystemPromptChannel>,
+
+    /// 出力を生成したプロバイダー名（成功時のみ、フォールバックした場合はフォールバック先）
+    pub provider: Option<String>,
+
+    /// 使用したモデル名（成功時のみ、ステップの `model`・`[models]`・ユーザー設定・プロバイダーの既定の順に解決）
+    ///
+    /// プロバイダーが既定のモデル名を公開していない場合は `None` です。
+    pub model: Option<String>,
+
+    /// 失敗した各試行のエラーメッセージ（試行順、失敗時は最後の試行を含む）
+    pub attempt_errors: Vec<String>,
+
+    /// コスト（米ドル、失敗した試行・フォールバック・修正依頼を含む合計）
+    ///
+    /// 使ったモデルの単価（[`select_price`](crate::config::prices::select_price)）がすべての応答で不明な場合は `None` です。
+    pub cost_usd: Option<f64>,
+
+    /// チェックポイントから復元した結果か（[`resume_from`](crate::engine::WorkflowExecutor::resume_from) で
+    /// 前回の実行の出力を再利用し、LLM を実行しなかったステップ）
+    pub resumed: bool,
+
+    /// 応答キャッシュ（[`ResponseCache`](crate::provider::cache::ResponseCache)）の応答を使った結果か
+    ///
+    /// LLM を実行していないため、トークン使用量は0、コストは `Some(0.0)` です。
+    /// 出力スキーマの修正依頼を含むステップは、すべての応答がキャッシュにあった場合のみ `true` です。
+    #[serde(default)]
+    pub cache_hit: bool,
+}
+
+impl StepResult {
+    /// 未実行（スキップ）のステップ結果を生成
+    ///
+    /// 前のステップの失敗や、実行条件（`when`）を満たさないことにより
+    /// 実行されなかったステップに使用します。
+    ///
+    /// # 引数
+    ///
+    /// - `step_name`: ステップ名
+    /// - `index`: ステップインデックス（0始まり）
+    /// - `reason`: スキップした理由
+    pub fn skipped(step_name: &str, index: usize, reason: impl Into<String>) -> Self {
+        Self {
+            step_name: step_name.to_string(),
+            index,
+            status: StepStatus::Skipped,
+            output: None,
+            token_usage: TokenUsage::default(),
+            duration: Duration::from_secs(0),
+            retry_count: 0,
+            error: None,
+            skip_reason: Some(reason.into()),
+            iteration: 0,
+            structured_output: None,
+            repair_count: 0,
+            system_prompt_channel: None,
+            provider: None,
+            model: None,
+            attempt_errors: Vec::new(),
+            cost_usd: None,
+            resumed: false,
+            cache_hit: false,
+        }
+    }
+}
+
+/// ステップのコストの合計（米ドル、単価が不明な `None` は含まない）
+///
+/// `f64` の `sum` は空の場合に `-0.0` を返すため、`0.0` から加算します。
+/// コストを合計する箇所はすべてこの関数を使います。
+///
+/// # 例
+///
+/// ```rust
+/// use melted_adw::engine::result::total_cost;
+///
+/// assert_eq!(total_cost([Some(0.25), None, Some(0.5)]), 0.75);
+/// assert!(total_cost([None]).is_sign_positive());
+/// ```
+pub fn total_cost(costs: impl IntoIterator<Item = Option<f64>>) -> f64 {
+    costs.into_iter().flatten().fold(0.0, |total, cost| total + cost)
+}
+
+/// ループ実行結果
+///
+/// `[[loops]]` で定義したステップ範囲の繰り返し結果を表します。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoopResult {
+    /// ループ名
+    pub name: String,
+
+    /// 実行回数（1回目を含む）
+    pub iterations: u32,
+
+    /// 終了条件を満たして抜けたか（`false` は最大実行回数に到達）
+    pub converged: bool,
 }
 
 /// ワークフロー実行ステータス
 ///
 /// ワークフロー全体の実行結果を表します。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExecutionStatus {
     /// すべてのステップが成功
     Success,
@@ -216,7 +352,7 @@ pub enum ExecutionStatus {
 /// ステップ実行ステータス
 ///
 /// 個別のステップの実行結果を表します。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum StepStatus {
     /// 成功（リトライなし）
     Success,
@@ -243,6 +379,10 @@ pub enum StepStatus {
 /// - [`ExecutionError::ConfigError`] - 設定エラー（ワークフロー定義の不備等）
 /// - [`ExecutionError::ProviderError`] - プロバイダーエラー（LLM通信失敗等）
 /// - [`ExecutionError::TimeoutError`] - タイムアウト（ステップが時間内に完了しない）
+/// - [`ExecutionError::OutputSchemaMismatch`] - 出力が `output_schema` に一致しない
+/// - [`ExecutionError::OutputTruncated`] - 出力が最大出力トークン数で打ち切られた
+/// - [`ExecutionError::BudgetExceeded`] - 予算（`[budget]` / `budget`）の上限を超えた
+/// - [`ExecutionError::Checkpoint`] - チェックポイントから再開できない（ワークフロー定義の変更等）
 /// - [`ExecutionError::ValidationError`] - バリデーションエラー（入力値の不備等）
 /// - [`ExecutionError::ContextError`] - コンテキストエラー（ステップ間データ受け渡しの失敗等）
 #[derive(Debug, Error)]
@@ -265,6 +405,39 @@ pub enum ExecutionError {
         timeout_secs: u64,
     },
 
+    /// 出力スキーマエラー（修正依頼を使い切っても出力がスキーマに一致しない）
+    #[error("出力スキーマエラー: ステップ '{step_name}' の出力がスキーマに一致しません: {}", .errors.join("; "))]
+    OutputSchemaMismatch {
+        /// ステップ名
+        step_name: String,
+        /// 最後の出力の問題点
+        errors: Vec<String>,
+    },
+
+    /// 出力の打ち切り（応答が最大出力トークン数に達した）
+    #[error("出力打ち切り: ステップ '{step_name}' の出力がプロバイダー '{provider}' の最大出力トークン数に達しました")]
+    OutputTruncated {
+        /// ステップ名
+        step_name: String,
+        /// 応答したプロバイダー名
+        provider: String,
+    },
+
+    /// 予算超過（トークン数・コスト・経過時間の上限を超えた、または超える見込み）
+    #[error("予算超過: {scope} の {limit} を超えました: {detail}")]
+    BudgetExceeded {
+        /// 予算の範囲（`ワークフロー` または `ステップ '<name>'`）
+        scope: String,
+        /// 超過した上限のキー（`max_tokens_total` / `max_cost_usd` / `max_wall_time`）
+        limit: &'static str,
+        /// 使用量と上限
+        detail: String,
+    },
+
+    /// チェックポイントエラー
+    #[error("チェックポイントエラー: {0}")]
+    Checkpoint(#[from] CheckpointError),
+
     /// バリデーションエラー
     #[error("バリデーションエラー: {0}")]
     ValidationError(String),
@@ -274,6 +447,27 @@ pub enum ExecutionError {
     ContextError(String),
 }
 
+impl ExecutionError {
+    /// 再試行で解消する見込みのあるエラーの種類
+    ///
+    /// ステップのタイムアウトは [`RetryClass::Timeout`]、修正依頼を使い切っても出力がスキーマに
+    /// 一致しない場合は [`RetryClass::InvalidResponse`] です。設定エラーや、同じ上限では再試行しても
+    /// 解消しない出力の打ち切り等は `None` です。
+    pub fn retry_class(&self) -> Option<RetryClass> {
+        match self {
+            ExecutionError::ProviderError(e) => e.retry_class(),
+            ExecutionError::TimeoutError { .. } => Some(RetryClass::Timeout),
+            ExecutionError::OutputSchemaMismatch { .. } => Some(RetryClass::InvalidResponse),
+            ExecutionError::ConfigError(_)
+            | ExecutionError::OutputTruncated { .. }
+            | ExecutionError::BudgetExceeded { .. }
+            | ExecutionError::Checkpoint(_)
+            | ExecutionError::ValidationError(_)
+            | ExecutionError::ContextError(_) => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,12 +476,15 @@ mod tests {
     fn test_workflow_result_is_success() {
         let result = WorkflowResult {
             workflow_name: "test_workflow".to_string(),
+            run_id: "run".to_string(),
             status: ExecutionStatus::Success,
             steps: vec![],
             start_time: SystemTime::now(),
             end_time: SystemTime::now(),
             total_duration: Duration::from_secs(10),
             total_tokens_used: 1000,
+            total_cost_usd: 0.0,
+            loops: vec![],
             error: None,
         };
 
@@ -298,12 +495,15 @@ mod tests {
     fn test_workflow_result_is_not_success_when_failed() {
         let result = WorkflowResult {
             workflow_name: "test_workflow".to_string(),
+            run_id: "run".to_string(),
             status: ExecutionStatus::Failed,
             steps: vec![],
             start_time: SystemTime::now(),
             end_time: SystemTime::now(),
             total_duration: Duration::from_secs(5),
             total_tokens_used: 500,
+            total_cost_usd: 0.0,
+            loops: vec![],
             error: Some("エラーが発生しました".to_string()),
         };
 
@@ -314,6 +514,7 @@ mod tests {
     fn test_workflow_result_is_not_success_when_partial() {
         let result = WorkflowResult {
             workflow_name: "test_workflow".to_string(),
+            run_id: "run".to_string(),
             status: ExecutionStatus::PartialSuccess {
                 completed: 2,
                 total: 3,
@@ -323,6 +524,8 @@ mod tests {
             end_time: SystemTime::now(),
             total_duration: Duration::from_secs(15),
             total_tokens_used: 1500,
+            total_cost_usd: 0.0,
+            loops: vec![],
             error: None,
         };
 
@@ -333,6 +536,7 @@ mod tests {
     fn test_completed_steps_count() {
         let result = WorkflowResult {
             workflow_name: "test_workflow".to_string(),
+            run_id: "run".to_string(),
             status: ExecutionStatus::PartialSuccess {
                 completed: 2,
                 total: 4,
@@ -346,10 +550,22 @@ mod tests {
                     token_usage: TokenUsage {
                         input_tokens: 100,
                         output_tokens: 200,
+                        ..Default::default()
                     },
                     duration: Duration::from_secs(5),
                     retry_count: 0,
                     error: None,
+                    skip_reason: None,
+                    iteration: 0,
+                    structured_output: None,
+                    repair_count: 0,
+                    system_prompt_channel: None,
+                    provider: None,
+                    model: None,
+                    attempt_errors: Vec::new(),
+                    cost_usd: None,
+                    resumed: false,
+                    cache_hit: false,
                 },
                 StepResult {
                     step_name: "step2".to_string(),
@@ -359,10 +575,22 @@ mod tests {
                     token_usage: TokenUsage {
                         input_tokens: 150,
                         output_tokens: 250,
+                        ..Default::default()
                     },
                     duration: Duration::from_secs(10),
                     retry_count: 2,
                     error: None,
+                    skip_reason: None,
+                    iteration: 0,
+                    structured_output: None,
+                    repair_count: 0,
+                    system_prompt_channel: None,
+                    provider: None,
+                    model: None,
+                    attempt_errors: Vec::new(),
+                    cost_usd: None,
+                    resumed: false,
+                    cache_hit: false,
                 },
                 StepResult {
                     step_name: "step3".to_string(),
@@ -372,29 +600,51 @@ mod tests {
                     token_usage: TokenUsage {
                         input_tokens: 100,
                         output_tokens: 0,
+                        ..Default::default()
                     },
                     duration: Duration::from_secs(2),
                     retry_count: 3,
                     error: Some("実行エラー".to_string()),
+                    skip_reason: None,
+                    iteration: 0,
+                    structured_output: None,
+                    repair_count: 0,
+                    system_prompt_channel: None,
+                    provider: None,
+                    model: None,
+                    attempt_errors: Vec::new(),
+                    cost_usd: None,
+                    resumed: false,
+                    cache_hit: false,
                 },
                 StepResult {
                     step_name: "step4".to_string(),
                     index: 3,
                     status: StepStatus::Skipped,
                     output: None,
-                    token_usage: TokenUsage {
-                        input_tokens: 0,
-                        output_tokens: 0,
-                    },
+                    token_usage: TokenUsage::default(),
                     duration: Duration::from_secs(0),
                     retry_count: 0,
                     error: None,
+                    skip_reason: None,
+                    iteration: 0,
+                    structured_output: None,
+                    repair_count: 0,
+                    system_prompt_channel: None,
+                    provider: None,
+                    model: None,
+                    attempt_errors: Vec::new(),
+                    cost_usd: None,
+                    resumed: false,
+                    cache_hit: false,
                 },
             ],
             start_time: SystemTime::now(),
             end_time: SystemTime::now(),
             total_duration: Duration::from_secs(17),
             total_tokens_used: 800,
+            total_cost_usd: 0.0,
+            loops: vec![],
             error: None,
         };
 
@@ -402,10 +652,55 @@ mod tests {
         assert_eq!(result.completed_steps(), 2);
     }
 
+    #[test]
+    fn test_revision_count() {
+        let result = WorkflowResult {
+            workflow_name: "test_workflow".to_string(),
+            run_id: "run".to_string(),
+            status: ExecutionStatus::Success,
+            steps: vec![],
+            start_time: SystemTime::now(),
+            end_time: SystemTime::now(),
+            total_duration: Duration::from_secs(1),
+            total_tokens_used: 0,
+            total_cost_usd: 0.0,
+            loops: vec![
+                LoopResult {
+                    name: "review-fix".to_string(),
+                    iterations: 3,
+                    converged: true,
+                },
+                LoopResult {
+                    name: "test-fix".to_string(),
+                    iterations: 1,
+                    converged: true,
+                },
+            ],
+            error: None,
+        };
+
+        // 1回目は修正に含めない
+        assert_eq!(result.revision_count(), 2);
+    }
+
+    #[test]
+    fn test_step_result_skipped() {
+        let result = StepResult::skipped("step3", 2, "前のステップが失敗したため");
+
+        assert_eq!(result.step_name, "step3");
+        assert_eq!(result.index, 2);
+        assert_eq!(result.status, StepStatus::Skipped);
+        assert!(result.output.is_none());
+        assert_eq!(result.token_usage.total(), 0);
+        assert!(result.error.is_none());
+        assert_eq!(result.skip_reason.as_deref(), Some("前のステップが失敗したため"));
+    }
+
     #[test]
     fn test_workflow_result_to_json() {
         let result = WorkflowResult {
             workflow_name: "test_workflow".to_string(),
+            run_id: "run".to_string(),
             status: ExecutionStatus::Success,
             steps: vec![StepResult {
                 step_name: "step1".to_string(),
@@ -415,15 +710,29 @@ mod tests {
                 token_usage: TokenUsage {
                     input_tokens: 100,
                     output_tokens: 200,
+                    ..Default::default()
                 },
                 duration: Duration::from_secs(5),
                 retry_count: 0,
                 error: None,
+                skip_reason: None,
+                iteration: 0,
+                structured_output: None,
+                repair_count: 0,
+                system_prompt_channel: None,
+                provider: None,
+                model: None,
+                attempt_errors: Vec::new(),
+                cost_usd: None,
+                resumed: false,
+                cache_hit: false,
             }],
             start_time: SystemTime::now(),
             end_time: SystemTime::now(),
             total_duration: Duration::from_secs(5),
             total_tokens_used: 300,
+            total_cost_usd: 0.0,
+            loops: vec![],
             error: None,
         };
 
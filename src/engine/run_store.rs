@@ -0,0 +1,436 @@
+//! 実行履歴（実行ごとのディレクトリ）
+//!
+//! # 責務
+//!
+//! - 実行ごとのディレクトリ（`<runs_dir>/<run_id>/`）に、解決済みのワークフロー定義・初期入力・
+//!   ステップごとのプロンプトと出力・実行結果を保存する
+//! - 保存した実行の一覧と、各実行の記録を読み込む
+//!
+//! 保存は [`WorkflowExecutor::with_run_store`](super::executor::WorkflowExecutor::with_run_store) を設定した
+//! 実行エンジンが行い、`adw runs list/show/diff` が読み込みます。
+//!
+//! # 保存先
+//!
+//! ```text
+//! .adw/runs/
+//! └── 20251016-093012-3f2a/
+//!     ├── workflow.toml      # 解決済みのワークフロー定義（プロンプトファイルを展開済み）
+//!     ├── input.txt          # 初期入力（指定した場合のみ）
+//!     ├── checkpoint.json    # 完了したステップの出力（再開に使う）
+//!     ├── steps/
+//!     │   ├── 00-0.json      # ステップ 0 の1回目のプロンプトと出力
+//!     │   └── 01-0.json
+//!     └── result.json        # 実行結果（実行の終了時に保存）
+//! ```
+//!
+//! `result.json` のない実行は、実行中または中断した実行です。
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::workflow::Workflow;
+use crate::engine::checkpoint::{CHECKPOINT_FILE, Checkpoint};
+use crate::engine::result::{ExecutionStatus, StepResult, WorkflowResult, total_cost};
+use crate::error::RunStoreError;
+
+/// 解決済みのワークフロー定義のファイル名
+pub const WORKFLOW_FILE: &str = "workflow.toml";
+
+/// 初期入力のファイル名
+pub const INPUT_FILE: &str = "input.txt";
+
+/// ステップごとの記録のディレクトリ名
+pub const STEPS_DIR: &str = "steps";
+
+/// 実行結果のファイル名
+pub const RESULT_FILE: &str = "result.json";
+
+/// 実行ごとのディレクトリの保存先
+///
+/// # 例
+///
+/// ```rust,no_run
+/// use melted_adw::engine::run_store::RunStore;
+///
+/// let store = RunStore::new(".adw/runs");
+/// for run in store.list().unwrap() {
+///     println!("{} {}", run.run_id, run.workflow_name);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RunStore {
+    /// 保存先のディレクトリ
+    dir: PathBuf,
+}
+
+/// ステップの1回の実行の記録（プロンプトと出力）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepTranscript {
+    /// ステップ名
+    pub step_name: String,
+
+    /// ステップインデックス（0始まり）
+    pub index: usize,
+
+    /// ループ内の繰り返し回数（0始まり、ループ外のステップは0）
+    pub iteration: u32,
+
+    /// 展開済みのシステムプロンプト
+    pub system_prompt: String,
+
+    /// ステップへの入力
+    pub user_input: String,
+
+    /// LLMの出力（成功時のみ）
+    pub output: Option<String>,
+
+    /// エラーメッセージ（失敗時のみ）
+    pub error: Option<String>,
+
+    /// チェックポイントの出力を再利用したか
+    pub resumed: bool,
+}
+
+/// 保存した実行の概要（`adw runs list`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    /// 実行ID
+    pub run_id: String,
+
+    /// ワークフロー名
+    pub workflow_name: String,
+
+    /// 実行ステータス（結果が保存されていない実行中・中断した実行は `None`）
+    pub status: Option<ExecutionStatus>,
+
+    /// 完了したステップ数
+    pub completed_steps: usize,
+
+    /// ステップの結果の数（結果が保存されていない実行は `None`）
+    pub total_steps: Option<usize>,
+
+    /// 総トークン使用量
+    pub total_tokens_used: u64,
+
+    /// 総コスト（米ドル）
+    pub total_cost_usd: f64,
+
+    /// 総実行時間（結果が保存されていない実行は `None`）
+    pub total_duration: Option<Duration>,
+
+    /// 再開元の実行ID
+    pub resumed_from: Option<String>,
+}
+
+impl StepTranscript {
+    /// ステップの結果とプロンプトから記録を生成
+    pub fn new(result: &StepResult, system_prompt: String, user_input: String) -> Self {
+        Self {
+            step_name: result.step_name.clone(),
+            index: result.index,
+            iteration: result.iteration,
+            system_prompt,
+            user_input,
+            output: result.output.clone(),
+            error: result.error.clone(),
+            resumed: result.resumed,
+        }
+    }
+}
+
+impl RunStore {
+    /// 保存先のディレクトリを指定して生成
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 保存先のディレクトリを取得
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 実行のディレクトリのパスを取得
+    ///
+    /// # 例
+    ///
+    /// ```rust
+    /// use melted_adw::engine::run_store::RunStore;
+    /// use std::path::Path;
+    ///
+    /// let store = RunStore::new(".adw/runs");
+    /// assert_eq!(store.run_dir("20251016-093012-3f2a"), Path::new(".adw/runs/20251016-093012-3f2a"));
+    /// ```
+    pub fn run_dir(&self, run_id: &str) -> PathBuf {
+        self.dir.join(run_id)
+    }
+
+    /// 実行の開始時に、解決済みのワークフロー定義と初期入力を保存
+    pub fn save_definition(
+        &self,
+        run_id: &str,
+        workflow: &Workflow,
+        initial_input: Option<&str>,
+    ) -> Result<(), RunStoreError> {
+        let dir = self.run_dir(run_id);
+        std::fs::create_dir_all(&dir)?;
+        write_atomically(&dir.join(WORKFLOW_FILE), &workflow.to_string()?)?;
+        if let Some(input) = initial_input {
+            write_atomically(&dir.join(INPUT_FILE), input)?;
+        }
+        Ok(())
+    }
+
+    /// チェックポイントを保存
+    pub fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), RunStoreError> {
+        Ok(checkpoint.save(&self.dir)?)
+    }
+
+    /// ステップの1回の実行の記録を `steps/<index>-<iteration>.json` に保存
+    pub fn save_step(&self, run_id: &str, transcript: &StepTranscript) -> Result<(), RunStoreError> {
+        let dir = self.run_dir(run_id).join(STEPS_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let file = format!("{:02}-{}.json", transcript.index, transcript.iteration);
+        write_atomically(&dir.join(file), &serde_json::to_string_pretty(transcript)?)
+    }
+
+    /// 実行の終了時に実行結果を保存
+    pub fn save_result(&self, result: &WorkflowResult) -> Result<(), RunStoreError> {
+        let dir = self.run_dir(&result.run_id);
+        std::fs::create_dir_all(&dir)?;
+        write_atomically(&dir.join(RESULT_FILE), &result.to_json()?)
+    }
+
+    /// 保存した実行の概要を新しい順に取得
+    ///
+    /// 実行結果のない実行（実行中・中断）はチェックポイントから概要を作ります。
+    /// 実行結果とチェックポイントのどちらもないディレクトリは無視します。
+    /// 保存先のディレクトリが存在しない場合は空の一覧を返します。
+    pub fn list(&self) -> Result<Vec<RunSummary>, RunStoreError> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut run_ids = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir()
+                && let Some(run_id) = entry.file_name().to_str()
+            {
+                run_ids.push(run_id.to_string());
+            }
+        }
+        // 実行IDは開始時刻で始まるため、名前の降順が新しい順
+        run_ids.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut summaries = Vec::new();
+        for run_id in run_ids {
+            let dir = self.run_dir(&run_id);
+            let checkpoint = if dir.join(CHECKPOINT_FILE).is_file() {
+                Some(Checkpoint::load(&self.dir, &run_id)?)
+            } else {
+                None
+            };
+            if dir.join(RESULT_FILE).is_file() {
+                let mut summary = RunSummary::from(&self.load_result(&run_id)?);
+                summary.resumed_from = checkpoint.and_then(|checkpoint| checkpoint.resumed_from);
+                summaries.push(summary);
+            } else if let Some(checkpoint) = checkpoint {
+                summaries.push(RunSummary::from(&checkpoint));
+            }
+        }
+        Ok(summaries)
+    }
+
+    /// 実行結果を読み込む
+    ///
+    /// # エラー
+    ///
+    /// - [`RunStoreError::NotFound`][]: 実行が存在しない（実行IDがパスとして不正な場合を含む）
+    /// - [`RunStoreError::ResultMissing`][]: 実行結果が保存されていない（実行中・中断）
+    pub fn load_result(&self, run_id: &str) -> Result<WorkflowResult, RunStoreError> {
+        let dir = self.existing_run_dir(run_id)?;
+        let path = dir.join(RESULT_FILE);
+        if !path.is_file() {
+            return Err(RunStoreError::ResultMissing(run_id.to_string()));
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// ステップごとの記録をステップの定義順（ループ内は繰り返し順）に読み込む
+    ///
+    /// # エラー
+    ///
+    /// 実行が存在しない場合は [`RunStoreError::NotFound`]
+    pub fn load_steps(&self, run_id: &str) -> Result<Vec<StepTranscript>, RunStoreError> {
+        let dir = self.existing_run_dir(run_id)?.join(STEPS_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut transcripts = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                let transcript: StepTranscript = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+                transcripts.push(transcript);
+            }
+        }
+        transcripts.sort_by_key(|transcript| (transcript.index, transcript.iteration));
+        Ok(transcripts)
+    }
+
+    /// 実行のディレクトリを取得（存在しない、または実行IDがパスとして不正な場合はエラー）
+    fn existing_run_dir(&self, run_id: &str) -> Result<PathBuf, RunStoreError> {
+        let is_plain_name = !run_id.is_empty() && run_id != ".." && !run_id.contains(std::path::is_separator);
+        let dir = self.run_dir(run_id);
+        if !is_plain_name || !dir.is_dir() {
+            return Err(RunStoreError::NotFound(run_id.to_string()));
+        }
+        Ok(dir)
+    }
+}
+
+impl From<&WorkflowResult> for RunSummary {
+    fn from(result: &WorkflowResult) -> Self {
+        Self {
+            run_id: result.run_id.clone(),
+            workflow_name: result.workflow_name.clone(),
+            status: Some(result.status),
+            completed_steps: result.completed_steps(),
+            total_steps: Some(result.steps.len()),
+            total_tokens_used: result.total_tokens_used,
+            total_cost_usd: result.total_cost_usd,
+            total_duration: Some(result.total_duration),
+            // 再開元はチェックポイントに記録されている
+            resumed_from: None,
+        }
+    }
+}
+
+impl From<&Checkpoint> for RunSummary {
+    fn from(checkpoint: &Checkpoint) -> Self {
+        Self {
+            run_id: checkpoint.run_id.clone(),
+            workflow_name: checkpoint.workflow_name.clone(),
+            status: None,
+            completed_steps: checkpoint.steps.len(),
+            total_steps: None,
+            total_tokens_used: checkpoint.steps.iter().map(|step| step.output.token_usage.total()).sum(),
+            total_cost_usd: total_cost(checkpoint.steps.iter().map(|step| step.cost_usd)),
+            total_duration: None,
+            resumed_from: checkpoint.resumed_from.clone(),
+        }
+    }
+}
+
+/// 一時ファイルに書き込んでから置き換える（書き込みの途中で中断しても壊れたファイルを残さない）
+fn write_atomically(path: &Path, contents: &str) -> Result<(), RunStoreError> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    std::fs::write(&temporary, contents)?;
+    std::fs::rename(temporary, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::result::StepStatus;
+    use crate::provider::TokenUsage;
+    use std::time::SystemTime;
+
+    const WORKFLOW: &str = r#"
+[workflow]
+name = "history"
+
+[[steps]]
+name = "plan"
+system_prompt = "Plan"
+provider = "anthropic"
+model_tier = "heavy"
+"#;
+
+    fn result(run_id: &str, status: ExecutionStatus) -> WorkflowResult {
+        let plan = StepResult {
+            status: StepStatus::Success,
+            output: Some("the plan".to_string()),
+            skip_reason: None,
+            token_usage: TokenUsage { input_tokens: 10, output_tokens: 20, ..Default::default() },
+            cost_usd: Some(0.25),
+            ..StepResult::skipped("plan", 0, "")
+        };
+        WorkflowResult {
+            workflow_name: "history".to_string(),
+            run_id: run_id.to_string(),
+            status,
+            steps: vec![plan],
+            start_time: SystemTime::UNIX_EPOCH,
+            end_time: SystemTime::UNIX_EPOCH + Duration::from_secs(2),
+            total_duration: Duration::from_secs(2),
+            total_tokens_used: 30,
+            total_cost_usd: 0.25,
+            loops: vec![],
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("adw-run-store-test-{}", std::process::id()));
+        let store = RunStore::new(&dir);
+        let workflow = Workflow::from_toml(WORKFLOW).unwrap();
+
+        // 完了した実行
+        let finished = result("20251016-093012-3f2a", ExecutionStatus::Success);
+        store.save_definition(&finished.run_id, &workflow, Some("requirements")).unwrap();
+        let transcript = StepTranscript::new(&finished.steps[0], "Plan".to_string(), "requirements".to_string());
+        store.save_step(&finished.run_id, &transcript).unwrap();
+        store.save_result(&finished).unwrap();
+
+        // 中断した実行（チェックポイントのみ）
+        let mut checkpoint = Checkpoint::new("20251016-101544-91c0", &workflow, None);
+        checkpoint.record(&workflow, &finished.steps[0]);
+        store.save_checkpoint(&checkpoint).unwrap();
+
+        let run_dir = store.run_dir(&finished.run_id);
+        let saved_workflow = Workflow::from_file(run_dir.join(WORKFLOW_FILE)).unwrap();
+        assert_eq!(saved_workflow.fingerprint(), workflow.fingerprint());
+        assert_eq!(std::fs::read_to_string(run_dir.join(INPUT_FILE)).unwrap(), "requirements");
+        assert_eq!(store.load_steps(&finished.run_id).unwrap(), vec![transcript]);
+
+        let loaded = store.load_result(&finished.run_id).unwrap();
+        assert_eq!(loaded.steps[0].output.as_deref(), Some("the plan"));
+        assert_eq!(loaded.steps[0].token_usage, finished.steps[0].token_usage);
+
+        let summaries = store.list().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // 新しい順
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].run_id, "20251016-101544-91c0");
+        assert_eq!(summaries[0].status, None);
+        assert_eq!(summaries[0].completed_steps, 1);
+        assert_eq!(summaries[0].total_tokens_used, 30);
+        assert_eq!(summaries[1].status, Some(ExecutionStatus::Success));
+        assert_eq!(summaries[1].total_steps, Some(1));
+
+        assert!(matches!(store.load_result("missing"), Err(RunStoreError::NotFound(_))));
+        assert!(matches!(store.load_steps("../etc"), Err(RunStoreError::NotFound(_))));
+        assert!(store.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_load_result_missing() {
+        let dir = std::env::temp_dir().join(format!("adw-run-store-missing-test-{}", std::process::id()));
+        let store = RunStore::new(&dir);
+        let workflow = Workflow::from_toml(WORKFLOW).unwrap();
+        store.save_definition("run", &workflow, None).unwrap();
+
+        let err = store.load_result("run").unwrap_err();
+        assert!(!store.run_dir("run").join(INPUT_FILE).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(err, RunStoreError::ResultMissing(_)), "{:?}", err);
+    }
+}
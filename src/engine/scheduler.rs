@@ -0,0 +1,245 @@
+//! ステップの実行順序の制御
+//!
+//! # 責務
+//!
+//! 依存関係（`depends_on`）とループ（`[[loops]]`）に従って、実行可能になったステップを決定する。
+//! LLM の呼び出しや結果の記録は行わない（[`WorkflowExecutor`](super::executor::WorkflowExecutor) が担当）。
+//!
+//! # 実行可能の条件
+//!
+//! 未実行のステップは、すべての依存先が以下を満たした時点で実行可能になります。
+//!
+//! - 依存先が同じループ範囲内: 現在の繰り返しで完了している
+//! - 依存先が別のループ範囲内: そのループ全体が終了している
+//! - それ以外: 完了している
+//!
+//! ループ範囲内のステップがすべて完了すると、実行エンジンが終了条件を評価し、
+//! [`Scheduler::repeat_loop`] または [`Scheduler::finish_loop`] を呼び出します。
+
+use crate::config::workflow::Workflow;
+
+/// ステップの実行状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepState {
+    /// 未実行
+    Pending,
+    /// 実行中
+    Running,
+    /// 完了（成功・失敗・スキップ）
+    Done,
+}
+
+/// ステップの実行順序を管理するスケジューラー
+///
+/// 1回のワークフロー実行につき1つ生成します。
+#[derive(Debug)]
+pub(super) struct Scheduler<'a> {
+    workflow: &'a Workflow,
+    states: Vec<StepState>,
+    // ループごとの現在の繰り返し回数（0始まり）
+    loop_iterations: Vec<u32>,
+    loop_finished: Vec<bool>,
+}
+
+impl<'a> Scheduler<'a> {
+    /// すべてのステップが未実行の状態で生成
+    pub(super) fn new(workflow: &'a Workflow) -> Self {
+        Self {
+            workflow,
+            states: vec![StepState::Pending; workflow.steps().len()],
+            loop_iterations: vec![0; workflow.loops().len()],
+            loop_finished: vec![false; workflow.loops().len()],
+        }
+    }
+
+    /// ステップを含むループの位置を取得
+    fn loop_position(&self, index: usize) -> Option<usize> {
+        self.workflow
+            .loops()
+            .iter()
+            .position(|step_loop| step_loop.contains(index))
+    }
+
+    /// ステップの現在の繰り返し回数（0始まり、ループ外は 0）
+    pub(super) fn iteration(&self, index: usize) -> u32 {
+        self.loop_position(index)
+            .map_or(0, |position| self.loop_iterations[position])
+    }
+
+    /// ループの現在の繰り返し回数（0始まり）
+    pub(super) fn loop_iteration(&self, position: usize) -> u32 {
+        self.loop_iterations[position]
+    }
+
+    /// 実行可能なステップのうち、最も前に定義されたものを取得
+    pub(super) fn next_ready(&self) -> Option<usize> {
+        (0..self.states.len()).find(|&index| {
+            self.states[index] == StepState::Pending && self.dependencies_satisfied(index)
+        })
+    }
+
+    /// 依存先がすべて完了しているか
+    fn dependencies_satisfied(&self, index: usize) -> bool {
+        let own_loop = self.loop_position(index);
+        self.workflow.dependencies(index).iter().all(|&dependency| {
+            match self.loop_position(dependency) {
+                Some(position) if own_loop != Some(position) => self.loop_finished[position],
+                _ => self.states[dependency] == StepState::Done,
+            }
+        })
+    }
+
+    /// ステップの実行開始を記録
+    pub(super) fn start(&mut self, index: usize) {
+        self.states[index] = StepState::Running;
+    }
+
+    /// ステップの完了を記録
+    ///
+    /// # 戻り値
+    ///
+    /// このステップの完了によってループ範囲内のステップがすべて完了した場合、そのループの位置
+    pub(super) fn finish(&mut self, index: usize) -> Option<usize> {
+        self.states[index] = StepState::Done;
+
+        let position = self.loop_position(index)?;
+        let step_loop = &self.workflow.loops()[position];
+        (step_loop.start()..=step_loop.end())
+            .all(|i| self.states[i] == StepState::Done)
+            .then_some(position)
+    }
+
+    /// ループを次の繰り返しに進める（範囲内のステップを未実行に戻す）
+    pub(super) fn repeat_loop(&mut self, position: usize) {
+        let step_loop = &self.workflow.loops()[position];
+        self.loop_iterations[position] += 1;
+        for index in step_loop.start()..=step_loop.end() {
+            self.states[index] = StepState::Pending;
+        }
+    }
+
+    /// ループの終了を記録
+    pub(super) fn finish_loop(&mut self, position: usize) {
+        self.loop_finished[position] = true;
+    }
+
+    /// 未実行のステップ（定義順）
+    pub(super) fn pending(&self) -> Vec<usize> {
+        (0..self.states.len())
+            .filter(|&index| self.states[index] == StepState::Pending)
+            .collect()
+    }
+
+    /// 開始されたが終了していないループの位置
+    pub(super) fn unfinished_loops(&self) -> Vec<usize> {
+        self.workflow
+            .loops()
+            .iter()
+            .enumerate()
+            .filter(|&(position, step_loop)| {
+                !self.loop_finished[position]
+                    && (step_loop.start()..=step_loop.end())
+                        .any(|index| self.states[index] != StepState::Pending)
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(steps: &[(&str, Option<&str>)], loops: &str) -> Workflow {
+        let mut toml = String::from("[workflow]\nname = \"test\"\n\n");
+        for (name, depends_on) in steps {
+            toml.push_str(&format!(
+                "[[steps]]\nname = \"{}\"\nsystem_prompt = \"p\"\nprovider = \"anthropic\"\nmodel_tier = \"light\"\n",
+                name
+            ));
+            if let Some(depends_on) = depends_on {
+                toml.push_str(&format!("depends_on = {}\n", depends_on));
+            }
+            toml.push('\n');
+        }
+        toml.push_str(loops);
+        Workflow::from_toml(&toml).unwrap()
+    }
+
+    #[test]
+    fn test_sequential_by_default() {
+        let workflow = workflow(&[("plan", None), ("implement", None)], "");
+        let mut scheduler = Scheduler::new(&workflow);
+
+        assert_eq!(scheduler.next_ready(), Some(0));
+        scheduler.start(0);
+        assert_eq!(scheduler.next_ready(), None);
+        assert_eq!(scheduler.finish(0), None);
+        assert_eq!(scheduler.next_ready(), Some(1));
+    }
+
+    #[test]
+    fn test_independent_steps_are_ready_together() {
+        let workflow = workflow(
+            &[
+                ("implement", None),
+                ("security", Some(r#"["implement"]"#)),
+                ("style", Some(r#"["implement"]"#)),
+                ("summarize", Some(r#"["security", "style"]"#)),
+            ],
+            "",
+        );
+        let mut scheduler = Scheduler::new(&workflow);
+
+        scheduler.start(0);
+        scheduler.finish(0);
+
+        assert_eq!(scheduler.next_ready(), Some(1));
+        scheduler.start(1);
+        assert_eq!(scheduler.next_ready(), Some(2));
+        scheduler.start(2);
+        assert_eq!(scheduler.next_ready(), None);
+
+        scheduler.finish(2);
+        assert_eq!(scheduler.next_ready(), None);
+        scheduler.finish(1);
+        assert_eq!(scheduler.next_ready(), Some(3));
+    }
+
+    #[test]
+    fn test_loop_repeat_and_finish() {
+        let workflow = workflow(
+            &[("implement", None), ("review", None), ("summarize", None)],
+            r#"
+[[loops]]
+name = "review-fix"
+from = "implement"
+to = "review"
+max_iterations = 3
+until = { step = "review", contains = "LGTM" }
+"#,
+        );
+        let mut scheduler = Scheduler::new(&workflow);
+
+        scheduler.start(0);
+        assert_eq!(scheduler.finish(0), None);
+        scheduler.start(1);
+        assert_eq!(scheduler.finish(1), Some(0));
+
+        // 後続のステップはループの終了を待つ
+        assert_eq!(scheduler.next_ready(), None);
+        assert_eq!(scheduler.unfinished_loops(), vec![0]);
+
+        scheduler.repeat_loop(0);
+        assert_eq!(scheduler.iteration(1), 1);
+        assert_eq!(scheduler.iteration(2), 0);
+        assert_eq!(scheduler.next_ready(), Some(0));
+
+        scheduler.finish(0);
+        scheduler.finish(1);
+        scheduler.finish_loop(0);
+        assert_eq!(scheduler.next_ready(), Some(2));
+        assert!(scheduler.unfinished_loops().is_empty());
+        assert_eq!(scheduler.pending(), vec![2]);
+    }
+}
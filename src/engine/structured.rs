@@ -0,0 +1,130 @@
+//! 構造化出力（`output_schema`）の取り出しと修正依頼
+//!
+//! # 責務
+//!
+//! - LLM の出力から JSON を取り出す
+//! - 出力スキーマの指示をシステムプロンプトに追加する
+//! - スキーマに一致しなかった出力について、問題点を伝えて再回答を求める入力を組み立てる
+//!
+//! プロバイダーの呼び出しは [`WorkflowExecutor`](super::executor::WorkflowExecutor) が担当します。
+
+use serde_json::Value;
+
+use crate::config::schema::OutputSchema;
+
+/// LLM の出力から JSON を取り出す
+///
+/// 以下の順に試し、最初に JSON として解釈できたものを返します。
+///
+/// 1. 出力全体
+/// 2. コードブロック（```` ```json ```` など）の中身（出現順）
+/// 3. 最初の `{` / `[` から最後の `}` / `]` まで
+pub(super) fn extract_json(output: &str) -> Option<Value> {
+    let trimmed = output.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    if let Some(value) = code_blocks(trimmed).find_map(|block| serde_json::from_str(block.trim()).ok()) {
+        return Some(value);
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let end = trimmed.rfind(['}', ']'])?;
+    (start < end)
+        .then(|| serde_json::from_str(&trimmed[start..=end]).ok())
+        .flatten()
+}
+
+/// コードブロックの中身（言語名の行を除く）を出現順に列挙
+fn code_blocks(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let start = rest.find("```")?;
+        let after_fence = &rest[start + 3..];
+        let body_start = after_fence.find('\n').map_or(after_fence.len(), |i| i + 1);
+        let body = &after_fence[body_start..];
+        let end = body.find("```")?;
+        rest = &body[end + 3..];
+        Some(&body[..end])
+    })
+}
+
+/// 出力から JSON を取り出し、スキーマで検証する
+///
+/// # 戻り値
+///
+/// 一致しない場合、修正依頼に含める問題点の一覧
+pub(super) fn check_output(schema: &OutputSchema, output: &str) -> Result<Value, Vec<String>> {
+    let value = extract_json(output)
+        .ok_or_else(|| vec!["出力から JSON を取り出せませんでした".to_string()])?;
+    schema.validate(&value)?;
+    Ok(value)
+}
+
+/// 出力スキーマの指示を追加したシステムプロンプト
+pub(super) fn with_schema_instruction(system_prompt: &str, schema: &OutputSchema) -> String {
+    format!(
+        "{}\n\n回答は次の JSON Schema に一致する JSON で出力してください。\n\n```json\n{}\n```",
+        system_prompt,
+        pretty(schema.schema())
+    )
+}
+
+/// スキーマに一致しなかった出力の修正を依頼する入力
+///
+/// # 引数
+///
+/// - `user_input`: 元の入力
+/// - `previous_output`: スキーマに一致しなかった出力
+/// - `errors`: [`check_output`] が返した問題点
+pub(super) fn repair_input(user_input: &str, previous_output: &str, errors: &[String]) -> String {
+    let problems: Vec<String> = errors.iter().map(|error| format!("- {}", error)).collect();
+    format!(
+        "{}\n\n---\n\n前回の回答は出力スキーマに一致しませんでした。\n\n## 前回の回答\n\n{}\n\n## 問題点\n\n{}\n\n問題点を修正し、スキーマに一致する JSON のみを出力してください。",
+        user_input,
+        previous_output,
+        problems.join("\n")
+    )
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json(r#" {"ok": true} "#), Some(json!({ "ok": true })));
+        assert_eq!(
+            extract_json("結果です。\n```json\n{\"verdict\": \"approve\"}\n```\n以上"),
+            Some(json!({ "verdict": "approve" }))
+        );
+        assert_eq!(
+            extract_json("```\nnot json\n```\n```json\n[1, 2]\n```"),
+            Some(json!([1, 2]))
+        );
+        assert_eq!(
+            extract_json("判定: {\"verdict\": \"reject\"} です"),
+            Some(json!({ "verdict": "reject" }))
+        );
+        assert_eq!(extract_json("LGTM"), None);
+        assert_eq!(extract_json("} {"), None);
+    }
+
+    #[test]
+    fn test_repair_input_lists_problems() {
+        let input = repair_input(
+            "レビューしてください",
+            "{\"verdict\": \"maybe\"}",
+            &["/verdict: \"maybe\" is not one of [\"approve\",\"reject\"]".to_string()],
+        );
+        assert!(input.starts_with("レビューしてください\n\n---"));
+        assert!(input.contains("{\"verdict\": \"maybe\"}"));
+        assert!(input.contains("- /verdict: "));
+    }
+}
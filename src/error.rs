@@ -5,9 +5,16 @@
 //! アプリケーション全体で使用されるエラー型を定義する。
 //! - [`ConfigError`] - 設定ファイルの読み込み・パースエラー
 //! - [`ProviderError`] - LLMプロバイダー通信エラー（CLI版）
+//! - [`RetryClass`] - 再試行の対象となるエラーの種類
+//! - [`TelemetryError`] - テレメトリーのエクスポートエラー
+//! - [`CheckpointError`] - チェックポイントの保存・読み込みエラー
+//! - [`RunStoreError`] - 実行履歴の保存・読み込みエラー
+//! - [`CliError`] - `adw` コマンドのエラー
 
 use thiserror::Error;
 
+use crate::engine::ExecutionError;
+
 /// 設定関連のエラー
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -28,27 +35,35 @@ pub enum ConfigError {
     Validation(String),
 }
 
-/// LLMプロバイダー通信関連のエラー（CLI版）
+/// LLMプロバイダー通信関連のエラー
 ///
-/// CLIツール（`claude`, `codex`）を呼び出す際のエラーを表現します。
+/// CLIツール（`claude`, `codex`）や HTTP API を呼び出す際のエラーを表現します。
 #[derive(Debug, Error)]
 pub enum ProviderError {
     /// CLIツールが見つからない（未インストール）
     #[error("CLIツールが見つかりません: {0}。インストールしてください: npm install -g {1}")]
     CliNotFound(String, String), // (コマンド名, NPMパッケージ名)
 
-    /// 認証エラー（ログインが必要）
-    #[error("認証に失敗しました: {0}。'{1} login' を実行してください")]
-    AuthenticationError(String, String), // (エラー詳細, コマンド名)
+    /// 認証エラー（CLI はログインが必要、HTTP API は API キーが無効）
+    #[error("認証に失敗しました: {0}。{hint}", hint = authentication_hint(.1))]
+    AuthenticationError(String, String), // (エラー詳細, コマンド名。HTTP API 版は空)
 
     /// CLIコマンド実行エラー（終了コードが非0）
     #[error("CLIコマンド実行エラー: {0}")]
     CliExecutionError(String),
 
+    /// 登録されていないプロバイダー
+    #[error("登録されていないプロバイダーです: {0}")]
+    UnknownProvider(String),
+
     /// 不正なモデルティア指定
     #[error("不正なモデルティア: {0}")]
     InvalidModelTier(String),
 
+    /// モデル名の指定に対応していないクライアント
+    #[error("このプロバイダーはモデル名の指定に対応していません: {0}")]
+    UnsupportedModel(String),
+
     /// レート制限超過
     #[error("レート制限を超えました")]
     RateLimitExceeded,
@@ -72,4 +87,213 @@ pub enum ProviderError {
     /// UTF-8デコードエラー
     #[error("UTF-8デコードエラー: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
+
+    /// API キーの環境変数が未設定（HTTP API 版）
+    #[error("API キーが設定されていません。環境変数 {0} を設定してください")]
+    MissingApiKey(String), // (環境変数名)
+
+    /// HTTP API がエラーを返した（HTTP API 版）
+    #[error("API エラー (HTTP {0}): {1}")]
+    ApiError(u16, String), // (ステータスコード, エラーメッセージ)
+
+    /// HTTP リクエストの送受信エラー（HTTP API 版）
+    #[error("HTTP リクエストエラー: {0}")]
+    HttpError(#[from] reqwest::Error),
+}
+
+/// 認証エラーの対処方法（コマンド名が空なら HTTP API）
+fn authentication_hint(command: &str) -> String {
+    if command.is_empty() {
+        "API キーを確認してください".to_string()
+    } else {
+        format!("'{} login' を実行してください", command)
+    }
+}
+
+impl ProviderError {
+    /// 再試行で解消する見込みのあるエラーの種類
+    ///
+    /// CLI が見つからない・認証に失敗した等、同じ条件で再試行しても解消しないエラーは `None` です。
+    pub fn retry_class(&self) -> Option<RetryClass> {
+        match self {
+            ProviderError::RateLimitExceeded => Some(RetryClass::RateLimit),
+            ProviderError::Timeout(_) => Some(RetryClass::Timeout),
+            ProviderError::InvalidResponse(_) | ProviderError::JsonError(_) | ProviderError::Utf8Error(_) => {
+                Some(RetryClass::InvalidResponse)
+            }
+            ProviderError::CliExecutionError(_) | ProviderError::HttpError(_) => Some(RetryClass::Transient),
+            ProviderError::ApiError(status, _) if *status >= 500 => Some(RetryClass::Transient),
+            ProviderError::CliNotFound(..)
+            | ProviderError::AuthenticationError(..)
+            | ProviderError::UnknownProvider(_)
+            | ProviderError::InvalidModelTier(_)
+            | ProviderError::UnsupportedModel(_)
+            | ProviderError::ProcessError(_)
+            | ProviderError::MissingApiKey(_)
+            | ProviderError::ApiError(..) => None,
+        }
+    }
+
+    /// 再試行で解消する見込みのあるエラーか（[`retry_class`](Self::retry_class) が `Some`）
+    pub fn is_retryable(&self) -> bool {
+        self.retry_class().is_some()
+    }
+
+    /// フォールバック先のプロバイダーに切り替えるべきエラーか
+    ///
+    /// 同じプロバイダーで再試行しても解消しない、プロバイダーが使えない状態を表すエラーが対象です。
+    ///
+    /// - [`ProviderError::RateLimitExceeded`]
+    /// - [`ProviderError::CliNotFound`]
+    /// - [`ProviderError::AuthenticationError`]
+    /// - [`ProviderError::MissingApiKey`]
+    pub fn triggers_fallback(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::RateLimitExceeded
+                | ProviderError::CliNotFound(..)
+                | ProviderError::AuthenticationError(..)
+                | ProviderError::MissingApiKey(_)
+        )
+    }
+}
+
+/// 再試行の対象となるエラーの種類
+///
+/// ステップの `retry_on` で、どの種類のエラーを再試行するかを指定します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    /// レート制限（`rate_limit`）
+    RateLimit,
+    /// タイムアウト（`timeout`）
+    Timeout,
+    /// 不正な応答・出力がスキーマに一致しない（`invalid_response`）
+    InvalidResponse,
+    /// CLI の異常終了・HTTP 5xx・通信エラー等の一時的なエラー（`transient`）
+    Transient,
+}
+
+impl RetryClass {
+    /// すべての種類
+    pub const ALL: [RetryClass; 4] = [
+        RetryClass::RateLimit,
+        RetryClass::Timeout,
+        RetryClass::InvalidResponse,
+        RetryClass::Transient,
+    ];
+
+    /// `retry_on` での名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryClass::RateLimit => "rate_limit",
+            RetryClass::Timeout => "timeout",
+            RetryClass::InvalidResponse => "invalid_response",
+            RetryClass::Transient => "transient",
+        }
+    }
+}
+
+/// テレメトリー関連のエラー
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    /// 出力先への書き込みに失敗
+    #[error("テレメトリーの書き込みに失敗しました: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// JSONシリアライズに失敗
+    #[error("テレメトリーのJSON変換に失敗しました: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// DuckDB の操作に失敗
+    #[cfg(feature = "duckdb")]
+    #[error("テレメトリーデータベースの操作に失敗しました: {0}")]
+    DuckDb(#[from] duckdb::Error),
+}
+
+/// チェックポイント関連のエラー
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    /// 読み書きに失敗
+    #[error("チェックポイントの読み書きに失敗しました: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// JSON の変換に失敗
+    #[error("チェックポイントのJSON変換に失敗しました: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// 指定した実行のチェックポイントが存在しない
+    #[error("実行 '{0}' のチェックポイントが見つかりません")]
+    NotFound(String),
+
+    /// ワークフロー定義がチェックポイントを保存したときから互換性のない形で変更された
+    #[error("ワークフロー定義がチェックポイントと互換性がありません: {0}")]
+    Incompatible(String),
+}
+
+/// 実行履歴（実行ごとのディレクトリ）関連のエラー
+#[derive(Debug, Error)]
+pub enum RunStoreError {
+    /// 読み書きに失敗
+    #[error("実行履歴の読み書きに失敗しました: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// JSON の変換に失敗
+    #[error("実行履歴のJSON変換に失敗しました: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// ワークフロー定義の書き出しに失敗
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    /// チェックポイントの保存に失敗
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+
+    /// 指定した実行が存在しない
+    #[error("実行 '{0}' が見つかりません")]
+    NotFound(String),
+
+    /// 指定した実行の結果が保存されていない（実行中、または中断した実行）
+    #[error("実行 '{0}' の結果が保存されていません（実行中、または中断した実行です）")]
+    ResultMissing(String),
+}
+
+/// `adw` コマンド関連のエラー
+#[derive(Debug, Error)]
+pub enum CliError {
+    /// ワークフロー定義の読み込みエラー
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    /// ワークフロー実行エラー
+    #[error(transparent)]
+    Execution(#[from] ExecutionError),
+
+    /// 入力の読み込みエラー（`@file` や標準入力）
+    #[error("入力の読み込みに失敗しました: {0}")]
+    InputRead(#[from] std::io::Error),
+
+    /// 実行結果のJSON変換エラー
+    #[error("実行結果のJSON変換に失敗しました: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// テレメトリー出力先の準備エラー
+    #[error(transparent)]
+    Telemetry(#[from] TelemetryError),
+
+    /// チェックポイントの読み込みエラー（`adw resume`）
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+
+    /// 再開する実行のワークフロー定義ファイルが不明（チェックポイントに記録されていない）
+    #[error("実行 '{0}' のワークフロー定義ファイルが記録されていません。--workflow で指定してください")]
+    WorkflowPathUnknown(String),
+
+    /// 実行履歴の読み込みエラー（`adw runs`）
+    #[error(transparent)]
+    RunStore(#[from] RunStoreError),
+
+    /// 比較する2つの実行のワークフローが異なる（`adw runs diff`）
+    #[error("実行 '{0}'（ワークフロー '{1}'）と '{2}'（ワークフロー '{3}'）は異なるワークフローの実行のため比較できません")]
+    RunsNotComparable(String, String, String, String),
 }
@@ -2,3 +2,5 @@ pub mod config;
 pub mod error;
 pub mod provider;
 pub mod engine;
+pub mod cli;
+pub mod telemetry;
@@ -1,11 +1,6 @@
-mod cli;
-mod config;
-mod engine;
-mod error;
-mod provider;
-mod telemetry;
+use std::process::ExitCode;
 
-fn main() {
-    println!("Hello World");
+#[tokio::main]
+async fn main() -> ExitCode {
+    melted_adw::cli::run().await
 }
-
@@ -1,9 +1,9 @@
-//! LLMプロバイダー抽象化レイヤー（CLI版）
+//! LLMプロバイダー抽象化レイヤー
 //!
 //! # 責務
 //!
 //! - 複数のLLMプロバイダー（Anthropic, OpenAI等）を統一的に扱うインターフェースを提供
-//! - プロバイダーの種類に応じた適切なクライアントを生成するファクトリー機能
+//! - プロバイダー名とクライアントの生成方法を対応付ける登録簿（[`ProviderRegistry`]）
 //! - モデルティア（Heavy/Medium/Light）から実際のモデル名へのマッピング
 //!
 //! # アーキテクチャ
@@ -11,6 +11,9 @@
 //! このモジュールは **CLIツール呼び出しベース** で設計されています。
 //! APIキーの管理や認証はCLIツールに委譲し、コード内では扱いません。
 //!
+//! ツールを使わないステップ（要約・分類など）向けに、CLI を起動せず HTTP API を直接呼び出す
+//! クライアント（`anthropic-api`, `openai-api`）も提供します。こちらは API キーを環境変数から読み込みます。
+//!
 //! ## 使用するCLIツール
 //!
 //! - **Anthropic**: `claude` コマンド（Claude Code CLI）
@@ -25,8 +28,16 @@
 //!
 //! - `traits` - 共通インターフェース（[`ProviderClient`]トレイト等）
 //! - `model_tier` - モデルティアマッピング
+//! - `registry` - プロバイダーの登録簿
 //! - `anthropic` - Anthropic Claude Code CLI クライアント
 //! - `openai` - OpenAI Codex CLI クライアント
+//! - `anthropic_api` - Anthropic Messages API クライアント
+//! - `openai_api` - OpenAI Chat Completions API クライアント
+//! - `http` - HTTP API クライアントの共通処理（非公開）
+//! - `command` - 任意の CLI エージェントを宣言的な定義で呼び出すクライアント
+//! - `classify` - CLI のエラー出力の分類（全 CLI クライアントで共有）
+//! - `cache` - 任意のクライアントを包む応答キャッシュ
+//! - `stream` - CLI の JSONL 出力を逐次イベントに変換するストリーム（非公開）
 //!
 //! # 使用例
 //!
@@ -39,7 +50,7 @@
 //!     // 事前に `claude login` または環境変数設定が必要
 //!
 //!     // プロバイダークライアントを生成（APIキー不要）
-//!     let client = create_provider(&Provider::Anthropic)?;
+//!     let client = create_provider(&Provider::new("anthropic"))?;
 //!
 //!     // LLMを実行
 //!     let response = client.execute(
@@ -56,17 +67,31 @@
 pub mod traits;
 pub mod model_tier;
 pub mod anthropic;
+pub mod anthropic_api;
+pub mod cache;
+pub mod classify;
+pub mod command;
+mod http;
 pub mod openai;
+pub mod openai_api;
+pub mod registry;
+mod stream;
 
 // 公開APIの再エクスポート
-pub use traits::{ProviderClient, ProviderResponse, TokenUsage, StopReason};
+pub use traits::{
+    ProviderClient, ProviderEvent, ProviderEventStream, ProviderResponse, StopReason, SystemPromptChannel,
+    TokenUsage,
+};
+pub use registry::{ProviderConstructor, ProviderRegistry};
 
 use crate::config::step::Provider;
 use crate::error::ProviderError;
 
 /// プロバイダークライアントを生成するファクトリー関数
 ///
-/// 指定されたプロバイダーの種類に応じて、適切なCLIベースのクライアントを生成します。
+/// 組み込みのプロバイダー（[`ProviderRegistry::builtin`]）から、指定された名前の
+/// CLIベースのクライアントを生成します。独自のプロバイダーを使う場合は
+/// [`ProviderRegistry`] に登録してください。
 ///
 /// # 認証について
 ///
@@ -86,7 +111,7 @@ use crate::error::ProviderError;
 ///
 /// # 引数
 ///
-/// - `provider`: プロバイダーの種類（[Provider::Anthropic] または [Provider::OpenAI]）
+/// - `provider`: プロバイダー（`anthropic`, `openai`, `anthropic-api`, `openai-api`）
 ///
 /// # 戻り値
 ///
@@ -95,7 +120,7 @@ use crate::error::ProviderError;
 ///
 /// # エラー
 ///
-/// - [`ProviderError::CliNotFound`] - CLIツールが未インストール
+/// - [`ProviderError::UnknownProvider`] - 組み込みのプロバイダーではない
 ///
 /// # 例
 ///
@@ -107,13 +132,10 @@ use crate::error::ProviderError;
 /// // export ANTHROPIC_API_KEY="sk-ant-..."
 /// // または: claude (起動後 /login)
 ///
-/// let client = create_provider(&Provider::Anthropic).unwrap();
+/// let client = create_provider(&Provider::new(Provider::ANTHROPIC)).unwrap();
 /// ```
 pub fn create_provider(
     provider: &Provider,
 ) -> Result<Box<dyn ProviderClient>, ProviderError> {
-    match provider {
-        Provider::Anthropic => Ok(Box::new(anthropic::AnthropicClient::new())),
-        Provider::OpenAI => Ok(Box::new(openai::OpenAIClient::new())),
-    }
+    ProviderRegistry::builtin().create(provider)
 }
@@ -0,0 +1,371 @@
+//! Anthropic Messages API クライアント実装
+//!
+//! # 責務
+//!
+//! - Anthropic Messages API (`POST /v1/messages`) との HTTP 通信を担当
+//! - [`ProviderClient`] トレイトを実装し、統一インターフェースを提供
+//! - 停止理由（`stop_reason`）とトークン使用量を共通型に変換
+//!
+//! CLI を起動しないため、要約・分類のようなツールを使わないステップを低コストで実行できます。
+//!
+//! # 設定
+//!
+//! - **API キー**: 環境変数 `ANTHROPIC_API_KEY`
+//! - **ベース URL**: 環境変数 `ANTHROPIC_BASE_URL`（既定: `https://api.anthropic.com`）、
+//!   または [`AnthropicApiClient::with_base_url`]
+//! - **最大出力トークン数**: 環境変数 `ANTHROPIC_MAX_TOKENS`（既定: [`DEFAULT_MAX_TOKENS`]）、
+//!   または [`AnthropicApiClient::with_max_tokens`]。上限で打ち切られた応答は実行エンジンがステップの失敗とします
+//! - **タイムアウト**: 環境変数 `ANTHROPIC_TIMEOUT`（秒、既定: [`DEFAULT_TIMEOUT`]）、
+//!   または [`AnthropicApiClient::with_timeout`]
+//!
+//! # システムプロンプト
+//!
+//! システムプロンプトはリクエストの `system` で渡します（[`SystemPromptChannel::Native`]）。
+//!
+//! # 使用例
+//!
+//! ```rust,no_run
+//! use melted_adw::provider::anthropic_api::AnthropicApiClient;
+//! use melted_adw::provider::ProviderClient;
+//! use melted_adw::config::step::ModelTier;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = AnthropicApiClient::from_env().unwrap().with_max_tokens(1024);
+//!
+//!     let response = client.execute(
+//!         "Summarize in one sentence.",
+//!         "Rust is a systems programming language...",
+//!         &ModelTier::Light,
+//!     ).await.unwrap();
+//!
+//!     println!("{}", response.content);
+//! }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::config::step::ModelTier;
+use crate::error::ProviderError;
+use super::http::{
+    api_key_from_env, base_url_from_env, endpoint, http_client, max_tokens_from_env, send_json, timeout_from_env,
+};
+use super::model_tier::anthropic_api_model;
+use super::traits::{ProviderClient, ProviderResponse, StopReason, SystemPromptChannel, TokenUsage};
+
+/// 既定のベース URL
+pub const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+
+/// 既定の最大出力トークン数
+pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// 既定のリクエストのタイムアウト（送信から応答の受信完了まで）
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// API キーの環境変数名
+const API_KEY_ENV: &str = "ANTHROPIC_API_KEY";
+
+/// ベース URL の環境変数名
+const BASE_URL_ENV: &str = "ANTHROPIC_BASE_URL";
+
+/// 最大出力トークン数の環境変数名
+const MAX_TOKENS_ENV: &str = "ANTHROPIC_MAX_TOKENS";
+
+/// タイムアウトの環境変数名
+const TIMEOUT_ENV: &str = "ANTHROPIC_TIMEOUT";
+
+/// API バージョン（`anthropic-version` ヘッダー）
+const API_VERSION: &str = "2023-06-01";
+
+/// Anthropic Messages API クライアント
+#[derive(Clone)]
+pub struct AnthropicApiClient {
+    http: reqwest::Client,
+    api_key: String,
+    base_url: String,
+    max_tokens: u32,
+    timeout: Duration,
+}
+
+impl AnthropicApiClient {
+    /// API キーを指定してクライアントを生成
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            http: http_client(DEFAULT_TIMEOUT),
+            api_key: api_key.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// 環境変数（`ANTHROPIC_API_KEY`, `ANTHROPIC_BASE_URL`, `ANTHROPIC_MAX_TOKENS`, `ANTHROPIC_TIMEOUT`）からクライアントを生成
+    ///
+    /// # エラー
+    ///
+    /// - [`ProviderError::MissingApiKey`] - `ANTHROPIC_API_KEY` が未設定
+    pub fn from_env() -> Result<Self, ProviderError> {
+        let client = Self::new(api_key_from_env(API_KEY_ENV)?);
+        Ok(client
+            .with_base_url(base_url_from_env(BASE_URL_ENV, DEFAULT_BASE_URL))
+            .with_max_tokens(max_tokens_from_env(MAX_TOKENS_ENV, DEFAULT_MAX_TOKENS))
+            .with_timeout(timeout_from_env(TIMEOUT_ENV, DEFAULT_TIMEOUT)))
+    }
+
+    /// ベース URL を設定（プロキシやテスト用のサーバーを使用する場合）
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// 最大出力トークン数を設定
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// リクエストのタイムアウト（送信から応答の受信完了まで）を設定
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = http_client(timeout);
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl fmt::Debug for AnthropicApiClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // API キーは表示しない
+        f.debug_struct("AnthropicApiClient")
+            .field("base_url", &self.base_url)
+            .field("max_tokens", &self.max_tokens)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ProviderClient for AnthropicApiClient {
+    async fn execute(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.execute_with_model(system_prompt, user_input, anthropic_api_model(model_tier)).await
+    }
+
+    async fn execute_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let request = MessagesRequest {
+            model,
+            max_tokens: self.max_tokens,
+            system: (!system_prompt.is_empty()).then_some(system_prompt),
+            messages: vec![Message {
+                role: "user",
+                content: user_input,
+            }],
+        };
+
+        let builder = self
+            .http
+            .post(endpoint(&self.base_url, "/v1/messages"))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION);
+        let response: MessagesResponse = send_json(builder, &request).await?;
+
+        response.into_provider_response()
+    }
+
+    fn default_model(&self, model_tier: &ModelTier) -> Option<&str> {
+        Some(anthropic_api_model(model_tier))
+    }
+
+    fn system_prompt_channel(&self) -> SystemPromptChannel {
+        SystemPromptChannel::Native
+    }
+}
+
+/// リクエスト本文
+#[derive(Debug, Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    messages: Vec<Message<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+/// レスポンス本文
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    model: String,
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    usage: Usage,
+}
+
+/// コンテンツブロック（テキスト以外は応答に含めない）
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text { text: String },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    input_tokens: u32,
+    output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
+}
+
+impl MessagesResponse {
+    fn into_provider_response(self) -> Result<ProviderResponse, ProviderError> {
+        let content: String = self
+            .content
+            .into_iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } => Some(text),
+                ContentBlock::Other => None,
+            })
+            .collect();
+        if content.is_empty() {
+            return Err(ProviderError::InvalidResponse("No content in response".to_string()));
+        }
+
+        Ok(ProviderResponse {
+            content,
+            token_usage: TokenUsage {
+                input_tokens: self.usage.input_tokens,
+                output_tokens: self.usage.output_tokens,
+                cache_read_tokens: self.usage.cache_read_input_tokens,
+                cache_creation_tokens: self.usage.cache_creation_input_tokens,
+                reasoning_tokens: 0,
+            },
+            stop_reason: stop_reason(self.stop_reason.as_deref()),
+            model: self.model,
+            cached: false,
+        })
+    }
+}
+
+/// `stop_reason` を共通型に変換
+fn stop_reason(reason: Option<&str>) -> StopReason {
+    match reason {
+        Some("end_turn") => StopReason::EndTurn,
+        Some("max_tokens") => StopReason::MaxTokens,
+        Some("stop_sequence") => StopReason::StopSequence,
+        Some("refusal") => StopReason::ContentFilter,
+        _ => StopReason::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::http::test_server;
+
+    #[tokio::test]
+    async fn test_execute_sends_messages_request() {
+        let (base_url, server) = test_server::respond(
+            200,
+            r#"{"id":"msg_1","type":"message","role":"assistant","model":"claude-haiku-4-5",
+               "content":[{"type":"text","text":"Hello"},{"type":"text","text":", world"}],
+               "stop_reason":"max_tokens","usage":{"input_tokens":12,"output_tokens":5}}"#,
+        );
+        let client = AnthropicApiClient::new("test-key").with_base_url(base_url).with_max_tokens(5);
+
+        let response = client.execute("Be brief.", "Hi", &ModelTier::Light).await.unwrap();
+        assert_eq!(response.content, "Hello, world");
+        assert_eq!(response.stop_reason, StopReason::MaxTokens);
+        assert_eq!(response.token_usage.total(), 17);
+        assert_eq!(response.model, "claude-haiku-4-5");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /v1/messages "), "{}", request);
+        assert!(request.contains("x-api-key: test-key"));
+        assert!(request.contains("anthropic-version: 2023-06-01"));
+        let body: serde_json::Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["model"], "claude-haiku-4-5");
+        assert_eq!(body["max_tokens"], 5);
+        assert_eq!(body["system"], "Be brief.");
+        assert_eq!(body["messages"][0]["content"], "Hi");
+    }
+
+    #[tokio::test]
+    async fn test_execute_maps_error_status() {
+        let (base_url, server) = test_server::respond(
+            401,
+            r#"{"type":"error","error":{"type":"authentication_error","message":"invalid x-api-key"}}"#,
+        );
+        let client = AnthropicApiClient::new("wrong").with_base_url(base_url);
+
+        let result = client.execute("system", "input", &ModelTier::Light).await;
+        assert!(matches!(result, Err(ProviderError::AuthenticationError(message, _)) if message == "invalid x-api-key"));
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_times_out_on_stalled_server() {
+        // 接続は受け付けるが応答を返さないサーバー
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = AnthropicApiClient::new("test-key")
+            .with_base_url(format!("http://{}", listener.local_addr().unwrap()))
+            .with_timeout(Duration::from_millis(200));
+
+        let result = client.execute("system", "input", &ModelTier::Light).await;
+        assert!(matches!(result, Err(ProviderError::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_model_overrides_tier() {
+        let (base_url, server) = test_server::respond(
+            200,
+            r#"{"model":"claude-opus-4-1-20250805","content":[{"type":"text","text":"ok"}],
+               "stop_reason":"end_turn","usage":{"input_tokens":1,"output_tokens":1}}"#,
+        );
+        let client = AnthropicApiClient::new("test-key").with_base_url(base_url);
+        assert_eq!(client.default_model(&ModelTier::Heavy), Some("claude-opus-4-1"));
+
+        let response = client.execute_with_model("", "Hi", "claude-opus-4-1-20250805").await.unwrap();
+        assert_eq!(response.model, "claude-opus-4-1-20250805");
+
+        let request = server.join().unwrap();
+        let body: serde_json::Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["model"], "claude-opus-4-1-20250805");
+    }
+
+    #[test]
+    fn test_stop_reason() {
+        assert_eq!(stop_reason(Some("end_turn")), StopReason::EndTurn);
+        assert_eq!(stop_reason(Some("stop_sequence")), StopReason::StopSequence);
+        assert_eq!(stop_reason(Some("refusal")), StopReason::ContentFilter);
+        assert_eq!(stop_reason(Some("tool_use")), StopReason::Unknown);
+        assert_eq!(stop_reason(None), StopReason::Unknown);
+    }
+
+    #[test]
+    fn test_debug_hides_api_key() {
+        let client = AnthropicApiClient::new("secret-key");
+        assert!(!format!("{:?}", client).contains("secret-key"));
+        assert_eq!(client.system_prompt_channel(), SystemPromptChannel::Native);
+    }
+}
@@ -0,0 +1,425 @@
+//! 応答キャッシュ
+//!
+//! # 責務
+//!
+//! - プロバイダー名・解決済みのモデル名・システムプロンプト・ユーザー入力をキーに、
+//!   LLM の応答（[`ProviderResponse`]）をディスクに保存する（[`ResponseCache`]）
+//! - 任意の [`ProviderClient`] を包み、同じキーの呼び出しには保存した応答を返す（[`CachedClient`]）
+//!
+//! キャッシュから返した応答は [`ProviderResponse::cached`] が `true` で、トークン使用量は0です。
+//! 実行エンジンはこの応答のステップを [`StepResult::cache_hit`](crate::engine::StepResult::cache_hit) とし、
+//! コストを0として記録します。
+//!
+//! # 保存先
+//!
+//! キーのハッシュ値をファイル名として、キャッシュのディレクトリ直下に1応答1ファイルで保存します。
+//!
+//! ```text
+//! .adw/cache/
+//! ├── 3f2a91c0d4e5b6a7.json
+//! └── 91c03f2ab6a7d4e5.json
+//! ```
+//!
+//! 有効期限（[`ResponseCache::with_ttl`]）を過ぎた応答は使わず、次の呼び出しの応答で置き換えます。
+//! 失敗した呼び出しと、最大出力トークン数で打ち切られた応答（上限を上げて呼び出し直せるように）は保存しません。
+//! 一方、出力スキーマ（`output_schema`）に一致しない応答は保存するため、
+//! キャッシュを使う実行では同じ入力の再試行が同じ応答を返します。
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use futures::stream::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::config::step::ModelTier;
+use crate::error::ProviderError;
+use super::traits::{
+    ProviderClient, ProviderEvent, ProviderEventStream, ProviderResponse, StopReason, SystemPromptChannel,
+    TokenUsage, response_events,
+};
+
+/// 応答の保存先と有効期限
+///
+/// # 例
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use melted_adw::provider::cache::ResponseCache;
+///
+/// let cache = ResponseCache::new(".adw/cache").with_ttl(Duration::from_secs(24 * 60 * 60));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    /// 保存先のディレクトリ
+    dir: PathBuf,
+    /// 有効期限（`None` は無期限）
+    ttl: Option<Duration>,
+}
+
+/// キャッシュのキー
+///
+/// ハッシュ値が衝突しても別の呼び出しの応答を返さないよう、保存した応答と一緒に記録して読み込み時に照合します。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// プロバイダー名
+    pub provider: String,
+
+    /// 解決済みのモデル名（既定のモデル名が不明なクライアントは `tier:<ティア名>`）
+    pub model: String,
+
+    /// システムプロンプト
+    pub system_prompt: String,
+
+    /// ユーザー入力
+    pub user_input: String,
+}
+
+/// 保存する応答
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 応答のキー
+    key: CacheKey,
+    /// 保存した時刻
+    created_at: SystemTime,
+    /// 応答
+    response: ProviderResponse,
+}
+
+impl CacheKey {
+    /// キーのハッシュ値（保存するファイル名）
+    pub fn digest(&self) -> String {
+        let text = serde_json::to_string(self).unwrap_or_default();
+        crate::config::fingerprint(&text)
+    }
+}
+
+impl ResponseCache {
+    /// 保存先のディレクトリを指定して生成（有効期限なし）
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), ttl: None }
+    }
+
+    /// 有効期限を設定
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// 保存先のディレクトリを取得
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 保存した応答を取得
+    ///
+    /// 応答がない・有効期限を過ぎた・読み込めない場合は `None` です。
+    /// 返す応答は [`ProviderResponse::cached`] を `true`、トークン使用量を0とします。
+    pub fn get(&self, key: &CacheKey) -> Option<ProviderResponse> {
+        let path = self.path(key);
+        let text = std::fs::read_to_string(&path).ok()?;
+        let entry: CacheEntry = match serde_json::from_str(&text) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "応答キャッシュを読み込めません: {}", e);
+                return None;
+            }
+        };
+
+        // 保存時刻が未来の場合（時計の巻き戻り）は期限内として扱う
+        let expired = self
+            .ttl
+            .is_some_and(|ttl| entry.created_at.elapsed().is_ok_and(|elapsed| elapsed > ttl));
+        if entry.key != *key || expired {
+            return None;
+        }
+
+        Some(ProviderResponse {
+            token_usage: TokenUsage::default(),
+            cached: true,
+            ..entry.response
+        })
+    }
+
+    /// 応答を保存
+    ///
+    /// 最大出力トークン数で打ち切られた応答（[`StopReason::MaxTokens`]）は保存しません。
+    /// 保存の失敗は呼び出しの結果に影響させず、警告ログのみ出力します。
+    pub fn put(&self, key: &CacheKey, response: &ProviderResponse) {
+        if response.stop_reason == StopReason::MaxTokens {
+            return;
+        }
+        if let Err(e) = self.write(key, response) {
+            tracing::warn!(dir = %self.dir.display(), "応答キャッシュの保存に失敗しました: {}", e);
+        }
+    }
+
+    /// 応答をファイルに書き込む（一時ファイルに書き込んでから置き換える）
+    fn write(&self, key: &CacheKey, response: &ProviderResponse) -> std::io::Result<()> {
+        let entry = CacheEntry {
+            key: key.clone(),
+            created_at: SystemTime::now(),
+            response: response.clone(),
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let temporary = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&temporary, serde_json::to_string_pretty(&entry)?)?;
+        std::fs::rename(temporary, path)
+    }
+
+    /// 応答を保存するファイルのパス
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}.json", key.digest()))
+    }
+}
+
+/// 応答キャッシュを使うクライアント
+///
+/// 包んだクライアントを呼び出す前にキャッシュを確認し、保存した応答があればそれを返します。
+/// なければ包んだクライアントを呼び出し、成功した応答を保存します。
+///
+/// # 例
+///
+/// ```rust,no_run
+/// use melted_adw::provider::anthropic::AnthropicClient;
+/// use melted_adw::provider::cache::{CachedClient, ResponseCache};
+///
+/// let client = CachedClient::new(Box::new(AnthropicClient::new()), "anthropic", ResponseCache::new(".adw/cache"));
+/// ```
+pub struct CachedClient {
+    /// 包んだクライアント
+    inner: Box<dyn ProviderClient>,
+    /// キーに使うプロバイダー名
+    provider: String,
+    /// 応答の保存先
+    cache: ResponseCache,
+}
+
+impl CachedClient {
+    /// クライアントを包んで生成
+    ///
+    /// # 引数
+    ///
+    /// - `inner`: 包むクライアント
+    /// - `provider`: キーに使うプロバイダー名
+    /// - `cache`: 応答の保存先
+    pub fn new(inner: Box<dyn ProviderClient>, provider: impl Into<String>, cache: ResponseCache) -> Self {
+        Self {
+            inner,
+            provider: provider.into(),
+            cache,
+        }
+    }
+
+    /// 呼び出しのキーを生成
+    fn key(&self, system_prompt: &str, user_input: &str, model: String) -> CacheKey {
+        CacheKey {
+            provider: self.provider.clone(),
+            model,
+            system_prompt: system_prompt.to_string(),
+            user_input: user_input.to_string(),
+        }
+    }
+
+    /// モデルティアで呼び出す場合のモデル名（既定のモデル名が不明なクライアントはティア名）
+    fn tier_model(&self, model_tier: &ModelTier) -> String {
+        self.inner
+            .default_model(model_tier)
+            .map_or_else(|| format!("tier:{}", model_tier.as_str()), str::to_string)
+    }
+
+    /// ストリームの完了イベントの応答を保存するストリームに変換
+    fn store_completed(&self, events: ProviderEventStream, key: CacheKey) -> ProviderEventStream {
+        let cache = self.cache.clone();
+        events
+            .inspect(move |event| {
+                if let Ok(ProviderEvent::Completed(response)) = event {
+                    cache.put(&key, response);
+                }
+            })
+            .boxed()
+    }
+}
+
+#[async_trait]
+impl ProviderClient for CachedClient {
+    async fn execute(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let key = self.key(system_prompt, user_input, self.tier_model(model_tier));
+        if let Some(response) = self.cache.get(&key) {
+            return Ok(response);
+        }
+
+        let response = self.inner.execute(system_prompt, user_input, model_tier).await?;
+        self.cache.put(&key, &response);
+        Ok(response)
+    }
+
+    async fn execute_stream(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderEventStream, ProviderError> {
+        let key = self.key(system_prompt, user_input, self.tier_model(model_tier));
+        if let Some(response) = self.cache.get(&key) {
+            return Ok(response_events(response));
+        }
+
+        let events = self.inner.execute_stream(system_prompt, user_input, model_tier).await?;
+        Ok(self.store_completed(events, key))
+    }
+
+    async fn execute_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let key = self.key(system_prompt, user_input, model.to_string());
+        if let Some(response) = self.cache.get(&key) {
+            return Ok(response);
+        }
+
+        let response = self.inner.execute_with_model(system_prompt, user_input, model).await?;
+        self.cache.put(&key, &response);
+        Ok(response)
+    }
+
+    async fn execute_stream_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderEventStream, ProviderError> {
+        let key = self.key(system_prompt, user_input, model.to_string());
+        if let Some(response) = self.cache.get(&key) {
+            return Ok(response_events(response));
+        }
+
+        let events = self.inner.execute_stream_with_model(system_prompt, user_input, model).await?;
+        Ok(self.store_completed(events, key))
+    }
+
+    fn default_model(&self, model_tier: &ModelTier) -> Option<&str> {
+        self.inner.default_model(model_tier)
+    }
+
+    fn system_prompt_channel(&self) -> SystemPromptChannel {
+        self.inner.system_prompt_channel()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 呼び出し回数を数え、呼び出しごとに異なる応答を返すクライアント
+    #[derive(Clone, Default)]
+    struct CountingClient {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ProviderClient for CountingClient {
+        async fn execute(
+            &self,
+            _system_prompt: &str,
+            user_input: &str,
+            _model_tier: &ModelTier,
+        ) -> Result<ProviderResponse, ProviderError> {
+            if user_input == "fail" {
+                return Err(ProviderError::InvalidResponse("fail".to_string()));
+            }
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(ProviderResponse {
+                content: format!("response {}", call),
+                token_usage: TokenUsage { input_tokens: 10, output_tokens: 5, ..Default::default() },
+                stop_reason: if user_input == "long" { StopReason::MaxTokens } else { StopReason::EndTurn },
+                model: "counting-model".to_string(),
+                cached: false,
+            })
+        }
+
+        async fn execute_with_model(
+            &self,
+            system_prompt: &str,
+            user_input: &str,
+            _model: &str,
+        ) -> Result<ProviderResponse, ProviderError> {
+            self.execute(system_prompt, user_input, &ModelTier::Medium).await
+        }
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("adw-cache-test-{}-{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_reuses_responses() {
+        let dir = cache_dir("reuse");
+        let inner = CountingClient::default();
+        let client = CachedClient::new(Box::new(inner.clone()), "counting", ResponseCache::new(&dir));
+
+        let first = client.execute("system", "input", &ModelTier::Heavy).await.unwrap();
+        let second = client.execute("system", "input", &ModelTier::Heavy).await.unwrap();
+        assert!(!first.cached);
+        assert!(second.cached);
+        assert_eq!(second.content, first.content);
+        assert_eq!(second.token_usage, TokenUsage::default());
+        assert_eq!(second.model, "counting-model");
+
+        // プロンプト・モデルティア・モデル名が異なれば別のキー
+        client.execute("system", "other input", &ModelTier::Heavy).await.unwrap();
+        client.execute("other system", "input", &ModelTier::Heavy).await.unwrap();
+        client.execute("system", "input", &ModelTier::Light).await.unwrap();
+        client.execute_with_model("system", "input", "model-a").await.unwrap();
+        assert!(client.execute_with_model("system", "input", "model-a").await.unwrap().cached);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 5);
+
+        // ストリーミングの完了イベントも保存し、保存した応答を返す
+        let events: Vec<_> = client.execute_stream("stream", "input", &ModelTier::Heavy).await.unwrap().collect().await;
+        assert_eq!(events.len(), 3);
+        let cached = client.execute("stream", "input", &ModelTier::Heavy).await.unwrap();
+        assert!(cached.cached);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 6);
+
+        // 失敗した呼び出しと、打ち切られた応答は保存しない
+        assert!(client.execute("system", "fail", &ModelTier::Heavy).await.is_err());
+        client.execute("system", "long", &ModelTier::Heavy).await.unwrap();
+        assert!(!client.execute("system", "long", &ModelTier::Heavy).await.unwrap().cached);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 6);
+
+        // 別のプロバイダー名のキャッシュは使わない
+        let other = CachedClient::new(Box::new(inner.clone()), "other", ResponseCache::new(&dir));
+        assert!(!other.execute("system", "input", &ModelTier::Heavy).await.unwrap().cached);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cached_client_expires_responses() {
+        let dir = cache_dir("ttl");
+        let inner = CountingClient::default();
+        let client = CachedClient::new(
+            Box::new(inner.clone()),
+            "counting",
+            ResponseCache::new(&dir).with_ttl(Duration::from_millis(1)),
+        );
+
+        client.execute("system", "input", &ModelTier::Heavy).await.unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        let refreshed = client.execute("system", "input", &ModelTier::Heavy).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!refreshed.cached);
+        assert_eq!(refreshed.content, "response 1");
+    }
+}
@@ -0,0 +1,150 @@
+//! CLI のエラー出力の分類
+//!
+//! # 責務
+//!
+//! - 標準エラー出力のパターンと [`ProviderError`] の種類を対応付ける [`ErrorPattern`] を定義
+//! - パターンに一致したエラーを [`ProviderError`] に変換する（全 CLI クライアントで共有）
+//!
+//! 組み込みのクライアントは固定のパターンを、コマンドプロバイダーは
+//! `[providers.<name>]` の `errors` に記述したパターンを使用します。
+
+use regex::Regex;
+
+use crate::error::ProviderError;
+
+/// エラーの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 認証エラー（[`ProviderError::AuthenticationError`]）
+    Authentication,
+    /// レート制限（[`ProviderError::RateLimitExceeded`]）
+    RateLimit,
+    /// タイムアウト（[`ProviderError::Timeout`]）
+    Timeout,
+    /// その他の実行エラー（[`ProviderError::CliExecutionError`]）
+    Error,
+}
+
+impl ErrorKind {
+    /// 設定ファイルで使用する名前を取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::Authentication => "authentication",
+            ErrorKind::RateLimit => "rate_limit",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Error => "error",
+        }
+    }
+
+    /// 設定ファイルの名前から変換（大文字小文字を区別しない）
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_lowercase().as_str() {
+            "authentication" => Some(ErrorKind::Authentication),
+            "rate_limit" => Some(ErrorKind::RateLimit),
+            "timeout" => Some(ErrorKind::Timeout),
+            "error" => Some(ErrorKind::Error),
+            _ => None,
+        }
+    }
+
+    /// [`ProviderError`] に変換
+    ///
+    /// # 引数
+    ///
+    /// - `command`: CLI コマンド名（認証エラーのメッセージ用）
+    /// - `stderr`: 標準エラー出力
+    fn to_error(self, command: &str, stderr: &str) -> ProviderError {
+        match self {
+            ErrorKind::Authentication => {
+                ProviderError::AuthenticationError(stderr.to_string(), command.to_string())
+            }
+            ErrorKind::RateLimit => ProviderError::RateLimitExceeded,
+            ErrorKind::Timeout => ProviderError::Timeout(stderr.to_string()),
+            ErrorKind::Error => ProviderError::CliExecutionError(stderr.to_string()),
+        }
+    }
+}
+
+/// 標準エラー出力のパターンとエラーの種類の組
+#[derive(Debug, Clone)]
+pub struct ErrorPattern {
+    regex: Regex,
+    kind: ErrorKind,
+}
+
+impl ErrorPattern {
+    /// 新しいパターンを生成
+    pub fn new(regex: Regex, kind: ErrorKind) -> Self {
+        Self { regex, kind }
+    }
+
+    /// 正規表現を取得
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    /// エラーの種類を取得
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// 標準エラー出力を分類する
+///
+/// パターンを先頭から順に試し、最初に一致したパターンの種類のエラーを返します。
+///
+/// # 戻り値
+///
+/// 一致するパターンがなければ `None`
+pub fn classify(patterns: &[ErrorPattern], command: &str, stderr: &str) -> Option<ProviderError> {
+    patterns
+        .iter()
+        .find(|pattern| pattern.regex.is_match(stderr))
+        .map(|pattern| pattern.kind.to_error(command, stderr))
+}
+
+/// 組み込みのクライアント用のパターンを生成
+///
+/// # Panics
+///
+/// 正規表現が不正な場合（組み込みの定数のみを渡すこと）
+pub(super) fn builtin_patterns(rules: &[(&str, ErrorKind)]) -> Vec<ErrorPattern> {
+    rules
+        .iter()
+        .map(|(pattern, kind)| {
+            let regex = Regex::new(pattern).expect("組み込みのエラーパターンは正しい正規表現");
+            ErrorPattern::new(regex, *kind)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_uses_first_matching_pattern() {
+        let patterns = builtin_patterns(&[
+            ("(?i)unauthorized", ErrorKind::Authentication),
+            ("(?i)rate limit|429", ErrorKind::RateLimit),
+            ("(?i)timed? ?out", ErrorKind::Timeout),
+        ]);
+
+        assert!(matches!(
+            classify(&patterns, "agent", "401 Unauthorized"),
+            Some(ProviderError::AuthenticationError(_, command)) if command == "agent"
+        ));
+        assert!(matches!(classify(&patterns, "agent", "HTTP 429"), Some(ProviderError::RateLimitExceeded)));
+        assert!(matches!(classify(&patterns, "agent", "request timed out"), Some(ProviderError::Timeout(_))));
+        assert!(classify(&patterns, "agent", "something else").is_none());
+    }
+
+    #[test]
+    fn test_error_kind_names() {
+        for kind in [ErrorKind::Authentication, ErrorKind::RateLimit, ErrorKind::Timeout, ErrorKind::Error] {
+            assert_eq!(ErrorKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(ErrorKind::parse("Rate_Limit"), Some(ErrorKind::RateLimit));
+        assert_eq!(ErrorKind::parse("fatal"), None);
+    }
+}
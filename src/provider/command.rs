@@ -0,0 +1,503 @@
+//! コマンドプロバイダー（任意の CLI エージェント）のクライアント実装
+//!
+//! # 責務
+//!
+//! - ワークフローの `[providers.<name>]` で定義した CLI エージェント（[`CommandSpec`]）を呼び出す
+//! - 引数のプレースホルダー（`{prompt}`, `{system_prompt}`, `{model}`）を展開する
+//! - 出力形式（`raw` / `json` / `jsonl`）に従って応答を組み立てる
+//! - `jsonl` 形式の出力を逐次イベントに変換（[`ProviderClient::execute_stream`]）
+//! - 定義したエラーパターンで標準エラー出力を分類する
+//!
+//! # システムプロンプト
+//!
+//! `args` に `{system_prompt}` がある場合はその引数で渡します（[`SystemPromptChannel::Native`]）。
+//! ない場合はユーザー入力に結合してプロンプトとして渡します（[`SystemPromptChannel::Inline`]）。
+//!
+//! # 使用例
+//!
+//! ```rust,no_run
+//! use melted_adw::config::providers::ProviderDefinition;
+//! use melted_adw::config::workflow::Workflow;
+//! use melted_adw::provider::command::CommandProvider;
+//! use melted_adw::provider::ProviderClient;
+//! use melted_adw::config::step::ModelTier;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let workflow = Workflow::from_file("workflow.toml").unwrap();
+//!     let ProviderDefinition::Command(spec) = &workflow.providers()[0] else { return };
+//!     let client = CommandProvider::new(spec.clone());
+//!
+//!     let response = client.execute(
+//!         "You are a helpful assistant.",
+//!         "Hello!",
+//!         &ModelTier::Medium,
+//!     ).await.unwrap();
+//!
+//!     println!("{}", response.content);
+//! }
+//! ```
+
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, LazyLock};
+
+use async_trait::async_trait;
+use regex::{Captures, Regex};
+use serde_json::Value;
+use tokio::process::Command;
+
+use crate::config::command::{CommandSpec, OutputFormat, PromptInput};
+use crate::config::step::ModelTier;
+use crate::error::ProviderError;
+use super::classify::classify;
+use super::stream::{feed_stdin, spawn_jsonl_stream, JsonlParser};
+use super::traits::{
+    inline_system_prompt, response_events, ProviderClient, ProviderEvent, ProviderEventStream, ProviderResponse,
+    StopReason, SystemPromptChannel, TokenUsage,
+};
+
+/// 引数のプレースホルダー
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{(prompt|system_prompt|model)\}").expect("プレースホルダーは正しい正規表現")
+});
+
+/// コマンドプロバイダーのクライアント
+///
+/// ステップの実行ごとに定義（[`CommandSpec`]）どおりにコマンドを起動します。
+#[derive(Debug, Clone)]
+pub struct CommandProvider {
+    spec: Arc<CommandSpec>,
+}
+
+impl CommandProvider {
+    /// 定義からクライアントを生成
+    pub fn new(spec: CommandSpec) -> Self {
+        Self { spec: Arc::new(spec) }
+    }
+
+    /// 定義を取得
+    pub fn spec(&self) -> &CommandSpec {
+        &self.spec
+    }
+
+    /// コマンドを組み立てる
+    ///
+    /// # 戻り値
+    ///
+    /// コマンドと、標準入力に書き込むプロンプト（`prompt_input = "stdin"` の場合）
+    fn build_command(&self, system_prompt: &str, user_input: &str, model: &str) -> (Command, Option<String>) {
+        let prompt = if self.spec.has_system_prompt_arg() {
+            user_input.to_string()
+        } else {
+            inline_system_prompt(system_prompt, user_input)
+        };
+
+        let mut command = Command::new(self.spec.command());
+        command.args(self.build_args(&prompt, system_prompt, model));
+
+        match self.spec.prompt_input() {
+            PromptInput::Argv => {
+                if !self.spec.has_prompt_arg() {
+                    command.arg(&prompt);
+                }
+                (command, None)
+            }
+            PromptInput::Stdin => (command, Some(prompt)),
+        }
+    }
+
+    /// 引数のプレースホルダーを展開
+    ///
+    /// 展開後の値に含まれるプレースホルダーは展開しません（1回の置換で処理します）。
+    fn build_args(&self, prompt: &str, system_prompt: &str, model: &str) -> Vec<String> {
+        self.spec
+            .args()
+            .iter()
+            .map(|arg| {
+                PLACEHOLDER
+                    .replace_all(arg, |caps: &Captures| match &caps[1] {
+                        "prompt" => prompt.to_string(),
+                        "system_prompt" => system_prompt.to_string(),
+                        _ => model.to_string(),
+                    })
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    /// 標準エラー出力と終了コードからエラーを判定
+    ///
+    /// 定義したエラーパターンは終了コードに関わらず適用します。
+    fn check_exit(&self, status: ExitStatus, stderr: &str) -> Result<(), ProviderError> {
+        if let Some(error) = classify(self.spec.errors(), self.spec.command(), stderr) {
+            return Err(error);
+        }
+
+        if !status.success() {
+            let exit_code = status.code().unwrap_or(-1);
+            return Err(ProviderError::CliExecutionError(format!(
+                "{} exited with code {}: {}",
+                self.spec.command(),
+                exit_code,
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 標準出力全体から応答を組み立てる
+    fn parse_output(&self, stdout: &str, model: &str) -> Result<ProviderResponse, ProviderError> {
+        let output = self.spec.output();
+        let (content, token_usage) = match output.format() {
+            OutputFormat::Raw => (stdout.trim_end().to_string(), TokenUsage::default()),
+            OutputFormat::Json => {
+                let value: Value = serde_json::from_str(stdout)?;
+                let content = output.text().and_then(|pointer| text_at(&value, pointer)).unwrap_or_default();
+                (content, self.usage_at(&value, TokenUsage::default()))
+            }
+            OutputFormat::Jsonl => {
+                let mut parser = CommandJsonlParser::new(self.clone(), model);
+                for line in stdout.lines() {
+                    let line = line.trim();
+                    if !line.is_empty() {
+                        parser.parse_line(line)?;
+                    }
+                }
+                return parser.response();
+            }
+        };
+
+        response(content, token_usage, model)
+    }
+
+    /// JSON からトークン使用量を取り出す（ポインターが指す値がなければ `current` のまま）
+    fn usage_at(&self, value: &Value, current: TokenUsage) -> TokenUsage {
+        let output = self.spec.output();
+        let count = |pointer: Option<&str>| {
+            pointer
+                .and_then(|pointer| value.pointer(pointer))
+                .and_then(Value::as_u64)
+                .map(|count| u32::try_from(count).unwrap_or(u32::MAX))
+        };
+        TokenUsage {
+            input_tokens: count(output.input_tokens()).unwrap_or(current.input_tokens),
+            output_tokens: count(output.output_tokens()).unwrap_or(current.output_tokens),
+            ..current
+        }
+    }
+}
+
+#[async_trait]
+impl ProviderClient for CommandProvider {
+    async fn execute(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.execute_with_model(system_prompt, user_input, self.spec.model(model_tier)).await
+    }
+
+    async fn execute_stream(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderEventStream, ProviderError> {
+        self.execute_stream_with_model(system_prompt, user_input, self.spec.model(model_tier)).await
+    }
+
+    async fn execute_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let (mut command, stdin) = self.build_command(system_prompt, user_input, model);
+
+        let mut child = command
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        feed_stdin(&mut child, stdin);
+        let output = child.wait_with_output().await?;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        self.check_exit(output.status, &stderr)?;
+
+        let stdout = String::from_utf8(output.stdout)?;
+        self.parse_output(&stdout, model)
+    }
+
+    async fn execute_stream_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderEventStream, ProviderError> {
+        // 逐次イベントを取り出せるのは jsonl のみ
+        if self.spec.output().format() != OutputFormat::Jsonl {
+            let response = self.execute_with_model(system_prompt, user_input, model).await?;
+            return Ok(response_events(response));
+        }
+
+        let (command, stdin) = self.build_command(system_prompt, user_input, model);
+        spawn_jsonl_stream(command, CommandJsonlParser::new(self.clone(), model), stdin)
+    }
+
+    fn default_model(&self, model_tier: &ModelTier) -> Option<&str> {
+        Some(self.spec.model(model_tier))
+    }
+
+    fn system_prompt_channel(&self) -> SystemPromptChannel {
+        if self.spec.has_system_prompt_arg() {
+            SystemPromptChannel::Native
+        } else {
+            SystemPromptChannel::Inline
+        }
+    }
+}
+
+/// `jsonl` 形式の出力のパーサー
+///
+/// 一括パース（[`CommandProvider::execute`]）とストリーミングの両方で使用します。
+struct CommandJsonlParser {
+    client: CommandProvider,
+    model: String,
+    content: String,
+    token_usage: TokenUsage,
+}
+
+impl CommandJsonlParser {
+    fn new(client: CommandProvider, model: &str) -> Self {
+        Self {
+            client,
+            model: model.to_string(),
+            content: String::new(),
+            token_usage: TokenUsage::default(),
+        }
+    }
+
+    fn response(&mut self) -> Result<ProviderResponse, ProviderError> {
+        response(std::mem::take(&mut self.content), self.token_usage, &self.model)
+    }
+}
+
+impl JsonlParser for CommandJsonlParser {
+    fn parse_line(&mut self, line: &str) -> Result<Vec<ProviderEvent>, ProviderError> {
+        let value: Value = serde_json::from_str(line)
+            .map_err(|e| ProviderError::InvalidResponse(format!("JSONL parse error: {}: {}", e, line)))?;
+        let output = self.client.spec.output();
+
+        let mut events = Vec::new();
+        if let Some(name) = output.tool().and_then(|pointer| value.pointer(pointer)).and_then(Value::as_str) {
+            events.push(ProviderEvent::ToolCall {
+                name: name.to_string(),
+                input: value.clone(),
+            });
+        } else if let Some(text) = output.text().and_then(|pointer| text_at(&value, pointer)) {
+            self.content.push_str(&text);
+            events.push(ProviderEvent::TextDelta(text));
+        }
+
+        let token_usage = self.client.usage_at(&value, self.token_usage);
+        if token_usage != self.token_usage {
+            self.token_usage = token_usage;
+            events.push(ProviderEvent::Usage(token_usage));
+        }
+
+        Ok(events)
+    }
+
+    fn finish(&mut self, status: ExitStatus, stderr: &str) -> Result<ProviderResponse, ProviderError> {
+        self.client.check_exit(status, stderr)?;
+        self.response()
+    }
+}
+
+/// JSON ポインターが指すテキストを取り出す（文字列以外は JSON として表現）
+fn text_at(value: &Value, pointer: &str) -> Option<String> {
+    match value.pointer(pointer)? {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// 応答を組み立てる（本文が空の場合はエラー）
+fn response(content: String, token_usage: TokenUsage, model: &str) -> Result<ProviderResponse, ProviderError> {
+    if content.is_empty() {
+        return Err(ProviderError::InvalidResponse("No content in response".to_string()));
+    }
+    Ok(ProviderResponse {
+        content,
+        token_usage,
+        stop_reason: StopReason::Unknown,
+        model: model.to_string(),
+        cached: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::providers::ProviderDefinition;
+    use crate::config::workflow::Workflow;
+
+    /// `[providers.agent]` の定義からクライアントを生成
+    fn provider(definition: &str) -> CommandProvider {
+        let toml = format!(
+            r#"
+[workflow]
+name = "command"
+
+[providers.agent]
+{}
+
+[[steps]]
+name = "step"
+system_prompt = "Be brief."
+provider = "agent"
+model_tier = "heavy"
+"#,
+            definition
+        );
+        let workflow = Workflow::from_toml(&toml).unwrap();
+        match &workflow.providers()[0] {
+            ProviderDefinition::Command(spec) => CommandProvider::new(spec.clone()),
+            other => panic!("Expected Command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_args_expands_placeholders_once() {
+        let client = provider(
+            r#"command = "agent"
+args = ["--model={model}", "--system", "{system_prompt}", "{prompt}"]
+models = { heavy = "agent-large" }"#,
+        );
+        let args = client.build_args("say {model}", "Be brief.", client.spec().model(&ModelTier::Heavy));
+        assert_eq!(args, vec!["--model=agent-large", "--system", "Be brief.", "say {model}"]);
+        assert_eq!(client.system_prompt_channel(), SystemPromptChannel::Native);
+    }
+
+    #[test]
+    fn test_prompt_is_appended_when_no_placeholder() {
+        let client = provider(
+            r#"command = "agent"
+args = ["run"]"#,
+        );
+        let (command, stdin) = client.build_command("Be brief.", "Hello", "light");
+        let args: Vec<_> = command.as_std().get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+
+        assert_eq!(args, vec!["run".to_string(), inline_system_prompt("Be brief.", "Hello")]);
+        assert!(stdin.is_none());
+        assert_eq!(client.system_prompt_channel(), SystemPromptChannel::Inline);
+    }
+
+    #[test]
+    fn test_parse_json_output() {
+        let client = provider(
+            r#"command = "agent"
+output = { format = "json", text = "/result", input_tokens = "/usage/in", output_tokens = "/usage/out" }"#,
+        );
+        let response = client
+            .parse_output(r#"{"result":"done","usage":{"in":12,"out":3}}"#, "agent-large")
+            .unwrap();
+
+        assert_eq!(response.content, "done");
+        assert_eq!(response.token_usage.total(), 15);
+        assert_eq!(response.model, "agent-large");
+
+        let result = client.parse_output(r#"{"other":"value"}"#, "agent-large");
+        assert!(matches!(result, Err(ProviderError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_jsonl_parser_emits_events() {
+        let client = provider(
+            r#"command = "agent"
+output = { format = "jsonl", text = "/delta", tool = "/tool", output_tokens = "/usage/out" }"#,
+        );
+        let mut parser = CommandJsonlParser::new(client, "agent-large");
+
+        let events = parser.parse_line(r#"{"delta":"Hello, "}"#).unwrap();
+        assert!(matches!(&events[..], [ProviderEvent::TextDelta(text)] if text == "Hello, "));
+
+        let events = parser.parse_line(r#"{"tool":"shell","args":["ls"]}"#).unwrap();
+        assert!(matches!(&events[..], [ProviderEvent::ToolCall { name, input }]
+            if name == "shell" && input["args"][0] == "ls"));
+
+        let events = parser.parse_line(r#"{"delta":"world","usage":{"out":2}}"#).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[1], ProviderEvent::Usage(usage) if usage.output_tokens == 2));
+
+        let response = parser.response().unwrap();
+        assert_eq!(response.content, "Hello, world");
+        assert_eq!(response.stop_reason, StopReason::Unknown);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_passes_prompt_on_stdin() {
+        let client = provider(
+            r#"command = "sh"
+args = ["-c", "cat; echo ' ({model})'"]
+prompt_input = "stdin"
+models = { heavy = "agent-large" }"#,
+        );
+        let response = client.execute("Be brief.", "Hello", &ModelTier::Heavy).await.unwrap();
+
+        assert_eq!(
+            response.content,
+            format!("{} (agent-large)", inline_system_prompt("Be brief.", "Hello"))
+        );
+        assert_eq!(response.model, "agent-large");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_classifies_stderr() {
+        let client = provider(
+            r#"command = "sh"
+args = ["-c", "echo 'HTTP 429 slow down' >&2; exit 1"]
+errors = [{ pattern = "429", kind = "rate_limit" }]"#,
+        );
+        let result = client.execute("system", "input", &ModelTier::Light).await;
+        assert!(matches!(result, Err(ProviderError::RateLimitExceeded)));
+
+        let client = provider(
+            r#"command = "sh"
+args = ["-c", "echo boom >&2; exit 3"]"#,
+        );
+        let result = client.execute("system", "input", &ModelTier::Light).await;
+        assert!(matches!(result, Err(ProviderError::CliExecutionError(message))
+            if message.contains("exited with code 3") && message.contains("boom")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_stream_reads_jsonl_incrementally() {
+        use futures::StreamExt;
+
+        let client = provider(
+            r#"command = "sh"
+args = ["-c", "echo '{\"text\":\"Hi\"}'; echo '{\"name\":\"edit\"}'"]
+output = { format = "jsonl", text = "/text", tool = "/name" }"#,
+        );
+        let events: Vec<ProviderEvent> = client
+            .execute_stream("system", "input", &ModelTier::Light)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[1], ProviderEvent::ToolCall { name, .. } if name == "edit"));
+        assert!(matches!(&events[2], ProviderEvent::Completed(response) if response.content == "Hi"));
+    }
+}
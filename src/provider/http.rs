@@ -0,0 +1,286 @@
+//! HTTP API クライアントの共通処理
+//!
+//! # 責務
+//!
+//! - タイムアウトを設定した HTTP クライアントの生成
+//! - JSON リクエストの送信と JSON レスポンスの受信
+//! - HTTP ステータスとエラーレスポンスを [`ProviderError`] に変換
+//! - 環境変数からの API キー・ベース URL・最大出力トークン数・タイムアウトの読み込み
+//!
+//! Anthropic Messages API・OpenAI Chat Completions API のどちらも、エラーレスポンスは
+//! `{"error": {"message": "..."}}` の形式です。
+
+use std::time::Duration;
+
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::error::ProviderError;
+
+/// 接続のタイムアウト
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// タイムアウトを設定した HTTP クライアントを生成
+///
+/// `timeout` は送信から応答の受信完了までの上限です。接続が確立しない場合は
+/// 10秒（`timeout` の方が短ければ `timeout`）で打ち切ります。
+pub(super) fn http_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT.min(timeout))
+        .timeout(timeout)
+        .build()
+        .expect("HTTP クライアントの初期化に失敗しました")
+}
+
+/// JSON リクエストを送信し、JSON レスポンスを受け取る
+///
+/// # エラー
+///
+/// - [`ProviderError::AuthenticationError`] - HTTP 401 / 403
+/// - [`ProviderError::RateLimitExceeded`] - HTTP 429
+/// - [`ProviderError::Timeout`] - HTTP 408 / 504、または送受信のタイムアウト
+/// - [`ProviderError::ApiError`] - その他の成功以外のステータス
+/// - [`ProviderError::HttpError`] - 接続できない等
+/// - [`ProviderError::JsonError`] - レスポンスが期待する形式でない
+pub(super) async fn send_json<T: DeserializeOwned>(
+    request: RequestBuilder,
+    body: &impl serde::Serialize,
+) -> Result<T, ProviderError> {
+    let response = request
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_vec(body)?)
+        .send()
+        .await
+        .map_err(transport_error)?;
+
+    let status = response.status();
+    let bytes = response.bytes().await.map_err(transport_error)?;
+
+    if !status.is_success() {
+        return Err(status_error(status, &String::from_utf8_lossy(&bytes)));
+    }
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// 送受信のエラーを変換
+fn transport_error(error: reqwest::Error) -> ProviderError {
+    if error.is_timeout() {
+        ProviderError::Timeout(error.to_string())
+    } else {
+        ProviderError::HttpError(error)
+    }
+}
+
+/// 成功以外のステータスを変換
+fn status_error(status: StatusCode, body: &str) -> ProviderError {
+    let message = serde_json::from_str::<ErrorResponse>(body)
+        .map(|response| response.error.message)
+        .unwrap_or_else(|_| body.trim().to_string());
+
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ProviderError::AuthenticationError(message, String::new()),
+        StatusCode::TOO_MANY_REQUESTS => ProviderError::RateLimitExceeded,
+        StatusCode::REQUEST_TIMEOUT | StatusCode::GATEWAY_TIMEOUT => ProviderError::Timeout(message),
+        _ => ProviderError::ApiError(status.as_u16(), message),
+    }
+}
+
+/// 環境変数から API キーを読み込む
+///
+/// # エラー
+///
+/// - [`ProviderError::MissingApiKey`] - 環境変数が未設定または空
+pub(super) fn api_key_from_env(name: &str) -> Result<String, ProviderError> {
+    match std::env::var(name) {
+        Ok(key) if !key.trim().is_empty() => Ok(key),
+        _ => Err(ProviderError::MissingApiKey(name.to_string())),
+    }
+}
+
+/// 環境変数からベース URL を読み込む（未設定なら `default`）
+pub(super) fn base_url_from_env(name: &str, default: &str) -> String {
+    std::env::var(name)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+/// 環境変数から最大出力トークン数を読み込む（未設定なら `default`）
+///
+/// 1以上の整数でない値は警告ログを出力して `default` を使います。
+pub(super) fn max_tokens_from_env(name: &str, default: u32) -> u32 {
+    parse_max_tokens(name, std::env::var(name).ok().as_deref(), default)
+}
+
+/// 最大出力トークン数の値を変換（`name` は警告ログに使う環境変数名）
+fn parse_max_tokens(name: &str, value: Option<&str>, default: u32) -> u32 {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return default;
+    };
+    match value.parse::<u32>() {
+        Ok(max_tokens) if max_tokens > 0 => max_tokens,
+        _ => {
+            tracing::warn!("{} は1以上の整数を指定してください（'{}'）。{} を使います", name, value, default);
+            default
+        }
+    }
+}
+
+/// 環境変数からリクエストのタイムアウト（秒）を読み込む（未設定なら `default`）
+///
+/// 0より大きい秒数でない値は警告ログを出力して `default` を使います。
+pub(super) fn timeout_from_env(name: &str, default: Duration) -> Duration {
+    parse_timeout(name, std::env::var(name).ok().as_deref(), default)
+}
+
+/// タイムアウトの値を変換（`name` は警告ログに使う環境変数名）
+fn parse_timeout(name: &str, value: Option<&str>, default: Duration) -> Duration {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return default;
+    };
+    match value.parse::<f64>().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()) {
+        Some(timeout) if !timeout.is_zero() => timeout,
+        _ => {
+            tracing::warn!(
+                "{} は0より大きい秒数を指定してください（'{}'）。{}秒を使います",
+                name,
+                value,
+                default.as_secs_f64()
+            );
+            default
+        }
+    }
+}
+
+/// ベース URL とパスを結合（ベース URL 末尾の `/` は無視）
+pub(super) fn endpoint(base_url: &str, path: &str) -> String {
+    format!("{}{}", base_url.trim_end_matches('/'), path)
+}
+
+/// エラーレスポンス
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorDetail {
+    message: String,
+}
+
+/// テスト用の HTTP サーバー
+///
+/// 1回だけリクエストを受け付け、決まったレスポンスを返します。
+#[cfg(test)]
+pub(super) mod test_server {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// サーバーを起動する
+    ///
+    /// # 戻り値
+    ///
+    /// ベース URL（`http://127.0.0.1:<port>`）と、受け取ったリクエスト（ヘッダーと本文）を返すスレッド
+    pub(in crate::provider) fn respond(status: u16, body: &str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let body = body.to_string();
+
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            request.push_str(&String::from_utf8(request_body).unwrap());
+
+            let response = format!(
+                "HTTP/1.1 {} Test\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            request
+        });
+
+        (base_url, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_error() {
+        let body = r#"{"type":"error","error":{"type":"invalid_request_error","message":"bad model"}}"#;
+        assert!(matches!(
+            status_error(StatusCode::BAD_REQUEST, body),
+            ProviderError::ApiError(400, message) if message == "bad model"
+        ));
+        assert!(matches!(
+            status_error(StatusCode::UNAUTHORIZED, r#"{"error":{"message":"invalid x-api-key"}}"#),
+            ProviderError::AuthenticationError(message, _) if message == "invalid x-api-key"
+        ));
+        assert!(matches!(
+            status_error(StatusCode::FORBIDDEN, "key revoked"),
+            ProviderError::AuthenticationError(message, _) if message == "key revoked"
+        ));
+        assert!(status_error(StatusCode::UNAUTHORIZED, "").triggers_fallback());
+        assert!(matches!(
+            status_error(StatusCode::TOO_MANY_REQUESTS, ""),
+            ProviderError::RateLimitExceeded
+        ));
+        assert!(matches!(
+            status_error(StatusCode::GATEWAY_TIMEOUT, "upstream timed out"),
+            ProviderError::Timeout(message) if message == "upstream timed out"
+        ));
+    }
+
+    #[test]
+    fn test_parse_max_tokens() {
+        assert_eq!(parse_max_tokens("MAX_TOKENS", Some("16000"), 4096), 16000);
+        assert_eq!(parse_max_tokens("MAX_TOKENS", Some(" 8192 "), 4096), 8192);
+        assert_eq!(parse_max_tokens("MAX_TOKENS", None, 4096), 4096);
+        assert_eq!(parse_max_tokens("MAX_TOKENS", Some(""), 4096), 4096);
+        assert_eq!(parse_max_tokens("MAX_TOKENS", Some("0"), 4096), 4096);
+        assert_eq!(parse_max_tokens("MAX_TOKENS", Some("lots"), 4096), 4096);
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        let default = Duration::from_secs(600);
+        assert_eq!(parse_timeout("TIMEOUT", Some("120"), default), Duration::from_secs(120));
+        assert_eq!(parse_timeout("TIMEOUT", Some(" 1.5 "), default), Duration::from_millis(1500));
+        assert_eq!(parse_timeout("TIMEOUT", None, default), default);
+        assert_eq!(parse_timeout("TIMEOUT", Some(""), default), default);
+        assert_eq!(parse_timeout("TIMEOUT", Some("0"), default), default);
+        assert_eq!(parse_timeout("TIMEOUT", Some("-1"), default), default);
+        assert_eq!(parse_timeout("TIMEOUT", Some("1e20"), default), default);
+        assert_eq!(parse_timeout("TIMEOUT", Some("soon"), default), default);
+    }
+
+    #[test]
+    fn test_endpoint() {
+        assert_eq!(endpoint("http://localhost:11434/v1/", "/chat/completions"), "http://localhost:11434/v1/chat/completions");
+        assert_eq!(endpoint("https://api.anthropic.com", "/v1/messages"), "https://api.anthropic.com/v1/messages");
+    }
+}
@@ -3,19 +3,24 @@
 //! # 責務
 //!
 //! - [`ModelTier`] と [`Provider`] の組み合わせから、実際のモデル名を解決
-//! - プロバイダー別のモデル名定数を管理
+//! - 組み込みのプロバイダー別のモデル名定数を管理
+//!
+//! 登録簿で追加したプロバイダーのモデル名は、各クライアントが解決します。
+//!
+//! ここで定める名前は組み込みの既定値です。ワークフロー定義・ユーザー設定の `[models.<provider>]` や
+//! ステップの `model` で上書きできます（[`select_model`](crate::config::models::select_model)）。
 //!
 //! # マッピング表
 //!
-//! | Tier   | Anthropic       | OpenAI      |
-//! |--------|----------------|-------------|
-//! | Heavy  | claude-opus-4  | o1          |
-//! | Medium | claude-sonnet-4-5 | gpt-4o   |
-//! | Light  | claude-haiku   | gpt-4o-mini |
+//! | Tier   | Anthropic       | Anthropic API     | OpenAI / OpenAI API |
+//! |--------|----------------|-------------------|---------------------|
+//! | Heavy  | claude-opus-4  | claude-opus-4-1   | o1                  |
+//! | Medium | claude-sonnet-4-5 | claude-sonnet-4-5 | gpt-4o           |
+//! | Light  | claude-haiku   | claude-haiku-4-5  | gpt-4o-mini         |
 //!
 //! # 注意
 //!
-//! モデル名はCLIツールが受け付ける形式に合わせています。
+//! モデル名はCLIツール・APIが受け付ける形式に合わせています（Messages API は CLI の短縮名を受け付けません）。
 //! CLIツールのバージョンやAPI仕様の変更により、モデル名が変わる可能性があります。
 //!
 //! # 使用例
@@ -24,8 +29,8 @@
 //! use melted_adw::provider::model_tier::resolve_model;
 //! use melted_adw::config::step::{Provider, ModelTier};
 //!
-//! let model = resolve_model(&Provider::Anthropic, &ModelTier::Medium);
-//! assert_eq!(model, "claude-sonnet-4-5");
+//! let model = resolve_model(&Provider::new(Provider::ANTHROPIC), &ModelTier::Medium);
+//! assert_eq!(model, Some("claude-sonnet-4-5"));
 //! ```
 
 use crate::config::step::{Provider, ModelTier};
@@ -35,6 +40,11 @@ const ANTHROPIC_HEAVY: &str = "claude-opus-4";
 const ANTHROPIC_MEDIUM: &str = "claude-sonnet-4-5";
 const ANTHROPIC_LIGHT: &str = "claude-haiku";
 
+// Anthropic Messages API モデル名定数
+const ANTHROPIC_API_HEAVY: &str = "claude-opus-4-1";
+const ANTHROPIC_API_MEDIUM: &str = "claude-sonnet-4-5";
+const ANTHROPIC_API_LIGHT: &str = "claude-haiku-4-5";
+
 // OpenAI モデル名定数
 const OPENAI_HEAVY: &str = "o1";
 const OPENAI_MEDIUM: &str = "gpt-4o";
@@ -44,12 +54,13 @@ const OPENAI_LIGHT: &str = "gpt-4o-mini";
 ///
 /// # 引数
 ///
-/// - `provider`: プロバイダーの種類（Anthropic or OpenAI）
+/// - `provider`: プロバイダー
 /// - `tier`: モデルティア（Heavy/Medium/Light）
 ///
 /// # 戻り値
 ///
-/// モデル名の文字列スライス（'static ライフタイム）
+/// モデル名の文字列スライス（'static ライフタイム）。
+/// 組み込みのプロバイダー（`anthropic`, `openai`, `anthropic-api`, `openai-api`）以外は `None` です。
 ///
 /// # 例
 ///
@@ -59,27 +70,52 @@ const OPENAI_LIGHT: &str = "gpt-4o-mini";
 ///
 /// // Anthropic Medium -> claude-sonnet-4-5
 /// assert_eq!(
-///     resolve_model(&Provider::Anthropic, &ModelTier::Medium),
-///     "claude-sonnet-4-5"
+///     resolve_model(&Provider::new("anthropic"), &ModelTier::Medium),
+///     Some("claude-sonnet-4-5")
 /// );
 ///
 /// // OpenAI Heavy -> o1
 /// assert_eq!(
-///     resolve_model(&Provider::OpenAI, &ModelTier::Heavy),
-///     "o1"
+///     resolve_model(&Provider::new("openai"), &ModelTier::Heavy),
+///     Some("o1")
 /// );
+///
+/// // 登録簿で追加したプロバイダー
+/// assert_eq!(resolve_model(&Provider::new("gemini"), &ModelTier::Light), None);
 /// ```
-pub fn resolve_model(provider: &Provider, tier: &ModelTier) -> &'static str {
-    match (provider, tier) {
-        // Anthropic
-        (Provider::Anthropic, ModelTier::Heavy) => ANTHROPIC_HEAVY,
-        (Provider::Anthropic, ModelTier::Medium) => ANTHROPIC_MEDIUM,
-        (Provider::Anthropic, ModelTier::Light) => ANTHROPIC_LIGHT,
+pub fn resolve_model(provider: &Provider, tier: &ModelTier) -> Option<&'static str> {
+    match provider.as_str() {
+        Provider::ANTHROPIC => Some(anthropic_model(tier)),
+        Provider::OPENAI | Provider::OPENAI_API => Some(openai_model(tier)),
+        Provider::ANTHROPIC_API => Some(anthropic_api_model(tier)),
+        _ => None,
+    }
+}
 
-        // OpenAI
-        (Provider::OpenAI, ModelTier::Heavy) => OPENAI_HEAVY,
-        (Provider::OpenAI, ModelTier::Medium) => OPENAI_MEDIUM,
-        (Provider::OpenAI, ModelTier::Light) => OPENAI_LIGHT,
+/// Anthropic のモデル名を解決する
+pub fn anthropic_model(tier: &ModelTier) -> &'static str {
+    match tier {
+        ModelTier::Heavy => ANTHROPIC_HEAVY,
+        ModelTier::Medium => ANTHROPIC_MEDIUM,
+        ModelTier::Light => ANTHROPIC_LIGHT,
+    }
+}
+
+/// Anthropic Messages API のモデル名を解決する
+pub fn anthropic_api_model(tier: &ModelTier) -> &'static str {
+    match tier {
+        ModelTier::Heavy => ANTHROPIC_API_HEAVY,
+        ModelTier::Medium => ANTHROPIC_API_MEDIUM,
+        ModelTier::Light => ANTHROPIC_API_LIGHT,
+    }
+}
+
+/// OpenAI のモデル名を解決する
+pub fn openai_model(tier: &ModelTier) -> &'static str {
+    match tier {
+        ModelTier::Heavy => OPENAI_HEAVY,
+        ModelTier::Medium => OPENAI_MEDIUM,
+        ModelTier::Light => OPENAI_LIGHT,
     }
 }
 
@@ -89,29 +125,36 @@ mod tests {
 
     #[test]
     fn test_anthropic_models() {
-        assert_eq!(resolve_model(&Provider::Anthropic, &ModelTier::Heavy), "claude-opus-4");
-        assert_eq!(resolve_model(&Provider::Anthropic, &ModelTier::Medium), "claude-sonnet-4-5");
-        assert_eq!(resolve_model(&Provider::Anthropic, &ModelTier::Light), "claude-haiku");
+        assert_eq!(anthropic_model(&ModelTier::Heavy), "claude-opus-4");
+        assert_eq!(anthropic_model(&ModelTier::Medium), "claude-sonnet-4-5");
+        assert_eq!(anthropic_model(&ModelTier::Light), "claude-haiku");
     }
 
     #[test]
     fn test_openai_models() {
-        assert_eq!(resolve_model(&Provider::OpenAI, &ModelTier::Heavy), "o1");
-        assert_eq!(resolve_model(&Provider::OpenAI, &ModelTier::Medium), "gpt-4o");
-        assert_eq!(resolve_model(&Provider::OpenAI, &ModelTier::Light), "gpt-4o-mini");
+        assert_eq!(openai_model(&ModelTier::Heavy), "o1");
+        assert_eq!(openai_model(&ModelTier::Medium), "gpt-4o");
+        assert_eq!(openai_model(&ModelTier::Light), "gpt-4o-mini");
     }
 
     #[test]
     fn test_all_combinations() {
-        // すべての組み合わせが正しくマッピングされることを確認
-        let providers = [Provider::Anthropic, Provider::OpenAI];
+        // 組み込みのプロバイダーはすべての組み合わせが正しくマッピングされることを確認
+        let providers = [
+            Provider::new(Provider::ANTHROPIC),
+            Provider::new(Provider::OPENAI),
+            Provider::new(Provider::ANTHROPIC_API),
+            Provider::new(Provider::OPENAI_API),
+        ];
         let tiers = [ModelTier::Heavy, ModelTier::Medium, ModelTier::Light];
 
         for provider in &providers {
             for tier in &tiers {
                 let model = resolve_model(provider, tier);
-                assert!(!model.is_empty(), "Model name should not be empty");
+                assert!(model.is_some_and(|model| !model.is_empty()), "Model name should not be empty");
             }
         }
+
+        assert_eq!(resolve_model(&Provider::new("aider"), &ModelTier::Heavy), None);
     }
 }
@@ -0,0 +1,453 @@
+//! OpenAI Chat Completions API クライアント実装
+//!
+//! # 責務
+//!
+//! - OpenAI Chat Completions API (`POST /chat/completions`) との HTTP 通信を担当
+//! - [`ProviderClient`] トレイトを実装し、統一インターフェースを提供
+//! - 終了理由（`finish_reason`）とトークン使用量を共通型に変換
+//!
+//! CLI を起動しないため、要約・分類のようなツールを使わないステップを低コストで実行できます。
+//! OpenAI 互換の API（Ollama・llama.cpp server 等）にも [`OpenAIApiClient::compatible`] で使用できます。
+//!
+//! # 設定
+//!
+//! - **API キー**: 環境変数 `OPENAI_API_KEY`
+//! - **ベース URL**: 環境変数 `OPENAI_BASE_URL`（既定: `https://api.openai.com/v1`）、
+//!   または [`OpenAIApiClient::with_base_url`]
+//! - **最大出力トークン数**: 環境変数 `OPENAI_MAX_TOKENS`（既定: [`DEFAULT_MAX_TOKENS`]）、
+//!   または [`OpenAIApiClient::with_max_tokens`]。上限で打ち切られた応答は実行エンジンがステップの失敗とします
+//! - **タイムアウト**: 環境変数 `OPENAI_TIMEOUT`（秒、既定: [`DEFAULT_TIMEOUT`]）、
+//!   または [`OpenAIApiClient::with_timeout`]
+//!
+//! # システムプロンプト
+//!
+//! システムプロンプトは `system` ロールのメッセージで渡します（[`SystemPromptChannel::Native`]）。
+//!
+//! # 使用例
+//!
+//! ```rust,no_run
+//! use melted_adw::provider::openai_api::OpenAIApiClient;
+//! use melted_adw::provider::ProviderClient;
+//! use melted_adw::config::step::ModelTier;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = OpenAIApiClient::from_env().unwrap();
+//!
+//!     let response = client.execute(
+//!         "Classify the message as bug or feature.",
+//!         "The app crashes on startup.",
+//!         &ModelTier::Light,
+//!     ).await.unwrap();
+//!
+//!     println!("{}", response.content);
+//! }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::config::providers::{OpenAiCompatibleSpec, TierModels};
+use crate::config::step::ModelTier;
+use crate::error::ProviderError;
+use super::http::{
+    api_key_from_env, base_url_from_env, endpoint, http_client, max_tokens_from_env, send_json, timeout_from_env,
+};
+use super::model_tier::openai_model;
+use super::traits::{ProviderClient, ProviderResponse, StopReason, SystemPromptChannel, TokenUsage};
+
+/// 既定のベース URL
+pub const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// 既定の最大出力トークン数
+pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// 既定のリクエストのタイムアウト（送信から応答の受信完了まで）
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// API キーの環境変数名
+const API_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// ベース URL の環境変数名
+const BASE_URL_ENV: &str = "OPENAI_BASE_URL";
+
+/// 最大出力トークン数の環境変数名
+const MAX_TOKENS_ENV: &str = "OPENAI_MAX_TOKENS";
+
+/// タイムアウトの環境変数名
+const TIMEOUT_ENV: &str = "OPENAI_TIMEOUT";
+
+/// OpenAI Chat Completions API クライアント
+#[derive(Clone)]
+pub struct OpenAIApiClient {
+    http: reqwest::Client,
+    /// API キー（`None` の場合は `Authorization` ヘッダーを送らない）
+    api_key: Option<String>,
+    base_url: String,
+    /// 最大出力トークン数（`None` の場合はサーバーの既定値）
+    max_tokens: Option<u32>,
+    /// モデルティアごとのモデル名（`None` の場合は OpenAI のモデル名）
+    models: Option<TierModels>,
+    /// OpenAI 互換 API か（最大出力トークン数を `max_tokens` で送る）
+    compatible: bool,
+    timeout: Duration,
+}
+
+impl OpenAIApiClient {
+    /// API キーを指定してクライアントを生成
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            http: http_client(DEFAULT_TIMEOUT),
+            api_key: Some(api_key.into()),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            max_tokens: Some(DEFAULT_MAX_TOKENS),
+            models: None,
+            compatible: false,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// OpenAI 互換 API の定義からクライアントを生成
+    ///
+    /// モデル名は定義の `models` から解決し、最大出力トークン数は互換 API で広く
+    /// 受け付けられる `max_tokens` で送ります。
+    ///
+    /// # エラー
+    ///
+    /// - [`ProviderError::MissingApiKey`] - `api_key_env` の環境変数が未設定
+    pub fn compatible(spec: &OpenAiCompatibleSpec) -> Result<Self, ProviderError> {
+        let api_key = spec.api_key_env().map(api_key_from_env).transpose()?;
+        Ok(Self {
+            http: http_client(DEFAULT_TIMEOUT),
+            api_key,
+            base_url: spec.base_url().to_string(),
+            max_tokens: spec.max_tokens(),
+            models: Some(spec.models().clone()),
+            compatible: true,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// 環境変数（`OPENAI_API_KEY`, `OPENAI_BASE_URL`, `OPENAI_MAX_TOKENS`, `OPENAI_TIMEOUT`）からクライアントを生成
+    ///
+    /// # エラー
+    ///
+    /// - [`ProviderError::MissingApiKey`] - `OPENAI_API_KEY` が未設定
+    pub fn from_env() -> Result<Self, ProviderError> {
+        let client = Self::new(api_key_from_env(API_KEY_ENV)?);
+        Ok(client
+            .with_base_url(base_url_from_env(BASE_URL_ENV, DEFAULT_BASE_URL))
+            .with_max_tokens(max_tokens_from_env(MAX_TOKENS_ENV, DEFAULT_MAX_TOKENS))
+            .with_timeout(timeout_from_env(TIMEOUT_ENV, DEFAULT_TIMEOUT)))
+    }
+
+    /// ベース URL を設定（`/chat/completions` の手前まで。例: `https://api.openai.com/v1`）
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// 最大出力トークン数を設定
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// リクエストのタイムアウト（送信から応答の受信完了まで）を設定
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.http = http_client(timeout);
+        self.timeout = timeout;
+        self
+    }
+
+    /// モデルティアに対応するモデル名を解決
+    fn model(&self, tier: &ModelTier) -> Result<&str, ProviderError> {
+        match &self.models {
+            None => Ok(openai_model(tier)),
+            Some(models) => models.get(tier).ok_or_else(|| {
+                ProviderError::InvalidModelTier(format!("{}（models にモデル名が指定されていません）", tier.as_str()))
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for OpenAIApiClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // API キーは表示しない
+        f.debug_struct("OpenAIApiClient")
+            .field("base_url", &self.base_url)
+            .field("max_tokens", &self.max_tokens)
+            .field("models", &self.models)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ProviderClient for OpenAIApiClient {
+    async fn execute(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderResponse, ProviderError> {
+        self.execute_with_model(system_prompt, user_input, self.model(model_tier)?).await
+    }
+
+    async fn execute_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let mut messages = Vec::with_capacity(2);
+        if !system_prompt.is_empty() {
+            messages.push(Message {
+                role: "system",
+                content: system_prompt,
+            });
+        }
+        messages.push(Message {
+            role: "user",
+            content: user_input,
+        });
+
+        let (max_completion_tokens, max_tokens) = if self.compatible {
+            (None, self.max_tokens)
+        } else {
+            (self.max_tokens, None)
+        };
+        let request = ChatRequest {
+            model,
+            max_completion_tokens,
+            max_tokens,
+            messages,
+        };
+
+        let mut builder = self.http.post(endpoint(&self.base_url, "/chat/completions"));
+        if let Some(api_key) = &self.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+        let response: ChatResponse = send_json(builder, &request).await?;
+
+        response.into_provider_response()
+    }
+
+    fn default_model(&self, model_tier: &ModelTier) -> Option<&str> {
+        self.model(model_tier).ok()
+    }
+
+    fn system_prompt_channel(&self) -> SystemPromptChannel {
+        SystemPromptChannel::Native
+    }
+}
+
+/// リクエスト本文
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<u32>,
+    /// OpenAI では非推奨だが、互換 API は `max_completion_tokens` を受け付けないことがある
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    messages: Vec<Message<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+/// レスポンス本文
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    model: String,
+    choices: Vec<Choice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ChoiceMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChoiceMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<PromptTokensDetails>,
+    #[serde(default)]
+    completion_tokens_details: Option<CompletionTokensDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptTokensDetails {
+    #[serde(default)]
+    cached_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompletionTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: u32,
+}
+
+impl Usage {
+    /// 共通形式のトークン使用量（`prompt_tokens` はキャッシュからの読み込みを含むため差し引く）
+    fn token_usage(&self) -> TokenUsage {
+        let cached = self.prompt_tokens_details.as_ref().map_or(0, |details| details.cached_tokens);
+        TokenUsage {
+            input_tokens: self.prompt_tokens.saturating_sub(cached),
+            output_tokens: self.completion_tokens,
+            cache_read_tokens: cached,
+            cache_creation_tokens: 0,
+            reasoning_tokens: self.completion_tokens_details.as_ref().map_or(0, |details| details.reasoning_tokens),
+        }
+    }
+}
+
+impl ChatResponse {
+    fn into_provider_response(self) -> Result<ProviderResponse, ProviderError> {
+        let choice = self.choices.into_iter().next();
+        let (content, finish_reason) = match choice {
+            Some(choice) => (choice.message.content.unwrap_or_default(), choice.finish_reason),
+            None => (String::new(), None),
+        };
+        if content.is_empty() {
+            return Err(ProviderError::InvalidResponse("No content in response".to_string()));
+        }
+
+        let token_usage = self.usage.as_ref().map_or_else(TokenUsage::default, Usage::token_usage);
+
+        Ok(ProviderResponse {
+            content,
+            token_usage,
+            stop_reason: stop_reason(finish_reason.as_deref()),
+            model: self.model,
+            cached: false,
+        })
+    }
+}
+
+/// `finish_reason` を共通型に変換
+fn stop_reason(reason: Option<&str>) -> StopReason {
+    match reason {
+        Some("stop") => StopReason::EndTurn,
+        Some("length") => StopReason::MaxTokens,
+        Some("content_filter") => StopReason::ContentFilter,
+        _ => StopReason::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::http::test_server;
+
+    #[tokio::test]
+    async fn test_execute_sends_chat_request() {
+        let (base_url, server) = test_server::respond(
+            200,
+            r#"{"id":"chatcmpl-1","object":"chat.completion","model":"gpt-4o-mini-2024-07-18",
+               "choices":[{"index":0,"message":{"role":"assistant","content":"bug"},"finish_reason":"stop"}],
+               "usage":{"prompt_tokens":20,"completion_tokens":1,"total_tokens":21}}"#,
+        );
+        let client = OpenAIApiClient::new("test-key").with_base_url(format!("{}/v1", base_url));
+
+        let response = client.execute("Classify.", "It crashes.", &ModelTier::Light).await.unwrap();
+        assert_eq!(response.content, "bug");
+        assert_eq!(response.stop_reason, StopReason::EndTurn);
+        assert_eq!(response.token_usage.total(), 21);
+        assert_eq!(response.model, "gpt-4o-mini-2024-07-18");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /v1/chat/completions "), "{}", request);
+        assert!(request.contains("authorization: Bearer test-key"));
+        let body: serde_json::Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["model"], "gpt-4o-mini");
+        assert_eq!(body["max_completion_tokens"], DEFAULT_MAX_TOKENS);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "It crashes.");
+    }
+
+    #[tokio::test]
+    async fn test_execute_maps_rate_limit() {
+        let (base_url, server) = test_server::respond(429, r#"{"error":{"message":"Rate limit reached"}}"#);
+        let client = OpenAIApiClient::new("test-key").with_base_url(base_url);
+
+        let result = client.execute("system", "input", &ModelTier::Light).await;
+        assert!(matches!(result, Err(ProviderError::RateLimitExceeded)));
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compatible_uses_configured_models() {
+        let toml = r#"
+[workflow]
+name = "local"
+
+[providers.local]
+type = "openai-compatible"
+base_url = "http://127.0.0.1:1/v1"
+max_tokens = 256
+models = { light = "llama3.2:3b" }
+
+[[steps]]
+name = "triage"
+system_prompt = "Triage"
+provider = "local"
+model_tier = "light"
+"#;
+        let workflow = crate::config::workflow::Workflow::from_toml(toml).unwrap();
+        let crate::config::providers::ProviderDefinition::OpenAiCompatible(spec) = &workflow.providers()[0] else {
+            panic!("Expected OpenAiCompatible");
+        };
+
+        let (base_url, server) = test_server::respond(
+            200,
+            r#"{"model":"llama3.2:3b","choices":[{"message":{"content":"lint"},"finish_reason":"length"}]}"#,
+        );
+        let client = OpenAIApiClient::compatible(spec).unwrap().with_base_url(format!("{}/v1", base_url));
+
+        let response = client.execute("Triage.", "warning: unused", &ModelTier::Light).await.unwrap();
+        assert_eq!(response.content, "lint");
+        assert_eq!(response.stop_reason, StopReason::MaxTokens);
+        assert_eq!(response.token_usage.total(), 0);
+
+        let request = server.join().unwrap();
+        assert!(!request.to_lowercase().contains("authorization:"), "{}", request);
+        let body: serde_json::Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["model"], "llama3.2:3b");
+        assert_eq!(body["max_tokens"], 256);
+        assert!(body.get("max_completion_tokens").is_none());
+
+        // models に指定のないティア
+        let result = client.execute("Triage.", "input", &ModelTier::Heavy).await;
+        assert!(matches!(result, Err(ProviderError::InvalidModelTier(message)) if message.starts_with("heavy")));
+    }
+
+    #[test]
+    fn test_stop_reason() {
+        assert_eq!(stop_reason(Some("length")), StopReason::MaxTokens);
+        assert_eq!(stop_reason(Some("content_filter")), StopReason::ContentFilter);
+        assert_eq!(stop_reason(Some("tool_calls")), StopReason::Unknown);
+    }
+
+    #[test]
+    fn test_empty_choices_is_invalid() {
+        let response: ChatResponse = serde_json::from_str(r#"{"model":"gpt-4o","choices":[]}"#).unwrap();
+        assert!(matches!(response.into_provider_response(), Err(ProviderError::InvalidResponse(_))));
+    }
+}
@@ -0,0 +1,196 @@
+//! プロバイダーの登録簿
+//!
+//! # 責務
+//!
+//! - プロバイダー名（`provider = "..."` に書く文字列）とクライアントの生成方法を対応付ける
+//! - ワークフロー定義の検証で、プロバイダー名が登録済みか判定する
+//! - 組み込みのプロバイダー（`anthropic`, `openai`, `anthropic-api`, `openai-api`）を登録する
+//! - ワークフローで定義したプロバイダー（`[providers.<name>]`）を登録する
+//!
+//! # 使用例
+//!
+//! ```rust
+//! use melted_adw::config::step::Provider;
+//! use melted_adw::provider::anthropic::AnthropicClient;
+//! use melted_adw::provider::{ProviderClient, ProviderRegistry};
+//!
+//! // 組み込みのプロバイダーに、社内向けの Claude Code ラッパーを追加
+//! let registry = ProviderRegistry::builtin().with_provider("claude-internal", || {
+//!     Ok(Box::new(AnthropicClient::with_command("claude-internal")) as Box<dyn ProviderClient>)
+//! });
+//!
+//! assert!(registry.contains("claude-internal"));
+//! assert!(registry.create(&Provider::new("claude-internal")).is_ok());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::config::providers::ProviderDefinition;
+use crate::config::step::Provider;
+use crate::error::ProviderError;
+use super::anthropic::AnthropicClient;
+use super::anthropic_api::AnthropicApiClient;
+use super::command::CommandProvider;
+use super::openai::OpenAIClient;
+use super::openai_api::OpenAIApiClient;
+use super::traits::ProviderClient;
+
+/// クライアントの生成方法
+pub type ProviderConstructor =
+    Arc<dyn Fn() -> Result<Box<dyn ProviderClient>, ProviderError> + Send + Sync>;
+
+/// プロバイダー名からクライアントを生成する登録簿
+///
+/// プロバイダー名は大文字小文字を区別しません（小文字に正規化して登録・検索します）。
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    constructors: BTreeMap<String, ProviderConstructor>,
+}
+
+impl ProviderRegistry {
+    /// 空の登録簿を生成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 組み込みのプロバイダーを登録した登録簿を生成
+    ///
+    /// - `anthropic`, `openai`: CLI（`claude`, `codex`）
+    /// - `anthropic-api`, `openai-api`: HTTP API（API キーは生成時に環境変数から読み込む）
+    pub fn builtin() -> Self {
+        Self::new()
+            .with_provider(Provider::ANTHROPIC, || {
+                Ok(Box::new(AnthropicClient::new()) as Box<dyn ProviderClient>)
+            })
+            .with_provider(Provider::OPENAI, || {
+                Ok(Box::new(OpenAIClient::new()) as Box<dyn ProviderClient>)
+            })
+            .with_provider(Provider::ANTHROPIC_API, || {
+                Ok(Box::new(AnthropicApiClient::from_env()?) as Box<dyn ProviderClient>)
+            })
+            .with_provider(Provider::OPENAI_API, || {
+                Ok(Box::new(OpenAIApiClient::from_env()?) as Box<dyn ProviderClient>)
+            })
+    }
+
+    /// プロバイダーを登録
+    ///
+    /// 同じ名前のプロバイダーが登録済みの場合は置き換えます。
+    ///
+    /// # 引数
+    ///
+    /// - `name`: プロバイダー名
+    /// - `constructor`: クライアントを生成する関数（ステップの実行ごとに呼び出されます）
+    pub fn register<F>(&mut self, name: impl AsRef<str>, constructor: F)
+    where
+        F: Fn() -> Result<Box<dyn ProviderClient>, ProviderError> + Send + Sync + 'static,
+    {
+        self.constructors
+            .insert(name.as_ref().to_lowercase(), Arc::new(constructor));
+    }
+
+    /// プロバイダーを登録（ビルダー形式）
+    ///
+    /// [`register`](Self::register) と同じです。
+    pub fn with_provider<F>(mut self, name: impl AsRef<str>, constructor: F) -> Self
+    where
+        F: Fn() -> Result<Box<dyn ProviderClient>, ProviderError> + Send + Sync + 'static,
+    {
+        self.register(name, constructor);
+        self
+    }
+
+    /// ワークフローで定義したプロバイダーを登録
+    ///
+    /// 定義のプロバイダー名で、種類に応じたクライアント（[`CommandProvider`] または
+    /// [`OpenAIApiClient::compatible`]）を生成するように登録します。
+    pub fn register_definition(&mut self, definition: ProviderDefinition) {
+        let name = definition.name().to_string();
+        match definition {
+            ProviderDefinition::Command(spec) => {
+                let client = CommandProvider::new(spec);
+                self.register(name, move || Ok(Box::new(client.clone()) as Box<dyn ProviderClient>));
+            }
+            ProviderDefinition::OpenAiCompatible(spec) => {
+                self.register(name, move || {
+                    Ok(Box::new(OpenAIApiClient::compatible(&spec)?) as Box<dyn ProviderClient>)
+                });
+            }
+        }
+    }
+
+    /// ワークフローで定義したプロバイダーをまとめて登録（ビルダー形式）
+    ///
+    /// # 引数
+    ///
+    /// - `definitions`: プロバイダーの定義（[`Workflow::providers`](crate::config::workflow::Workflow::providers)）
+    pub fn with_definitions(mut self, definitions: &[ProviderDefinition]) -> Self {
+        for definition in definitions {
+            self.register_definition(definition.clone());
+        }
+        self
+    }
+
+    /// プロバイダー名が登録済みか判定
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(&name.to_lowercase())
+    }
+
+    /// 登録済みのプロバイダー名（名前順）
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    /// クライアントを生成
+    ///
+    /// # エラー
+    ///
+    /// - [`ProviderError::UnknownProvider`] - プロバイダーが登録されていない
+    /// - 生成関数が返したエラー
+    pub fn create(&self, provider: &Provider) -> Result<Box<dyn ProviderClient>, ProviderError> {
+        let constructor = self
+            .constructors
+            .get(provider.as_str())
+            .ok_or_else(|| ProviderError::UnknownProvider(provider.as_str().to_string()))?;
+        constructor()
+    }
+}
+
+impl fmt::Debug for ProviderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_providers() {
+        let registry = ProviderRegistry::builtin();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["anthropic", "anthropic-api", "openai", "openai-api"]
+        );
+        assert!(registry.contains("OpenAI"));
+        assert!(registry.create(&Provider::new("anthropic")).is_ok());
+    }
+
+    #[test]
+    fn test_register_and_create() {
+        let mut registry = ProviderRegistry::new();
+        assert!(!registry.contains("gemini"));
+
+        registry.register("Gemini", || {
+            Ok(Box::new(OpenAIClient::with_command("gemini")) as Box<dyn ProviderClient>)
+        });
+        assert!(registry.contains("gemini"));
+        assert!(registry.create(&Provider::new("GEMINI")).is_ok());
+
+        let result = registry.create(&Provider::new("aider"));
+        assert!(matches!(result, Err(ProviderError::UnknownProvider(name)) if name == "aider"));
+    }
+}
@@ -0,0 +1,160 @@
+//! CLI の JSONL 出力を逐次イベントに変換するストリーム
+//!
+//! # 責務
+//!
+//! - CLI プロセスを起動し、標準出力を1行ずつ読み取る（必要なら標準入力にプロンプトを書き込む）
+//! - 各行を [`JsonlParser`] でイベントに変換して [`ProviderEventStream`] として返す
+//! - プロセス終了時に終了コードと標準エラー出力から最終的な応答（またはエラー）を組み立てる
+//!
+//! ストリームを途中で破棄した場合、CLI プロセスは終了させられます。
+
+use std::process::{ExitStatus, Stdio};
+
+use futures::stream::{self, StreamExt};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdout, Command};
+use tokio::task::JoinHandle;
+
+use crate::error::ProviderError;
+use super::traits::{ProviderEvent, ProviderEventStream, ProviderResponse};
+
+/// JSONL 出力の1行ずつのパーサー
+///
+/// プロバイダーごとの出力形式の違いを吸収します。
+pub(super) trait JsonlParser: Send + 'static {
+    /// 1行（空行を除く）をイベントに変換
+    ///
+    /// 応答の組み立てに必要な情報（本文・モデル名など）はパーサー自身が保持します。
+    fn parse_line(&mut self, line: &str) -> Result<Vec<ProviderEvent>, ProviderError>;
+
+    /// プロセス終了後に最終的な応答を組み立てる
+    ///
+    /// # 引数
+    ///
+    /// - `status`: CLI の終了ステータス
+    /// - `stderr`: CLI の標準エラー出力
+    fn finish(&mut self, status: ExitStatus, stderr: &str) -> Result<ProviderResponse, ProviderError>;
+}
+
+/// CLI プロセスを起動し、JSONL 出力をイベントのストリームとして返す
+///
+/// 最後のイベントは [`ProviderEvent::Completed`]、またはエラーです。
+/// エラーを返した後、ストリームは終了します。
+///
+/// # 引数
+///
+/// - `command`: 起動するコマンド
+/// - `parser`: 出力のパーサー
+/// - `stdin`: 標準入力に書き込む内容（`None` の場合は標準入力を閉じる）
+///
+/// # エラー
+///
+/// - [`ProviderError::ProcessError`] - プロセスを起動できない
+pub(super) fn spawn_jsonl_stream<P: JsonlParser>(
+    mut command: Command,
+    parser: P,
+    stdin: Option<String>,
+) -> Result<ProviderEventStream, ProviderError> {
+    let mut child = command
+        .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    feed_stdin(&mut child, stdin);
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| ProviderError::InvalidResponse("標準出力を取得できません".to_string()))?;
+
+    // 標準エラー出力はパイプが詰まらないよう並行して読み切る
+    let mut stderr = child.stderr.take();
+    let stderr_task = tokio::spawn(async move {
+        let mut buffer = String::new();
+        if let Some(stderr) = stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut buffer).await;
+        }
+        buffer
+    });
+
+    let state = StreamState {
+        lines: BufReader::new(stdout).lines(),
+        parser,
+        child,
+        stderr_task: Some(stderr_task),
+        finished: false,
+    };
+
+    let events = stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+        let events = state.next_events().await;
+        Some((events, state))
+    })
+    .flat_map(stream::iter);
+
+    Ok(events.boxed())
+}
+
+/// 子プロセスの標準入力に内容を書き込む
+///
+/// 標準出力の読み取りと並行できるよう別タスクで書き込み、書き終えたら標準入力を閉じます。
+/// 子プロセスが標準入力を読まずに終了した場合の書き込みエラーは無視します
+/// （終了コードと標準エラー出力で判定します）。
+pub(super) fn feed_stdin(child: &mut Child, input: Option<String>) {
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        tokio::spawn(async move {
+            let _ = stdin.write_all(input.as_bytes()).await;
+        });
+    }
+}
+
+/// ストリームの読み取り状態
+struct StreamState<P> {
+    lines: Lines<BufReader<ChildStdout>>,
+    parser: P,
+    child: Child,
+    stderr_task: Option<JoinHandle<String>>,
+    finished: bool,
+}
+
+impl<P: JsonlParser> StreamState<P> {
+    /// 次の1行を読み取り、イベントに変換
+    ///
+    /// 標準出力が閉じられた場合は、プロセスの終了を待って最終的な応答を返します。
+    async fn next_events(&mut self) -> Vec<Result<ProviderEvent, ProviderError>> {
+        match self.lines.next_line().await {
+            Ok(Some(line)) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    return Vec::new();
+                }
+                match self.parser.parse_line(line) {
+                    Ok(events) => events.into_iter().map(Ok).collect(),
+                    Err(e) => self.fail(e),
+                }
+            }
+            Ok(None) => {
+                self.finished = true;
+                vec![self.complete().await.map(ProviderEvent::Completed)]
+            }
+            Err(e) => self.fail(e.into()),
+        }
+    }
+
+    async fn complete(&mut self) -> Result<ProviderResponse, ProviderError> {
+        let status = self.child.wait().await?;
+        let stderr = match self.stderr_task.take() {
+            Some(task) => task.await.unwrap_or_default(),
+            None => String::new(),
+        };
+        self.parser.finish(status, &stderr)
+    }
+
+    fn fail(&mut self, error: ProviderError) -> Vec<Result<ProviderEvent, ProviderError>> {
+        self.finished = true;
+        vec![Err(error)]
+    }
+}
@@ -5,6 +5,8 @@
 //! - LLMプロバイダー（Anthropic, OpenAI等）の共通トレイト [`ProviderClient`] を定義
 //! - プロバイダー非依存のレスポンス型 [`ProviderResponse`] を提供
 //! - トークン使用量 [`TokenUsage`] と停止理由 [`StopReason`] の型を定義
+//! - システムプロンプトの渡し方 [`SystemPromptChannel`] を定義
+//! - ストリーミング実行のイベント [`ProviderEvent`] を定義
 //!
 //! # 実装方式
 //!
@@ -36,6 +38,7 @@
 //! ```
 
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
 use crate::config::step::ModelTier;
 use crate::error::ProviderError;
 
@@ -95,12 +98,151 @@ pub trait ProviderClient: Send + Sync {
         user_input: &str,
         model_tier: &ModelTier,
     ) -> Result<ProviderResponse, ProviderError>;
+
+    /// LLMに対してプロンプトを実行し、進捗をイベントのストリームとして受け取る
+    ///
+    /// ストリームは生成中のテキストやツール呼び出しを順に返し、最後に
+    /// [`ProviderEvent::Completed`]（[`execute`](Self::execute) の戻り値に相当）を返して終了します。
+    /// 途中でエラーが発生した場合は `Err` を返して終了します。
+    ///
+    /// 既定の実装は [`execute`](Self::execute) の完了を待ってから、
+    /// 応答全体を1つのテキストとして返します（逐次の出力に対応しないクライアント向け）。
+    ///
+    /// # 引数
+    ///
+    /// [`execute`](Self::execute) と同じです。
+    async fn execute_stream(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model_tier: &ModelTier,
+    ) -> Result<ProviderEventStream, ProviderError> {
+        let response = self.execute(system_prompt, user_input, model_tier).await?;
+        Ok(response_events(response))
+    }
+
+    /// モデル名を指定してLLMを実行する
+    ///
+    /// ステップの `model` や `[models]` の対応表でモデル名が決まっている場合に、
+    /// 実行エンジンが [`execute`](Self::execute) の代わりに呼び出します。
+    ///
+    /// 既定の実装はモデル名の指定に対応しないため、[`ProviderError::UnsupportedModel`] を返します。
+    ///
+    /// # 引数
+    ///
+    /// - `system_prompt`: システムプロンプト
+    /// - `user_input`: ユーザー入力
+    /// - `model`: プロバイダーが受け付ける形式のモデル名
+    async fn execute_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderResponse, ProviderError> {
+        let _ = (system_prompt, user_input);
+        Err(ProviderError::UnsupportedModel(model.to_string()))
+    }
+
+    /// モデル名を指定してLLMを実行し、進捗をイベントのストリームとして受け取る
+    ///
+    /// 既定の実装は [`execute_with_model`](Self::execute_with_model) の完了を待ってから、
+    /// 応答全体を1つのテキストとして返します。
+    ///
+    /// # 引数
+    ///
+    /// [`execute_with_model`](Self::execute_with_model) と同じです。
+    async fn execute_stream_with_model(
+        &self,
+        system_prompt: &str,
+        user_input: &str,
+        model: &str,
+    ) -> Result<ProviderEventStream, ProviderError> {
+        let response = self.execute_with_model(system_prompt, user_input, model).await?;
+        Ok(response_events(response))
+    }
+
+    /// モデルティアに対応する既定のモデル名
+    ///
+    /// 実行エンジンはモデル名を指定しなかったステップでこの値を
+    /// [`StepResult::model`](crate::engine::StepResult::model) に記録します。
+    /// 既定の実装は `None`（不明）です。
+    fn default_model(&self, model_tier: &ModelTier) -> Option<&str> {
+        let _ = model_tier;
+        None
+    }
+
+    /// システムプロンプトの渡し方
+    ///
+    /// 実行エンジンはこの値を [`StepResult`](crate::engine::StepResult) に記録します。
+    /// 専用の仕組みを持たないクライアントは [`inline_system_prompt`] でユーザー入力に結合し、
+    /// [`SystemPromptChannel::Inline`]（既定）を返します。
+    fn system_prompt_channel(&self) -> SystemPromptChannel {
+        SystemPromptChannel::Inline
+    }
+}
+
+/// ストリーミング実行中のイベント
+#[derive(Debug, Clone)]
+pub enum ProviderEvent {
+    /// 生成されたテキストの差分
+    TextDelta(String),
+
+    /// ツール呼び出し（コマンド実行・ファイル編集など）
+    ToolCall {
+        /// ツール名
+        name: String,
+        /// ツールへの入力
+        input: serde_json::Value,
+    },
+
+    /// トークン使用量の更新（その時点までの合計）
+    Usage(TokenUsage),
+
+    /// 生成の完了（最終的な応答）
+    Completed(ProviderResponse),
+}
+
+/// [`ProviderClient::execute_stream`] が返すイベントのストリーム
+pub type ProviderEventStream = BoxStream<'static, Result<ProviderEvent, ProviderError>>;
+
+/// 完了済みの応答をイベントのストリームに変換
+///
+/// テキスト全体・トークン使用量・完了の3つのイベントを返します。
+pub fn response_events(response: ProviderResponse) -> ProviderEventStream {
+    let events = vec![
+        Ok(ProviderEvent::TextDelta(response.content.clone())),
+        Ok(ProviderEvent::Usage(response.token_usage)),
+        Ok(ProviderEvent::Completed(response)),
+    ];
+    stream::iter(events).boxed()
+}
+
+/// システムプロンプトの渡し方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum SystemPromptChannel {
+    /// プロバイダーの専用の仕組み（CLI オプション・API の system 等）で、ユーザー入力と分けて渡す
+    Native,
+
+    /// ユーザー入力に結合して渡す（指示とデータが区別されない）
+    Inline,
+}
+
+/// システムプロンプトをユーザー入力に結合
+///
+/// システムプロンプトを渡す専用の仕組みを持たないクライアント向けです。
+/// 区切りを明示しますが、ユーザー入力中の指示と完全には区別できません。
+pub fn inline_system_prompt(system_prompt: &str, user_input: &str) -> String {
+    format!(
+        "<system>\n{}\n</system>\n\n<user_input>\n{}\n</user_input>",
+        system_prompt, user_input
+    )
 }
 
 /// LLMプロバイダーからのレスポンス
 ///
 /// プロバイダー固有のレスポンス形式（CLI出力）を共通の型に変換したもの。
-#[derive(Debug, Clone)]
+/// 応答キャッシュ（[`ResponseCache`](super::cache::ResponseCache)）には JSON として保存します。
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ProviderResponse {
     /// LLMが生成したテキスト
     pub content: String,
@@ -113,27 +255,63 @@ pub struct ProviderResponse {
 
     /// 使用されたモデル名（例: "claude-sonnet-4-5", "gpt-4o"）
     pub model: String,
+
+    /// 応答キャッシュから返した応答か（LLM を呼び出しておらず、トークン使用量は0）
+    #[serde(skip)]
+    pub cached: bool,
 }
 
 /// トークン使用量
-#[derive(Debug, Clone, Copy, serde::Serialize)]
+///
+/// プロバイダーによって報告の形式が異なるため、各クライアントで次の意味に揃えます。
+///
+/// - `input_tokens` はキャッシュから読み込んだ・キャッシュに書き込んだトークンを含まない
+///   （Codex CLI・OpenAI API はキャッシュからの読み込みを入力に含めて報告するため差し引く）
+/// - `reasoning_tokens` は `output_tokens` の内数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct TokenUsage {
-    /// 入力トークン数（プロンプト）
+    /// 入力トークン数（プロンプト、キャッシュの読み込み・書き込みを除く）
     pub input_tokens: u32,
 
-    /// 出力トークン数（LLM生成テキスト）
+    /// 出力トークン数（LLM生成テキスト、推論を含む）
     pub output_tokens: u32,
+
+    /// キャッシュから読み込んだ入力トークン数
+    pub cache_read_tokens: u32,
+
+    /// キャッシュに書き込んだ入力トークン数
+    pub cache_creation_tokens: u32,
+
+    /// 推論トークン数（出力トークンの内数）
+    pub reasoning_tokens: u32,
 }
 
 impl TokenUsage {
     /// 総トークン数を計算
-    pub fn total(&self) -> u32 {
-        self.input_tokens + self.output_tokens
+    ///
+    /// キャッシュの読み込み・書き込みを含む入力と、出力の合計です（推論トークンは出力に含まれる）。
+    pub fn total(&self) -> u64 {
+        [self.input_tokens, self.cache_read_tokens, self.cache_creation_tokens, self.output_tokens]
+            .into_iter()
+            .map(u64::from)
+            .fold(0, u64::saturating_add)
+    }
+
+    /// 2つのトークン使用量の合計（項目ごとに上限で飽和）
+    pub fn saturating_add(self, other: TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens.saturating_add(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_add(other.output_tokens),
+            cache_read_tokens: self.cache_read_tokens.saturating_add(other.cache_read_tokens),
+            cache_creation_tokens: self.cache_creation_tokens.saturating_add(other.cache_creation_tokens),
+            reasoning_tokens: self.reasoning_tokens.saturating_add(other.reasoning_tokens),
+        }
     }
 }
 
 /// LLMの生成停止理由
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum StopReason {
     /// 自然な終了（LLMが完了を判断）
     EndTurn,
@@ -160,8 +338,67 @@ mod tests {
         let usage = TokenUsage {
             input_tokens: 100,
             output_tokens: 250,
+            ..Default::default()
         };
         assert_eq!(usage.total(), 350);
+
+        // キャッシュの読み込み・書き込みを含み、推論トークンは出力の内数として数えない
+        let usage = TokenUsage {
+            input_tokens: 10,
+            output_tokens: 20,
+            cache_read_tokens: 1000,
+            cache_creation_tokens: 200,
+            reasoning_tokens: 15,
+        };
+        assert_eq!(usage.total(), 1230);
+    }
+
+    #[test]
+    fn test_token_usage_saturating_add() {
+        let usage = TokenUsage {
+            input_tokens: u32::MAX,
+            output_tokens: u32::MAX,
+            cache_read_tokens: u32::MAX,
+            cache_creation_tokens: u32::MAX,
+            reasoning_tokens: 1,
+        };
+
+        let sum = usage.saturating_add(TokenUsage { input_tokens: 1, reasoning_tokens: 2, ..Default::default() });
+        assert_eq!(sum.input_tokens, u32::MAX);
+        assert_eq!(sum.reasoning_tokens, 3);
+        assert_eq!(sum.total(), 4 * u64::from(u32::MAX));
+    }
+
+    #[test]
+    fn test_inline_system_prompt() {
+        assert_eq!(
+            inline_system_prompt("Be brief.", "Hello"),
+            "<system>\nBe brief.\n</system>\n\n<user_input>\nHello\n</user_input>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_response_events() {
+        let response = ProviderResponse {
+            content: "Hello".to_string(),
+            token_usage: TokenUsage {
+                input_tokens: 3,
+                output_tokens: 1,
+                ..Default::default()
+            },
+            stop_reason: StopReason::EndTurn,
+            model: "test-model".to_string(),
+            cached: false,
+        };
+
+        let events: Vec<ProviderEvent> = response_events(response)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], ProviderEvent::TextDelta(text) if text == "Hello"));
+        assert!(matches!(&events[1], ProviderEvent::Usage(usage) if usage.total() == 4));
+        assert!(matches!(&events[2], ProviderEvent::Completed(response) if response.model == "test-model"));
     }
 
     #[test]
@@ -169,4 +406,30 @@ mod tests {
         assert_eq!(StopReason::EndTurn, StopReason::EndTurn);
         assert_ne!(StopReason::EndTurn, StopReason::MaxTokens);
     }
+
+    /// モデル名の指定に対応しないクライアント
+    struct TierOnlyClient;
+
+    #[async_trait]
+    impl ProviderClient for TierOnlyClient {
+        async fn execute(
+            &self,
+            _system_prompt: &str,
+            _user_input: &str,
+            _model_tier: &ModelTier,
+        ) -> Result<ProviderResponse, ProviderError> {
+            unreachable!("モデル名を指定した呼び出しでは使わない")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_model_is_unsupported_by_default() {
+        let client = TierOnlyClient;
+        assert!(client.default_model(&ModelTier::Heavy).is_none());
+
+        let result = client.execute_with_model("system", "input", "custom-model").await;
+        assert!(matches!(result, Err(ProviderError::UnsupportedModel(model)) if model == "custom-model"));
+        let result = client.execute_stream_with_model("system", "input", "custom-model").await;
+        assert!(matches!(result, Err(ProviderError::UnsupportedModel(_))));
+    }
 }
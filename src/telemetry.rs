@@ -0,0 +1,57 @@
+//! テレメトリー収集
+//!
+//! # 責務
+//!
+//! - ワークフロー実行中のステップ単位のイベント（開始・完了・リトライ等）を収集
+//! - README に掲げる KPI（実行速度・修正回数・実行コスト）の算出
+//! - 実行記録のエクスポート（JSON、`duckdb` フィーチャー有効時は DuckDB）
+//!
+//! # モジュール構成
+//!
+//! - [`collector`][]: イベントを受け取り実行記録 [`RunRecord`] を組み立てる [`TelemetryCollector`]
+//! - [`metrics`][]: KPI 型の定義（[`WorkflowKpis`]）
+//! - [`exporter`][]: 実行記録の出力先（[`TelemetryExporter`] トレイトと実装）
+//!
+//! # データフロー
+//!
+//! ```text
+//! WorkflowExecutor
+//!   ↓ TelemetryEvent（ステップ開始・完了・失敗・リトライ）
+//! TelemetryCollector
+//!   ↓ finish()
+//! RunRecord（ステップ記録 + KPI）
+//!   ↓ export()
+//! TelemetryExporter（telemetry/<run_id>.json 等）
+//! ```
+//!
+//! # 使用例
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use melted_adw::config::workflow::Workflow;
+//! use melted_adw::engine::WorkflowExecutor;
+//! use melted_adw::telemetry::JsonExporter;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let workflow = Workflow::from_file("workflows/example.toml")?;
+//!
+//!     // 実行記録を telemetry/ 以下に1実行1ファイルで出力
+//!     let executor = WorkflowExecutor::new(workflow)
+//!         .with_exporter(Arc::new(JsonExporter::new("telemetry")));
+//!
+//!     executor.execute().await?;
+//!     Ok(())
+//! }
+//! ```
+
+pub mod collector;
+pub mod exporter;
+pub mod metrics;
+
+// 公開APIの再エクスポート
+pub use collector::{RunRecord, StepRecord, TelemetryCollector, TelemetryEvent};
+pub use exporter::{JsonExporter, TelemetryExporter};
+#[cfg(feature = "duckdb")]
+pub use exporter::DuckDbExporter;
+pub use metrics::WorkflowKpis;
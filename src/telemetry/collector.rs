@@ -0,0 +1,603 @@
+//! テレメトリーイベントの収集
+//!
+//! # 責務
+//!
+//! - 実行エンジンから送られるステップ単位のイベント [`TelemetryEvent`] を受け取る
+//! - イベントをステップ記録 [`StepRecord`] に集約する
+//! - 実行終了時に、KPI を含む実行記録 [`RunRecord`] を組み立てる
+//!
+//! # 使用例
+//!
+//! ```rust
+//! use melted_adw::telemetry::{TelemetryCollector, TelemetryEvent};
+//! use melted_adw::provider::{StopReason, TokenUsage};
+//! use std::time::Duration;
+//!
+//! let mut collector = TelemetryCollector::new("example");
+//!
+//! collector.record(TelemetryEvent::StepStarted {
+//!     step_name: "plan".to_string(),
+//!     index: 0,
+//!     iteration: 0,
+//!     provider: "anthropic".to_string(),
+//! });
+//! collector.record(TelemetryEvent::StepFinished {
+//!     step_name: "plan".to_string(),
+//!     provider: "anthropic".to_string(),
+//!     model: "claude-opus-4".to_string(),
+//!     stop_reason: StopReason::EndTurn,
+//!     token_usage: TokenUsage { input_tokens: 100, output_tokens: 50, ..Default::default() },
+//!     cost_usd: Some(0.005),
+//!     duration: Duration::from_secs(3),
+//! });
+//!
+//! assert_eq!(collector.steps().len(), 1);
+//! ```
+
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::engine::{ExecutionStatus, StepStatus, WorkflowResult};
+use crate::provider::{StopReason, TokenUsage};
+use super::metrics::WorkflowKpis;
+
+/// 実行エンジンから送られるステップ単位のイベント
+#[derive(Debug, Clone)]
+pub enum TelemetryEvent {
+    /// ステップ開始
+    StepStarted {
+        /// ステップ名
+        step_name: String,
+        /// ステップインデックス（0始まり）
+        index: usize,
+        /// ループ内の繰り返し回数（0始まり、ループ外は 0）
+        iteration: u32,
+        /// プロバイダー名
+        provider: String,
+    },
+
+    /// 1回の試行が失敗（リトライ対象）
+    AttemptFailed {
+        /// ステップ名
+        step_name: String,
+        /// 試行番号（0始まり）
+        attempt: u32,
+        /// エラー内容
+        error: String,
+    },
+
+    /// ステップ完了
+    StepFinished {
+        /// ステップ名
+        step_name: String,
+        /// 出力を生成したプロバイダー名（フォールバックした場合はフォールバック先）
+        provider: String,
+        /// 実際に使用されたモデル名
+        model: String,
+        /// 生成停止理由
+        stop_reason: StopReason,
+        /// トークン使用量
+        token_usage: TokenUsage,
+        /// コスト（米ドル、単価が不明なモデルのみの場合は `None`）
+        cost_usd: Option<f64>,
+        /// 実行時間（成功した試行）
+        duration: Duration,
+    },
+
+    /// ステップ失敗（リトライを使い切った）
+    StepFailed {
+        /// ステップ名
+        step_name: String,
+        /// 最後のエラー内容
+        error: String,
+        /// 失敗した試行を含むトークン使用量の合計
+        token_usage: TokenUsage,
+        /// 失敗した試行を含むコストの合計（米ドル、単価が不明なモデルのみの場合は `None`）
+        cost_usd: Option<f64>,
+        /// 全試行の実行時間
+        duration: Duration,
+    },
+
+    /// ステップのスキップ
+    StepSkipped {
+        /// ステップ名
+        step_name: String,
+        /// ステップインデックス（0始まり）
+        index: usize,
+        /// ループ内の繰り返し回数（0始まり、ループ外は 0）
+        iteration: u32,
+        /// プロバイダー名
+        provider: String,
+        /// スキップした理由
+        reason: String,
+    },
+}
+
+/// ステップ1回分の実行記録
+#[derive(Debug, Clone, Serialize)]
+pub struct StepRecord {
+    /// ステップ名
+    pub step_name: String,
+
+    /// ステップインデックス（0始まり）
+    pub index: usize,
+
+    /// ループ内の繰り返し回数（0始まり、ループ外は 0）
+    pub iteration: u32,
+
+    /// プロバイダー名（成功時は出力を生成したプロバイダー）
+    pub provider: String,
+
+    /// 実行ステータス
+    pub status: StepStatus,
+
+    /// 実際に使用されたモデル名（成功時のみ）
+    pub model: Option<String>,
+
+    /// 生成停止理由（成功時のみ）
+    pub stop_reason: Option<StopReason>,
+
+    /// トークン使用量
+    pub token_usage: TokenUsage,
+
+    /// コスト（米ドル、単価が不明なモデルのみの場合は `None`）
+    pub cost_usd: Option<f64>,
+
+    /// 開始時刻（スキップ時は `None`）
+    pub started_at: Option<SystemTime>,
+
+    /// 実行時間
+    pub duration: Duration,
+
+    /// リトライ回数
+    pub retry_count: u32,
+
+    /// 失敗した各試行のエラー内容（試行順）
+    pub attempt_errors: Vec<String>,
+
+    /// エラーメッセージ（失敗時のみ）
+    pub error: Option<String>,
+
+    /// スキップした理由（スキップ時のみ）
+    pub skip_reason: Option<String>,
+}
+
+/// ワークフロー1実行分の記録
+///
+/// エクスポーターに渡される単位です。
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRecord {
+    /// 実行ID（`YYYYMMDD-HHMMSS-xxxx` 形式、UTC）
+    pub run_id: String,
+
+    /// ワークフロー名
+    pub workflow_name: String,
+
+    /// 実行ステータス
+    pub status: ExecutionStatus,
+
+    /// 開始時刻
+    pub started_at: SystemTime,
+
+    /// 終了時刻
+    pub finished_at: SystemTime,
+
+    /// ステップ記録（完了順）
+    pub steps: Vec<StepRecord>,
+
+    /// KPI
+    pub kpis: WorkflowKpis,
+
+    /// エラーメッセージ（失敗時のみ）
+    pub error: Option<String>,
+}
+
+/// テレメトリーコレクター
+///
+/// 1回のワークフロー実行につき1つ生成し、実行エンジンからイベントを受け取ります。
+#[derive(Debug)]
+pub struct TelemetryCollector {
+    run_id: String,
+    workflow_name: String,
+    started_at: SystemTime,
+
+    // 実行中のステップ
+    in_progress: Vec<StepRecord>,
+
+    // 完了・失敗・スキップしたステップ
+    steps: Vec<StepRecord>,
+}
+
+impl TelemetryCollector {
+    /// 新しいコレクターを生成
+    ///
+    /// 実行IDは現在時刻から自動生成されます。
+    ///
+    /// # 引数
+    ///
+    /// - `workflow_name`: 実行するワークフロー名
+    pub fn new(workflow_name: impl Into<String>) -> Self {
+        Self {
+            run_id: generate_run_id(),
+            workflow_name: workflow_name.into(),
+            started_at: SystemTime::now(),
+            in_progress: Vec::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// 実行IDを取得
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// 記録済みのステップ（完了・失敗・スキップ）を取得
+    pub fn steps(&self) -> &[StepRecord] {
+        &self.steps
+    }
+
+    /// イベントを記録
+    ///
+    /// `StepStarted` で開始したステップに対し、`AttemptFailed` を0回以上、
+    /// 最後に `StepFinished` または `StepFailed` を送ります。
+    /// 開始されていないステップへのイベントは、その場で記録を作成して扱います。
+    pub fn record(&mut self, event: TelemetryEvent) {
+        match event {
+            TelemetryEvent::StepStarted { step_name, index, iteration, provider } => {
+                self.in_progress.push(StepRecord {
+                    step_name,
+                    index,
+                    iteration,
+                    provider,
+                    status: StepStatus::Failed,
+                    model: None,
+                    stop_reason: None,
+                    token_usage: TokenUsage::default(),
+                    cost_usd: None,
+                    started_at: Some(SystemTime::now()),
+                    duration: Duration::from_secs(0),
+                    retry_count: 0,
+                    attempt_errors: Vec::new(),
+                    error: None,
+                    skip_reason: None,
+                });
+            }
+            TelemetryEvent::AttemptFailed { step_name, error, .. } => {
+                let mut record = self.take_or_create(&step_name);
+                record.attempt_errors.push(error);
+                self.in_progress.push(record);
+            }
+            TelemetryEvent::StepFinished {
+                step_name,
+                provider,
+                model,
+                stop_reason,
+                token_usage,
+                cost_usd,
+                duration,
+            } => {
+                let mut record = self.take_or_create(&step_name);
+                let retries = record.attempt_errors.len() as u32;
+
+                record.status = if retries == 0 {
+                    StepStatus::Success
+                } else {
+                    StepStatus::Retried { attempts: retries }
+                };
+                record.provider = provider;
+                record.model = Some(model);
+                record.stop_reason = Some(stop_reason);
+                record.token_usage = token_usage;
+                record.cost_usd = cost_usd;
+                record.duration = duration;
+                record.retry_count = retries;
+                self.steps.push(record);
+            }
+            TelemetryEvent::StepFailed { step_name, error, token_usage, cost_usd, duration } => {
+                let mut record = self.take_or_create(&step_name);
+
+                record.status = StepStatus::Failed;
+                record.token_usage = token_usage;
+                record.cost_usd = cost_usd;
+                record.duration = duration;
+                record.retry_count = (record.attempt_errors.len() as u32).saturating_sub(1);
+                record.error = Some(error);
+                self.steps.push(record);
+            }
+            TelemetryEvent::StepSkipped { step_name, index, iteration, provider, reason } => {
+                self.steps.push(StepRecord {
+                    step_name,
+                    index,
+                    iteration,
+                    provider,
+                    status: StepStatus::Skipped,
+                    model: None,
+                    stop_reason: None,
+                    token_usage: TokenUsage::default(),
+                    cost_usd: None,
+                    started_at: None,
+                    duration: Duration::from_secs(0),
+                    retry_count: 0,
+                    attempt_errors: Vec::new(),
+                    error: None,
+                    skip_reason: Some(reason),
+                });
+            }
+        }
+    }
+
+    /// 実行を終了し、実行記録を組み立てる
+    ///
+    /// # 引数
+    ///
+    /// - `result`: 実行エンジンが返したワークフロー結果
+    pub fn finish(mut self, result: &WorkflowResult) -> RunRecord {
+        // 終了イベントが届かなかったステップは失敗として扱う
+        self.steps.append(&mut self.in_progress);
+
+        let mut kpis = WorkflowKpis::from_steps(&self.steps, result.total_duration);
+        kpis.revision_count = result.revision_count();
+
+        RunRecord {
+            run_id: self.run_id,
+            workflow_name: self.workflow_name,
+            status: result.status,
+            started_at: self.started_at,
+            finished_at: result.end_time,
+            steps: self.steps,
+            kpis,
+            error: result.error.clone(),
+        }
+    }
+
+    /// 実行中のステップ記録を取り出す
+    fn take_in_progress(&mut self, step_name: &str) -> Option<StepRecord> {
+        let position = self.in_progress.iter().position(|r| r.step_name == step_name)?;
+        Some(self.in_progress.remove(position))
+    }
+
+    /// 実行中のステップ記録を取り出す（存在しない場合は新規作成）
+    fn take_or_create(&mut self, step_name: &str) -> StepRecord {
+        self.take_in_progress(step_name).unwrap_or_else(|| StepRecord {
+            step_name: step_name.to_string(),
+            index: self.steps.len(),
+            iteration: 0,
+            provider: String::new(),
+            status: StepStatus::Failed,
+            model: None,
+            stop_reason: None,
+            token_usage: TokenUsage::default(),
+            cost_usd: None,
+            started_at: None,
+            duration: Duration::from_secs(0),
+            retry_count: 0,
+            attempt_errors: Vec::new(),
+            error: None,
+            skip_reason: None,
+        })
+    }
+}
+
+/// 実行IDを生成
+///
+/// `YYYYMMDD-HHMMSS-xxxx`（UTC）形式。末尾の16進数4桁は同一秒内の衝突を避けるための値で、
+/// プロセスごとの初期値（起動時刻とプロセスID）に呼び出し回数を足したものです。
+/// 同じプロセス内では 65536 回呼び出すまで重複しません。
+/// 文字列としてソートすると時系列順になります。
+pub fn generate_run_id() -> String {
+    static BASE: OnceLock<u32> = OnceLock::new();
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0));
+    let secs = now.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time_of_day = secs % 86_400;
+
+    let base = *BASE.get_or_init(|| now.subsec_nanos() ^ std::process::id().rotate_left(16));
+    let suffix = base.wrapping_add(COUNTER.fetch_add(1, Ordering::Relaxed)) & 0xffff;
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:04x}",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        suffix
+    )
+}
+
+/// 1970-01-01 からの日数を (年, 月, 日) に変換する
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(name: &str, index: usize) -> TelemetryEvent {
+        TelemetryEvent::StepStarted {
+            step_name: name.to_string(),
+            index,
+            iteration: 0,
+            provider: "anthropic".to_string(),
+        }
+    }
+
+    fn finished(name: &str) -> TelemetryEvent {
+        TelemetryEvent::StepFinished {
+            step_name: name.to_string(),
+            provider: "anthropic".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            stop_reason: StopReason::EndTurn,
+            token_usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 50,
+                ..Default::default()
+            },
+            cost_usd: Some(0.0025),
+            duration: Duration::from_secs(2),
+        }
+    }
+
+    fn attempt_failed(name: &str, attempt: u32) -> TelemetryEvent {
+        TelemetryEvent::AttemptFailed {
+            step_name: name.to_string(),
+            attempt,
+            error: format!("error {}", attempt),
+        }
+    }
+
+    fn workflow_result(status: ExecutionStatus) -> WorkflowResult {
+        WorkflowResult {
+            workflow_name: "test".to_string(),
+            run_id: "run".to_string(),
+            status,
+            steps: vec![],
+            start_time: SystemTime::now(),
+            end_time: SystemTime::now(),
+            total_duration: Duration::from_secs(10),
+            total_tokens_used: 0,
+            total_cost_usd: 0.0,
+            loops: vec![],
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_step_finished() {
+        let mut collector = TelemetryCollector::new("test");
+        collector.record(started("plan", 0));
+        collector.record(finished("plan"));
+
+        let step = &collector.steps()[0];
+        assert_eq!(step.step_name, "plan");
+        assert_eq!(step.status, StepStatus::Success);
+        assert_eq!(step.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(step.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(step.token_usage.total(), 150);
+        assert_eq!(step.cost_usd, Some(0.0025));
+        assert!(step.started_at.is_some());
+    }
+
+    #[test]
+    fn test_step_retried_records_each_attempt() {
+        let mut collector = TelemetryCollector::new("test");
+        collector.record(started("plan", 0));
+        collector.record(attempt_failed("plan", 0));
+        collector.record(attempt_failed("plan", 1));
+        collector.record(finished("plan"));
+
+        let step = &collector.steps()[0];
+        assert_eq!(step.status, StepStatus::Retried { attempts: 2 });
+        assert_eq!(step.retry_count, 2);
+        assert_eq!(step.attempt_errors, vec!["error 0", "error 1"]);
+    }
+
+    #[test]
+    fn test_step_failed() {
+        let mut collector = TelemetryCollector::new("test");
+        collector.record(started("plan", 0));
+        collector.record(attempt_failed("plan", 0));
+        collector.record(attempt_failed("plan", 1));
+        collector.record(TelemetryEvent::StepFailed {
+            step_name: "plan".to_string(),
+            error: "error 1".to_string(),
+            token_usage: TokenUsage {
+                input_tokens: 30,
+                output_tokens: 10,
+                ..Default::default()
+            },
+            cost_usd: Some(0.0004),
+            duration: Duration::from_secs(4),
+        });
+
+        let step = &collector.steps()[0];
+        assert_eq!(step.status, StepStatus::Failed);
+        assert_eq!(step.retry_count, 1);
+        assert_eq!(step.error.as_deref(), Some("error 1"));
+        assert_eq!(step.token_usage.total(), 40);
+        assert_eq!(step.cost_usd, Some(0.0004));
+        assert!(step.model.is_none());
+    }
+
+    #[test]
+    fn test_finish_builds_run_record() {
+        let mut collector = TelemetryCollector::new("test");
+        let run_id = collector.run_id().to_string();
+
+        collector.record(started("plan", 0));
+        collector.record(finished("plan"));
+        collector.record(TelemetryEvent::StepSkipped {
+            step_name: "review".to_string(),
+            index: 1,
+            iteration: 0,
+            provider: "openai".to_string(),
+            reason: "条件を満たさないためスキップ".to_string(),
+        });
+
+        let record = collector.finish(&workflow_result(ExecutionStatus::Success));
+
+        assert_eq!(record.run_id, run_id);
+        assert_eq!(record.workflow_name, "test");
+        assert_eq!(record.status, ExecutionStatus::Success);
+        assert_eq!(record.steps.len(), 2);
+        assert_eq!(record.steps[1].status, StepStatus::Skipped);
+        assert!(record.steps[1].skip_reason.is_some());
+        assert_eq!(record.kpis.total_tokens, 150);
+        assert_eq!(record.kpis.total_cost_usd, 0.0025);
+        assert_eq!(record.steps[1].cost_usd, None);
+        assert_eq!(record.kpis.completed_steps, 1);
+        assert_eq!(record.kpis.skipped_steps, 1);
+        assert_eq!(record.kpis.total_duration, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_finish_flushes_unfinished_steps() {
+        let mut collector = TelemetryCollector::new("test");
+        collector.record(started("plan", 0));
+
+        let record = collector.finish(&workflow_result(ExecutionStatus::Failed));
+
+        assert_eq!(record.steps.len(), 1);
+        assert_eq!(record.steps[0].status, StepStatus::Failed);
+    }
+
+    #[test]
+    fn test_generate_run_id_format() {
+        let id = generate_run_id();
+
+        assert_eq!(id.len(), "YYYYMMDD-HHMMSS-xxxx".len());
+        assert_eq!(&id[8..9], "-");
+        assert_eq!(&id[15..16], "-");
+        assert!(id[16..].chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_generate_run_id_is_unique_in_process() {
+        let ids: std::collections::HashSet<String> = (0..1000).map(|_| generate_run_id()).collect();
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_377), (2025, 10, 16));
+    }
+}
@@ -0,0 +1,35 @@
+//! 実行記録のエクスポーター
+//!
+//! # 責務
+//!
+//! - エクスポーターの共通インターフェース [`TelemetryExporter`] を定義
+//! - 出力形式ごとの実装を提供
+//!
+//! # モジュール構成
+//!
+//! - [`json`][]: 1実行1ファイルの JSON 出力（[`JsonExporter`]）
+//! - `duckdb`: ローカルの DuckDB ファイルへの正規化出力（`DuckDbExporter`、`duckdb` フィーチャー）
+
+pub mod json;
+#[cfg(feature = "duckdb")]
+pub mod duckdb;
+
+pub use json::JsonExporter;
+#[cfg(feature = "duckdb")]
+pub use self::duckdb::DuckDbExporter;
+
+use crate::error::TelemetryError;
+use super::collector::RunRecord;
+
+/// 実行記録のエクスポーター
+///
+/// ワークフローの実行終了時に、[`RunRecord`] を受け取って永続化します。
+/// エクスポートの失敗はワークフローの結果には影響しません（実行エンジンが警告を出力します）。
+pub trait TelemetryExporter: Send + Sync {
+    /// 実行記録を出力する
+    ///
+    /// # エラー
+    ///
+    /// - [`TelemetryError`] - 出力先への書き込みに失敗した場合
+    fn export(&self, record: &RunRecord) -> Result<(), TelemetryError>;
+}
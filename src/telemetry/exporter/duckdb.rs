@@ -0,0 +1,507 @@
+//! DuckDB エクスポーター
+//!
+//! # 責務
+//!
+//! 実行記録をローカルの `.duckdb` ファイルに正規化して追記する。
+//! サーバーを立てずに、数か月分のワークフロー履歴を SQL で分析できるようにする。
+//!
+//! `duckdb` フィーチャーを有効にした場合のみ利用できます。
+//!
+//! ```bash
+//! cargo build --features duckdb
+//! ```
+//!
+//! # テーブル構成
+//!
+//! | テーブル   | 主キー                                          | 内容                         |
+//! |-----------|------------------------------------------------|------------------------------|
+//! | `runs`     | `run_id`                                       | 1実行分の記録と KPI            |
+//! | `steps`    | `run_id`, `step_index`, `iteration`            | ステップごとの記録             |
+//! | `attempts` | `run_id`, `step_index`, `iteration`, `attempt` | 失敗した試行ごとのエラー内容     |
+//!
+//! `iteration` はループ（`[[loops]]`）内の繰り返し回数（0始まり）で、ループ外のステップは 0 です。
+//!
+//! スキーマは `schema_migrations` テーブルでバージョン管理され、
+//! [`DuckDbExporter::open`] 時に未適用のマイグレーションが順に適用されます。
+//!
+//! # クエリ例
+//!
+//! ```sql
+//! -- ワークフローごとの平均実行時間とトークン数
+//! SELECT workflow_name, avg(total_duration_ms), avg(total_tokens)
+//! FROM runs
+//! GROUP BY workflow_name;
+//!
+//! -- 月ごと・モデルごとのコスト
+//! SELECT date_trunc('month', started_at) AS month, model, sum(cost_usd)
+//! FROM steps
+//! GROUP BY month, model
+//! ORDER BY month;
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ::duckdb::{Connection, params};
+
+use crate::engine::{ExecutionStatus, StepStatus};
+use crate::error::TelemetryError;
+use crate::telemetry::collector::RunRecord;
+use super::TelemetryExporter;
+
+/// スキーママイグレーション
+///
+/// インデックス + 1 がスキーマバージョンになります。
+/// 既存のマイグレーションは変更せず、末尾に追加してください。
+const MIGRATIONS: &[&str] = &[
+    // v1: runs / steps / attempts
+    r#"
+    CREATE TABLE runs (
+        run_id              VARCHAR PRIMARY KEY,
+        workflow_name       VARCHAR NOT NULL,
+        status              VARCHAR NOT NULL,
+        started_at          TIMESTAMP NOT NULL,
+        finished_at         TIMESTAMP NOT NULL,
+        total_duration_ms   BIGINT NOT NULL,
+        total_input_tokens  BIGINT NOT NULL,
+        total_output_tokens BIGINT NOT NULL,
+        total_tokens        BIGINT NOT NULL,
+        retry_count         INTEGER NOT NULL,
+        completed_steps     INTEGER NOT NULL,
+        failed_steps        INTEGER NOT NULL,
+        skipped_steps       INTEGER NOT NULL,
+        error               VARCHAR
+    );
+
+    CREATE TABLE steps (
+        run_id        VARCHAR NOT NULL,
+        step_index    INTEGER NOT NULL,
+        step_name     VARCHAR NOT NULL,
+        provider      VARCHAR NOT NULL,
+        status        VARCHAR NOT NULL,
+        model         VARCHAR,
+        stop_reason   VARCHAR,
+        input_tokens  BIGINT NOT NULL,
+        output_tokens BIGINT NOT NULL,
+        started_at    TIMESTAMP,
+        duration_ms   BIGINT NOT NULL,
+        retry_count   INTEGER NOT NULL,
+        error         VARCHAR,
+        PRIMARY KEY (run_id, step_index)
+    );
+
+    CREATE TABLE attempts (
+        run_id     VARCHAR NOT NULL,
+        step_index INTEGER NOT NULL,
+        attempt    INTEGER NOT NULL,
+        error      VARCHAR NOT NULL,
+        PRIMARY KEY (run_id, step_index, attempt)
+    );
+    "#,
+    // v2: ステップのスキップ理由
+    r#"
+    ALTER TABLE steps ADD COLUMN skip_reason VARCHAR;
+    "#,
+    // v3: ループの繰り返し回数と修正回数（主キーの変更のため steps / attempts を作り直す）
+    r#"
+    ALTER TABLE runs ADD COLUMN revision_count INTEGER DEFAULT 0;
+
+    CREATE TABLE steps_v3 (
+        run_id        VARCHAR NOT NULL,
+        step_index    INTEGER NOT NULL,
+        iteration     INTEGER NOT NULL,
+        step_name     VARCHAR NOT NULL,
+        provider      VARCHAR NOT NULL,
+        status        VARCHAR NOT NULL,
+        model         VARCHAR,
+        stop_reason   VARCHAR,
+        input_tokens  BIGINT NOT NULL,
+        output_tokens BIGINT NOT NULL,
+        started_at    TIMESTAMP,
+        duration_ms   BIGINT NOT NULL,
+        retry_count   INTEGER NOT NULL,
+        error         VARCHAR,
+        skip_reason   VARCHAR,
+        PRIMARY KEY (run_id, step_index, iteration)
+    );
+    INSERT INTO steps_v3
+    SELECT run_id, step_index, 0, step_name, provider, status, model, stop_reason,
+           input_tokens, output_tokens, started_at, duration_ms, retry_count, error, skip_reason
+    FROM steps;
+    DROP TABLE steps;
+    ALTER TABLE steps_v3 RENAME TO steps;
+
+    CREATE TABLE attempts_v3 (
+        run_id     VARCHAR NOT NULL,
+        step_index INTEGER NOT NULL,
+        iteration  INTEGER NOT NULL,
+        attempt    INTEGER NOT NULL,
+        error      VARCHAR NOT NULL,
+        PRIMARY KEY (run_id, step_index, iteration, attempt)
+    );
+    INSERT INTO attempts_v3
+    SELECT run_id, step_index, 0, attempt, error FROM attempts;
+    DROP TABLE attempts;
+    ALTER TABLE attempts_v3 RENAME TO attempts;
+    "#,
+    // v4: コスト（米ドル）
+    r#"
+    ALTER TABLE runs ADD COLUMN total_cost_usd DOUBLE DEFAULT 0;
+    ALTER TABLE steps ADD COLUMN cost_usd DOUBLE;
+    "#,
+    // v5: キャッシュ・推論トークン数（total_tokens は入力・キャッシュ・出力の和）
+    r#"
+    ALTER TABLE runs ADD COLUMN total_cache_read_tokens BIGINT DEFAULT 0;
+    ALTER TABLE runs ADD COLUMN total_cache_creation_tokens BIGINT DEFAULT 0;
+    ALTER TABLE runs ADD COLUMN total_reasoning_tokens BIGINT DEFAULT 0;
+    ALTER TABLE steps ADD COLUMN cache_read_tokens BIGINT DEFAULT 0;
+    ALTER TABLE steps ADD COLUMN cache_creation_tokens BIGINT DEFAULT 0;
+    ALTER TABLE steps ADD COLUMN reasoning_tokens BIGINT DEFAULT 0;
+    "#,
+];
+
+/// DuckDB エクスポーター
+///
+/// 同じ `run_id` の記録を再度エクスポートした場合は、既存の行を置き換えます。
+///
+/// # 例
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use melted_adw::config::workflow::Workflow;
+/// use melted_adw::engine::WorkflowExecutor;
+/// use melted_adw::telemetry::DuckDbExporter;
+///
+/// let workflow = Workflow::from_file("workflow.toml").unwrap();
+/// let exporter = DuckDbExporter::open("telemetry/adw.duckdb").unwrap();
+/// let executor = WorkflowExecutor::new(workflow).with_exporter(Arc::new(exporter));
+/// ```
+pub struct DuckDbExporter {
+    path: Option<PathBuf>,
+    conn: Mutex<Connection>,
+}
+
+impl DuckDbExporter {
+    /// データベースファイルを開き（存在しなければ作成し）、マイグレーションを適用
+    ///
+    /// # エラー
+    ///
+    /// - [`TelemetryError::Io`] - 親ディレクトリの作成に失敗した場合
+    /// - [`TelemetryError::DuckDb`] - データベースのオープンまたはマイグレーションに失敗した場合
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, TelemetryError> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(&path)?;
+        Self::with_connection(Some(path), conn)
+    }
+
+    /// インメモリデータベースで作成（主にテスト用）
+    pub fn open_in_memory() -> Result<Self, TelemetryError> {
+        let conn = Connection::open_in_memory()?;
+        Self::with_connection(None, conn)
+    }
+
+    /// データベースファイルのパスを取得（インメモリの場合は `None`）
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 適用済みのスキーマバージョンを取得
+    pub fn schema_version(&self) -> Result<usize, TelemetryError> {
+        current_version(&self.lock())
+    }
+
+    fn with_connection(path: Option<PathBuf>, mut conn: Connection) -> Result<Self, TelemetryError> {
+        migrate(&mut conn)?;
+        Ok(Self {
+            path,
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        // 書き込み途中のパニックはトランザクションがロールバックされるため、そのまま続行できる
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl TelemetryExporter for DuckDbExporter {
+    fn export(&self, record: &RunRecord) -> Result<(), TelemetryError> {
+        let mut conn = self.lock();
+        let tx = conn.transaction()?;
+
+        // 同じ run_id の既存記録を置き換える
+        tx.execute("DELETE FROM attempts WHERE run_id = ?", params![record.run_id])?;
+        tx.execute("DELETE FROM steps WHERE run_id = ?", params![record.run_id])?;
+        tx.execute("DELETE FROM runs WHERE run_id = ?", params![record.run_id])?;
+
+        let kpis = &record.kpis;
+        tx.execute(
+            "INSERT INTO runs (
+                run_id, workflow_name, status, started_at, finished_at, total_duration_ms,
+                total_input_tokens, total_output_tokens, total_tokens, retry_count,
+                completed_steps, failed_steps, skipped_steps, error, revision_count, total_cost_usd,
+                total_cache_read_tokens, total_cache_creation_tokens, total_reasoning_tokens
+            ) VALUES (?, ?, ?, to_timestamp(?), to_timestamp(?), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                record.run_id,
+                record.workflow_name,
+                execution_status_label(&record.status),
+                epoch_secs(record.started_at),
+                epoch_secs(record.finished_at),
+                kpis.total_duration.as_millis() as i64,
+                kpis.total_input_tokens as i64,
+                kpis.total_output_tokens as i64,
+                kpis.total_tokens as i64,
+                kpis.retry_count,
+                kpis.completed_steps as i64,
+                kpis.failed_steps as i64,
+                kpis.skipped_steps as i64,
+                record.error,
+                kpis.revision_count,
+                kpis.total_cost_usd,
+                kpis.total_cache_read_tokens as i64,
+                kpis.total_cache_creation_tokens as i64,
+                kpis.total_reasoning_tokens as i64,
+            ],
+        )?;
+
+        for step in &record.steps {
+            tx.execute(
+                "INSERT INTO steps (
+                    run_id, step_index, iteration, step_name, provider, status, model, stop_reason,
+                    input_tokens, output_tokens, started_at, duration_ms, retry_count, error,
+                    skip_reason, cost_usd, cache_read_tokens, cache_creation_tokens, reasoning_tokens
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, to_timestamp(?), ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    record.run_id,
+                    step.index as i64,
+                    step.iteration,
+                    step.step_name,
+                    step.provider,
+                    step_status_label(&step.status),
+                    step.model,
+                    step.stop_reason.map(|r| format!("{:?}", r)),
+                    i64::from(step.token_usage.input_tokens),
+                    i64::from(step.token_usage.output_tokens),
+                    step.started_at.map(epoch_secs),
+                    step.duration.as_millis() as i64,
+                    step.retry_count,
+                    step.error,
+                    step.skip_reason,
+                    step.cost_usd,
+                    i64::from(step.token_usage.cache_read_tokens),
+                    i64::from(step.token_usage.cache_creation_tokens),
+                    i64::from(step.token_usage.reasoning_tokens),
+                ],
+            )?;
+
+            for (attempt, error) in step.attempt_errors.iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO attempts (run_id, step_index, iteration, attempt, error) VALUES (?, ?, ?, ?, ?)",
+                    params![record.run_id, step.index as i64, step.iteration, attempt as i64, error],
+                )?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// 未適用のマイグレーションを順に適用
+fn migrate(conn: &mut Connection) -> Result<(), TelemetryError> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version    INTEGER PRIMARY KEY,
+            applied_at TIMESTAMP NOT NULL DEFAULT current_timestamp
+        )",
+    )?;
+
+    let current = current_version(conn)?;
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?)",
+            params![(index + 1) as i64],
+        )?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// 適用済みの最新スキーマバージョン（未適用なら 0）
+fn current_version(conn: &Connection) -> Result<usize, TelemetryError> {
+    let version: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(version as usize)
+}
+
+/// UNIX エポックからの秒数（`to_timestamp` 用）
+fn epoch_secs(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+fn execution_status_label(status: &ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Success => "success",
+        ExecutionStatus::PartialSuccess { .. } => "partial_success",
+        ExecutionStatus::Failed => "failed",
+    }
+}
+
+fn step_status_label(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Success => "success",
+        StepStatus::Retried { .. } => "retried",
+        StepStatus::Failed => "failed",
+        StepStatus::Skipped => "skipped",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::TokenUsage;
+    use crate::telemetry::{StepRecord, WorkflowKpis};
+    use std::time::Duration;
+
+    fn step(index: usize, status: StepStatus, attempt_errors: Vec<&str>) -> StepRecord {
+        StepRecord {
+            step_name: format!("step{}", index),
+            index,
+            iteration: 0,
+            provider: "anthropic".to_string(),
+            status,
+            model: Some("claude-sonnet-4".to_string()),
+            stop_reason: None,
+            token_usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 50,
+                cache_read_tokens: 1000,
+                cache_creation_tokens: 200,
+                reasoning_tokens: 20,
+            },
+            cost_usd: Some(0.0025),
+            started_at: Some(SystemTime::now()),
+            duration: Duration::from_millis(1500),
+            retry_count: attempt_errors.len() as u32,
+            attempt_errors: attempt_errors.into_iter().map(String::from).collect(),
+            error: None,
+            skip_reason: None,
+        }
+    }
+
+    fn record(run_id: &str) -> RunRecord {
+        let steps = vec![
+            step(0, StepStatus::Success, vec![]),
+            step(1, StepStatus::Retried { attempts: 2 }, vec!["rate limit", "timeout"]),
+        ];
+        let kpis = WorkflowKpis::from_steps(&steps, Duration::from_secs(5));
+
+        RunRecord {
+            run_id: run_id.to_string(),
+            workflow_name: "example".to_string(),
+            status: ExecutionStatus::Success,
+            started_at: SystemTime::now(),
+            finished_at: SystemTime::now(),
+            steps,
+            kpis,
+            error: None,
+        }
+    }
+
+    fn count(exporter: &DuckDbExporter, table: &str) -> i64 {
+        exporter
+            .lock()
+            .query_row(&format!("SELECT count(*) FROM {}", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_open_applies_migrations() {
+        let exporter = DuckDbExporter::open_in_memory().unwrap();
+        assert_eq!(exporter.schema_version().unwrap(), MIGRATIONS.len());
+        assert_eq!(count(&exporter, "runs"), 0);
+    }
+
+    #[test]
+    fn test_export_normalizes_record() {
+        let exporter = DuckDbExporter::open_in_memory().unwrap();
+        exporter.export(&record("20250101-000000-0001")).unwrap();
+
+        assert_eq!(count(&exporter, "runs"), 1);
+        assert_eq!(count(&exporter, "steps"), 2);
+        assert_eq!(count(&exporter, "attempts"), 2);
+
+        let (status, total_tokens, total_cost_usd): (String, i64, f64) = exporter
+            .lock()
+            .query_row("SELECT status, total_tokens, total_cost_usd FROM runs", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!(status, "success");
+        assert_eq!(total_tokens, 2700);
+        assert_eq!(total_cost_usd, 0.005);
+
+        // 合計トークン数は入力・キャッシュ・出力の和
+        let sums: (i64, i64, i64, i64, i64) = exporter
+            .lock()
+            .query_row(
+                "SELECT total_input_tokens + total_cache_read_tokens + total_cache_creation_tokens + total_output_tokens,
+                        total_cache_read_tokens, total_cache_creation_tokens, total_reasoning_tokens,
+                        (SELECT sum(cache_read_tokens + cache_creation_tokens + reasoning_tokens) FROM steps)
+                 FROM runs",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .unwrap();
+        assert_eq!(sums, (2700, 2000, 400, 40, 2440));
+
+        let step_cost: f64 = exporter
+            .lock()
+            .query_row("SELECT sum(cost_usd) FROM steps", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(step_cost, 0.005);
+    }
+
+    #[test]
+    fn test_export_same_run_id_replaces_rows() {
+        let exporter = DuckDbExporter::open_in_memory().unwrap();
+        exporter.export(&record("20250101-000000-0001")).unwrap();
+        exporter.export(&record("20250101-000000-0001")).unwrap();
+        exporter.export(&record("20250101-000000-0002")).unwrap();
+
+        assert_eq!(count(&exporter, "runs"), 2);
+        assert_eq!(count(&exporter, "steps"), 4);
+    }
+
+    #[test]
+    fn test_reopen_file_keeps_history() {
+        let dir = std::env::temp_dir().join(format!("adw-duckdb-test-{}", std::process::id()));
+        let path = dir.join("adw.duckdb");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        {
+            let exporter = DuckDbExporter::open(&path).unwrap();
+            exporter.export(&record("20250101-000000-0001")).unwrap();
+        }
+
+        let exporter = DuckDbExporter::open(&path).unwrap();
+        assert_eq!(exporter.schema_version().unwrap(), MIGRATIONS.len());
+        assert_eq!(count(&exporter, "runs"), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+//! JSON エクスポーター
+//!
+//! # 責務
+//!
+//! 実行記録を、出力ディレクトリ配下に1実行1ファイルの JSON として書き出す。
+//!
+//! # 出力先
+//!
+//! ```text
+//! telemetry/
+//! ├── 20251016-093012-3f2a.json
+//! └── 20251016-101544-91c0.json
+//! ```
+//!
+//! ファイル名は実行IDのため、ディレクトリを名前順に並べると時系列順になります。
+
+use std::path::{Path, PathBuf};
+
+use crate::error::TelemetryError;
+use super::TelemetryExporter;
+use super::super::collector::RunRecord;
+
+/// JSON エクスポーター
+#[derive(Debug, Clone)]
+pub struct JsonExporter {
+    /// 出力ディレクトリ
+    dir: PathBuf,
+}
+
+impl JsonExporter {
+    /// 出力ディレクトリを指定してエクスポーターを生成
+    ///
+    /// ディレクトリは最初のエクスポート時に作成されます。
+    ///
+    /// # 例
+    ///
+    /// ```rust
+    /// use melted_adw::telemetry::JsonExporter;
+    ///
+    /// let exporter = JsonExporter::new("telemetry");
+    /// ```
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 出力ディレクトリを取得
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 実行記録の出力先パスを取得
+    pub fn path_for(&self, record: &RunRecord) -> PathBuf {
+        self.dir.join(format!("{}.json", record.run_id))
+    }
+}
+
+impl TelemetryExporter for JsonExporter {
+    fn export(&self, record: &RunRecord) -> Result<(), TelemetryError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let json = serde_json::to_string_pretty(record)?;
+        std::fs::write(self.path_for(record), json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ExecutionStatus;
+    use crate::telemetry::WorkflowKpis;
+    use std::time::{Duration, SystemTime};
+
+    fn create_record(run_id: &str) -> RunRecord {
+        RunRecord {
+            run_id: run_id.to_string(),
+            workflow_name: "test_workflow".to_string(),
+            status: ExecutionStatus::Success,
+            started_at: SystemTime::now(),
+            finished_at: SystemTime::now(),
+            steps: vec![],
+            kpis: WorkflowKpis::from_steps(&[], Duration::from_secs(1)),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_export_writes_one_file_per_run() {
+        let dir = std::env::temp_dir().join("adw_json_exporter_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let exporter = JsonExporter::new(&dir);
+
+        exporter.export(&create_record("20250101-000000-0001")).unwrap();
+        exporter.export(&create_record("20250101-000000-0002")).unwrap();
+
+        let mut files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["20250101-000000-0001.json", "20250101-000000-0002.json"]);
+
+        let content = std::fs::read_to_string(dir.join("20250101-000000-0001.json")).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json["workflow_name"], "test_workflow");
+        assert_eq!(json["status"], "Success");
+        assert!(json["kpis"]["total_tokens"].is_number());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_path_for() {
+        let exporter = JsonExporter::new("telemetry");
+        let path = exporter.path_for(&create_record("20250101-000000-abcd"));
+
+        assert_eq!(path, PathBuf::from("telemetry/20250101-000000-abcd.json"));
+        assert_eq!(exporter.dir(), Path::new("telemetry"));
+    }
+}
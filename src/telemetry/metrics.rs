@@ -0,0 +1,195 @@
+//! KPI（重要業績評価指標）の定義
+//!
+//! # 責務
+//!
+//! ワークフロー改善のための指標を、実行記録から算出する。
+//!
+//! | KPI        | フィールド                                   | 目標       |
+//! |------------|---------------------------------------------|-----------|
+//! | 実行速度   | `total_duration`                            | 短いほど良い |
+//! | 修正回数   | `retry_count`                               | 0 が理想    |
+//! | 実行コスト | `total_tokens`（入力・キャッシュ・出力の内訳あり）、`total_cost_usd` | 少ないほど良い |
+//!
+//! # 使用例
+//!
+//! ```rust
+//! use melted_adw::telemetry::{StepRecord, WorkflowKpis};
+//! use std::time::Duration;
+//!
+//! let steps: Vec<StepRecord> = vec![];
+//! let kpis = WorkflowKpis::from_steps(&steps, Duration::from_secs(10));
+//! assert_eq!(kpis.total_tokens, 0);
+//! ```
+
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::engine::StepStatus;
+use crate::engine::result::total_cost;
+use super::collector::StepRecord;
+
+/// ワークフロー1実行あたりの KPI
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkflowKpis {
+    /// 総実行時間（実行速度）
+    pub total_duration: Duration,
+
+    /// 入力トークン数の合計
+    pub total_input_tokens: u64,
+
+    /// 出力トークン数の合計
+    pub total_output_tokens: u64,
+
+    /// キャッシュから読み込んだ入力トークン数の合計
+    pub total_cache_read_tokens: u64,
+
+    /// キャッシュに書き込んだ入力トークン数の合計
+    pub total_cache_creation_tokens: u64,
+
+    /// 推論トークン数の合計（出力トークンの内数）
+    pub total_reasoning_tokens: u64,
+
+    /// 総トークン数（実行コスト、キャッシュの読み込み・書き込みを含む）
+    pub total_tokens: u64,
+
+    /// コストの合計（米ドル、単価が不明なモデルの分は含まない）
+    pub total_cost_usd: f64,
+
+    /// 全ステップのリトライ回数の合計
+    pub retry_count: u32,
+
+    /// 完了したステップ数（リトライ後の成功を含む）
+    pub completed_steps: usize,
+
+    /// 失敗したステップ数
+    pub failed_steps: usize,
+
+    /// スキップされたステップ数
+    pub skipped_steps: usize,
+
+    /// 修正回数（ループの2回目以降の実行回数の合計）
+    ///
+    /// ステップ記録からは算出できないため、[`WorkflowResult::revision_count`] の値を設定します。
+    ///
+    /// [`WorkflowResult::revision_count`]: crate::engine::WorkflowResult::revision_count
+    pub revision_count: u32,
+}
+
+impl WorkflowKpis {
+    /// ステップ記録から KPI を算出
+    ///
+    /// # 引数
+    ///
+    /// - `steps`: ステップ記録
+    /// - `total_duration`: ワークフロー全体の実行時間
+    pub fn from_steps(steps: &[StepRecord], total_duration: Duration) -> Self {
+        let mut kpis = Self {
+            total_duration,
+            total_input_tokens: 0,
+            total_output_tokens: 0,
+            total_cache_read_tokens: 0,
+            total_cache_creation_tokens: 0,
+            total_reasoning_tokens: 0,
+            total_tokens: 0,
+            total_cost_usd: total_cost(steps.iter().map(|step| step.cost_usd)),
+            retry_count: 0,
+            completed_steps: 0,
+            failed_steps: 0,
+            skipped_steps: 0,
+            revision_count: 0,
+        };
+
+        for step in steps {
+            let usage = &step.token_usage;
+            kpis.total_input_tokens = kpis.total_input_tokens.saturating_add(u64::from(usage.input_tokens));
+            kpis.total_output_tokens = kpis.total_output_tokens.saturating_add(u64::from(usage.output_tokens));
+            kpis.total_cache_read_tokens = kpis.total_cache_read_tokens.saturating_add(u64::from(usage.cache_read_tokens));
+            kpis.total_cache_creation_tokens =
+                kpis.total_cache_creation_tokens.saturating_add(u64::from(usage.cache_creation_tokens));
+            kpis.total_reasoning_tokens = kpis.total_reasoning_tokens.saturating_add(u64::from(usage.reasoning_tokens));
+            kpis.total_tokens = kpis.total_tokens.saturating_add(usage.total());
+            kpis.retry_count += step.retry_count;
+
+            match step.status {
+                StepStatus::Success | StepStatus::Retried { .. } => kpis.completed_steps += 1,
+                StepStatus::Failed => kpis.failed_steps += 1,
+                StepStatus::Skipped => kpis.skipped_steps += 1,
+            }
+        }
+
+        kpis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::TokenUsage;
+
+    fn step(status: StepStatus, input: u32, output: u32, retries: u32) -> StepRecord {
+        StepRecord {
+            step_name: "step".to_string(),
+            index: 0,
+            iteration: 0,
+            provider: "anthropic".to_string(),
+            status,
+            model: None,
+            stop_reason: None,
+            token_usage: TokenUsage {
+                input_tokens: input,
+                output_tokens: output,
+                ..Default::default()
+            },
+            cost_usd: None,
+            started_at: None,
+            duration: Duration::from_secs(1),
+            retry_count: retries,
+            attempt_errors: vec![],
+            error: None,
+            skip_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_kpis_from_steps() {
+        let mut cached = step(StepStatus::Success, 10, 5, 0);
+        cached.token_usage.cache_read_tokens = 1000;
+        cached.token_usage.cache_creation_tokens = 200;
+        cached.token_usage.reasoning_tokens = 3;
+        let mut priced = step(StepStatus::Success, 100, 50, 0);
+        priced.cost_usd = Some(0.25);
+        let mut failed = step(StepStatus::Failed, 0, 0, 1);
+        failed.cost_usd = Some(0.5);
+        let steps = vec![
+            priced,
+            step(StepStatus::Retried { attempts: 2 }, 200, 100, 2),
+            failed,
+            step(StepStatus::Skipped, 0, 0, 0),
+            cached,
+        ];
+
+        let kpis = WorkflowKpis::from_steps(&steps, Duration::from_secs(30));
+
+        assert_eq!(kpis.total_duration, Duration::from_secs(30));
+        assert_eq!(kpis.total_input_tokens, 310);
+        assert_eq!(kpis.total_output_tokens, 155);
+        assert_eq!(kpis.total_cache_read_tokens, 1000);
+        assert_eq!(kpis.total_cache_creation_tokens, 200);
+        assert_eq!(kpis.total_reasoning_tokens, 3);
+        assert_eq!(kpis.total_tokens, 1665);
+        assert_eq!(kpis.total_cost_usd, 0.75);
+        assert_eq!(kpis.retry_count, 3);
+        assert_eq!(kpis.completed_steps, 3);
+        assert_eq!(kpis.failed_steps, 1);
+        assert_eq!(kpis.skipped_steps, 1);
+    }
+
+    #[test]
+    fn test_kpis_empty() {
+        let kpis = WorkflowKpis::from_steps(&[], Duration::from_secs(0));
+
+        assert_eq!(kpis.total_tokens, 0);
+        assert_eq!(kpis.total_cost_usd, 0.0);
+        assert_eq!(kpis.completed_steps, 0);
+    }
+}